@@ -0,0 +1,112 @@
+-- Hex (EVM) and bech32 (ada, luna) addresses don't depend on case, so they are stored lowercased.
+-- The form an address was submitted in is kept in display_address.
+BEGIN;
+    ALTER TABLE addresses ADD COLUMN display_address TEXT;
+    UPDATE addresses SET display_address = address;
+    ALTER TABLE addresses ALTER COLUMN display_address SET NOT NULL;
+
+    CREATE TEMPORARY TABLE case_insensitive_networks ON COMMIT DROP AS
+        SELECT network_id FROM networks
+        WHERE network_name IN ('eth', 'bsc', 'avax', 'matic', 'ftm', 'movr', 'ada', 'luna');
+
+    -- A checksummed spelling is preferred as the display form of a merged address.
+    INSERT INTO addresses (network_id, address, display_address)
+        SELECT DISTINCT ON (a.network_id, lower(a.address)) a.network_id, lower(a.address), a.address
+        FROM addresses a
+        WHERE a.network_id IN (SELECT network_id FROM case_insensitive_networks)
+        ORDER BY a.network_id, lower(a.address),
+            a.address = lower(a.address), a.address ~ '^0x[0-9A-F]+$', a.address
+    ON CONFLICT (network_id, address) DO UPDATE SET display_address = EXCLUDED.display_address;
+
+    UPDATE holder_totals
+        SET holder_address = lower(holder_address), contract_address = lower(contract_address)
+        WHERE network_id IN (SELECT network_id FROM case_insensitive_networks);
+    UPDATE holder_descriptions
+        SET holder_address = lower(holder_address), contract_address = lower(contract_address)
+        WHERE network_id IN (SELECT network_id FROM case_insensitive_networks);
+    UPDATE scam_token_creators
+        SET address = lower(address), scammed_contract_address = lower(scammed_contract_address)
+        WHERE network_of_scammed_token IN (SELECT network_id FROM case_insensitive_networks);
+    UPDATE legit_token_creators
+        SET address = lower(address), legit_contract_address = lower(legit_contract_address)
+        WHERE network_of_legit_token IN (SELECT network_id FROM case_insensitive_networks);
+    UPDATE scam_tokens
+        SET address = lower(address), scam_creator_address = lower(scam_creator_address)
+        WHERE scam_creator_network IN (SELECT network_id FROM case_insensitive_networks);
+    UPDATE legit_tokens
+        SET address = lower(address), creator_address = lower(creator_address)
+        WHERE creator_network IN (SELECT network_id FROM case_insensitive_networks);
+    UPDATE address_token_names
+        SET address = lower(address)
+        WHERE network_id IN (SELECT network_id FROM case_insensitive_networks);
+    UPDATE holders
+        SET address = lower(address)
+        WHERE network_id IN (SELECT network_id FROM case_insensitive_networks);
+
+    -- A holder listed twice in the same snapshot keeps its best place.
+    DELETE FROM holder_totals h
+        USING holder_totals d
+        WHERE h.network_id = d.network_id
+            AND h.contract_address = d.contract_address
+            AND h.holder_address = d.holder_address
+            AND h.checked_on = d.checked_on
+            AND (h.place, h.transaction_id) > (d.place, d.transaction_id);
+
+    -- Descriptions of the same holder are merged into one, keeping all of their notes and types.
+    CREATE TEMPORARY TABLE merged_holder_descriptions ON COMMIT DROP AS
+        SELECT h.network_id, h.holder_address, h.contract_address,
+            string_agg(DISTINCT h.notes, E'\n') AS notes,
+            ARRAY(
+                SELECT DISTINCT t
+                FROM holder_descriptions d, unnest(d.address_types) t
+                WHERE d.network_id IS NOT DISTINCT FROM h.network_id
+                    AND d.holder_address = h.holder_address
+                    AND d.contract_address = h.contract_address
+                ORDER BY t
+            ) AS address_types
+        FROM holder_descriptions h
+        GROUP BY h.network_id, h.holder_address, h.contract_address
+        HAVING count(*) > 1;
+    DELETE FROM holder_descriptions h
+        USING merged_holder_descriptions m
+        WHERE h.network_id IS NOT DISTINCT FROM m.network_id
+            AND h.holder_address = m.holder_address
+            AND h.contract_address = m.contract_address;
+    INSERT INTO holder_descriptions (network_id, holder_address, contract_address, notes, address_types)
+        SELECT network_id, holder_address, contract_address, notes, address_types
+        FROM merged_holder_descriptions;
+
+    CREATE TEMPORARY TABLE merged_scam_token_creators ON COMMIT DROP AS
+        SELECT address, network_of_scammed_token, scammed_contract_address,
+            string_agg(DISTINCT notes, E'\n') AS notes
+        FROM scam_token_creators
+        GROUP BY address, network_of_scammed_token, scammed_contract_address
+        HAVING count(*) > 1;
+    DELETE FROM scam_token_creators s
+        USING merged_scam_token_creators m
+        WHERE s.address = m.address
+            AND s.network_of_scammed_token = m.network_of_scammed_token
+            AND s.scammed_contract_address = m.scammed_contract_address;
+    INSERT INTO scam_token_creators (address, notes, network_of_scammed_token, scammed_contract_address)
+        SELECT address, notes, network_of_scammed_token, scammed_contract_address
+        FROM merged_scam_token_creators;
+
+    CREATE TEMPORARY TABLE merged_legit_token_creators ON COMMIT DROP AS
+        SELECT address, network_of_legit_token, legit_contract_address,
+            string_agg(DISTINCT notes, E'\n') AS notes
+        FROM legit_token_creators
+        GROUP BY address, network_of_legit_token, legit_contract_address
+        HAVING count(*) > 1;
+    DELETE FROM legit_token_creators l
+        USING merged_legit_token_creators m
+        WHERE l.address = m.address
+            AND l.network_of_legit_token = m.network_of_legit_token
+            AND l.legit_contract_address = m.legit_contract_address;
+    INSERT INTO legit_token_creators (address, notes, network_of_legit_token, legit_contract_address)
+        SELECT address, notes, network_of_legit_token, legit_contract_address
+        FROM merged_legit_token_creators;
+
+    DELETE FROM addresses
+        WHERE network_id IN (SELECT network_id FROM case_insensitive_networks)
+            AND address <> lower(address);
+COMMIT;
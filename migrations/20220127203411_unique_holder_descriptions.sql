@@ -0,0 +1,38 @@
+-- A holder has one description per contract. Descriptions added since the last merge are folded
+-- together the same way before the constraint goes in.
+BEGIN;
+    CREATE TEMPORARY TABLE merged_holder_descriptions ON COMMIT DROP AS
+        SELECT h.network_id, h.holder_address, h.contract_address,
+            string_agg(DISTINCT h.notes, E'\n') AS notes,
+            ARRAY(
+                SELECT DISTINCT t
+                FROM holder_descriptions d, unnest(d.address_types) t
+                WHERE d.network_id IS NOT DISTINCT FROM h.network_id
+                    AND d.holder_address = h.holder_address
+                    AND d.contract_address = h.contract_address
+                ORDER BY t
+            ) AS address_types
+        FROM holder_descriptions h
+        GROUP BY h.network_id, h.holder_address, h.contract_address
+        HAVING count(*) > 1;
+    DELETE FROM holder_descriptions h
+        USING merged_holder_descriptions m
+        WHERE h.network_id IS NOT DISTINCT FROM m.network_id
+            AND h.holder_address = m.holder_address
+            AND h.contract_address = m.contract_address;
+    INSERT INTO holder_descriptions (network_id, holder_address, contract_address, notes, address_types)
+        SELECT network_id, holder_address, contract_address, notes, address_types
+        FROM merged_holder_descriptions;
+
+    UPDATE holder_descriptions SET address_types = '{}' WHERE address_types IS NULL;
+    ALTER TABLE holder_descriptions ALTER COLUMN address_types SET DEFAULT '{}';
+    ALTER TABLE holder_descriptions ALTER COLUMN address_types SET NOT NULL;
+    -- A description without a network can't be told apart from the same holder on another chain,
+    -- so it is dropped rather than guessed.
+    DELETE FROM holder_descriptions WHERE network_id IS NULL;
+    ALTER TABLE holder_descriptions ALTER COLUMN network_id SET NOT NULL;
+
+    ALTER TABLE holder_descriptions
+        ADD CONSTRAINT holder_descriptions_holder_contract_key
+            UNIQUE (network_id, holder_address, contract_address);
+COMMIT;
@@ -0,0 +1,18 @@
+-- Every change to a holder description. previous_* is NULL when the description was created and
+-- new_* is NULL when it was deleted.
+CREATE TABLE holder_description_history
+(
+    history_id             SERIAL UNIQUE PRIMARY KEY,
+    network_id             INTEGER     NOT NULL REFERENCES networks (network_id),
+    holder_address         TEXT        NOT NULL,
+    contract_address       TEXT        NOT NULL,
+    previous_notes         TEXT,
+    previous_address_types TEXT[],
+    new_notes              TEXT,
+    new_address_types      TEXT[],
+    changed_by             TEXT,
+    changed_on             timestamptz NOT NULL
+);
+
+CREATE INDEX holder_description_history_holder_idx
+    ON holder_description_history (network_id, holder_address, changed_on);
@@ -0,0 +1,5 @@
+-- Dashboards look descriptions up by token and by address type.
+CREATE INDEX holder_descriptions_contract_idx
+    ON holder_descriptions (network_id, contract_address);
+CREATE INDEX holder_descriptions_address_types_idx
+    ON holder_descriptions USING GIN (address_types);
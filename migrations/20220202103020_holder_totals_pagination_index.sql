@@ -0,0 +1,3 @@
+-- Holder pages are read in (checked_on, place, holder_address) order for one contract.
+CREATE INDEX holder_totals_contract_page_idx
+    ON holder_totals (network_id, contract_address, checked_on, place, holder_address);
@@ -0,0 +1,63 @@
+-- One row per holders submission. Older submissions gave every row its own checked_on, so rows
+-- of a contract inserted less than a second apart are grouped into one snapshot and share its
+-- checked_on from now on.
+BEGIN;
+    CREATE TABLE holder_snapshots
+    (
+        snapshot_id      BIGSERIAL UNIQUE PRIMARY KEY,
+        network_id       INTEGER     NOT NULL,
+        contract_address TEXT        NOT NULL,
+        source           TEXT,
+        block_number     BIGINT,
+        checked_on       timestamptz NOT NULL,
+        FOREIGN KEY (network_id, contract_address) REFERENCES addresses (network_id, address)
+    );
+    CREATE INDEX holder_snapshots_contract_idx
+        ON holder_snapshots (network_id, contract_address, checked_on);
+
+    ALTER TABLE holder_totals ADD COLUMN snapshot_id BIGINT REFERENCES holder_snapshots (snapshot_id);
+
+    CREATE TEMPORARY TABLE holder_total_groups ON COMMIT DROP AS
+        SELECT transaction_id, network_id, contract_address,
+            SUM(starts_group) OVER (
+                PARTITION BY network_id, contract_address ORDER BY checked_on, transaction_id
+            ) AS group_number
+        FROM (
+            SELECT transaction_id, network_id, contract_address, checked_on,
+                CASE
+                    WHEN checked_on - LAG(checked_on) OVER (
+                        PARTITION BY network_id, contract_address ORDER BY checked_on, transaction_id
+                    ) < INTERVAL '1 second' THEN 0
+                    ELSE 1
+                END AS starts_group
+            FROM holder_totals
+        ) t;
+
+    CREATE TEMPORARY TABLE grouped_snapshots ON COMMIT DROP AS
+        SELECT g.network_id, g.contract_address, g.group_number,
+            MIN(h.checked_on) AS checked_on,
+            ROW_NUMBER() OVER (
+                ORDER BY MIN(h.checked_on), g.network_id, g.contract_address
+            ) AS snapshot_id
+        FROM holder_total_groups g
+        INNER JOIN holder_totals h ON h.transaction_id = g.transaction_id
+        GROUP BY g.network_id, g.contract_address, g.group_number;
+
+    INSERT INTO holder_snapshots (snapshot_id, network_id, contract_address, checked_on)
+        SELECT snapshot_id, network_id, contract_address, checked_on FROM grouped_snapshots;
+    SELECT setval(
+        'holder_snapshots_snapshot_id_seq', COALESCE(MAX(snapshot_id), 0) + 1, false
+    ) FROM holder_snapshots;
+
+    UPDATE holder_totals h
+        SET snapshot_id = s.snapshot_id, checked_on = s.checked_on
+        FROM holder_total_groups g
+        INNER JOIN grouped_snapshots s
+            ON s.network_id = g.network_id
+            AND s.contract_address = g.contract_address
+            AND s.group_number = g.group_number
+        WHERE h.transaction_id = g.transaction_id;
+
+    ALTER TABLE holder_totals ALTER COLUMN snapshot_id SET NOT NULL;
+    CREATE INDEX holder_totals_snapshot_idx ON holder_totals (snapshot_id, place);
+COMMIT;
@@ -0,0 +1,14 @@
+-- Responses saved per Idempotency-Key so retried requests replay them instead of running again.
+-- A row without a response_status_code belongs to a request that is still running.
+CREATE TABLE idempotency
+(
+    idempotency_key        TEXT        NOT NULL,
+    request_path           TEXT        NOT NULL,
+    request_hash           BYTEA       NOT NULL,
+    response_status_code   SMALLINT,
+    response_header_names  TEXT[],
+    response_header_values TEXT[],
+    response_body          BYTEA,
+    created_on             timestamptz NOT NULL,
+    PRIMARY KEY (idempotency_key, request_path)
+);
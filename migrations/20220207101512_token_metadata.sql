@@ -0,0 +1,16 @@
+-- What is known about a token beyond its name. total_supply is the on-chain integer, before
+-- dividing by decimals.
+CREATE TABLE token_metadata
+(
+    network_id       INTEGER     NOT NULL,
+    contract_address TEXT        NOT NULL,
+    symbol           TEXT,
+    decimals         SMALLINT CHECK (decimals BETWEEN 0 AND 255),
+    total_supply     NUMERIC CHECK (total_supply >= 0 AND total_supply = trunc(total_supply)),
+    updated_on       timestamptz NOT NULL,
+    PRIMARY KEY (network_id, contract_address),
+    FOREIGN KEY (network_id, contract_address) REFERENCES addresses (network_id, address)
+);
+
+-- The on-chain integer balance, set when the token's decimals were known at submission.
+ALTER TABLE holder_totals ADD COLUMN raw_amount NUMERIC;
@@ -0,0 +1,36 @@
+-- One row per token, keyed by its network and contract so tokens sharing a name no longer
+-- collide. It takes over token_metadata, and holders name their token through it instead of
+-- token_names. A token's name is the latest one submitted for its contract.
+BEGIN;
+    ALTER TABLE token_metadata RENAME TO tokens;
+    ALTER TABLE tokens RENAME CONSTRAINT token_metadata_pkey TO tokens_pkey;
+    ALTER TABLE tokens ADD COLUMN token_name TEXT;
+    ALTER TABLE tokens ADD COLUMN creator_address TEXT;
+    ALTER TABLE tokens ADD COLUMN launched_on timestamptz;
+    ALTER TABLE tokens
+        ADD FOREIGN KEY (network_id, creator_address) REFERENCES addresses (network_id, address);
+
+    INSERT INTO tokens (network_id, contract_address, updated_on)
+    SELECT DISTINCT network_id, contract_address, now()
+    FROM holder_totals
+    ON CONFLICT DO NOTHING;
+    UPDATE tokens t
+    SET token_name = latest.token_name
+    FROM (
+        SELECT DISTINCT ON (h.network_id, h.contract_address)
+            h.network_id, h.contract_address, n.token_name
+        FROM holder_totals h
+        INNER JOIN token_names n ON n.token_name_id = h.token_name_id
+        ORDER BY h.network_id, h.contract_address, h.checked_on DESC, h.transaction_id DESC
+    ) latest
+    WHERE t.network_id = latest.network_id AND t.contract_address = latest.contract_address;
+    ALTER TABLE tokens ALTER COLUMN token_name SET NOT NULL;
+    CREATE INDEX tokens_token_name_idx ON tokens (lower(token_name));
+    CREATE INDEX tokens_creator_idx ON tokens (network_id, creator_address);
+
+    ALTER TABLE holder_snapshots
+        ADD FOREIGN KEY (network_id, contract_address) REFERENCES tokens (network_id, contract_address);
+    ALTER TABLE holder_totals DROP COLUMN token_name_id;
+    DROP TABLE address_token_names;
+    DROP TABLE token_names;
+COMMIT;
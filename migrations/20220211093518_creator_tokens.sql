@@ -0,0 +1,47 @@
+-- Tokens also learn their creator from scam and legit reports, so everything a creator deployed
+-- can be listed from tokens. A token known only from a report has no name yet. Legit creator
+-- reports are copied to legit_tokens, which now gets a row for every legit contract.
+BEGIN;
+    ALTER TABLE tokens ALTER COLUMN token_name DROP NOT NULL;
+
+    CREATE TEMPORARY TABLE reported_tokens ON COMMIT DROP AS
+        SELECT network_of_scammed_token AS network_id,
+            scammed_contract_address AS contract_address,
+            address AS creator_address
+        FROM scam_token_creators
+        UNION
+        SELECT network_of_legit_token, legit_contract_address, address FROM legit_token_creators
+        UNION
+        SELECT scam_creator_network, address, scam_creator_address FROM scam_tokens
+        UNION
+        SELECT creator_network, address, creator_address FROM legit_tokens;
+
+    INSERT INTO addresses (network_id, address, display_address)
+    SELECT network_id, contract_address, contract_address FROM reported_tokens
+    UNION
+    SELECT network_id, creator_address, creator_address FROM reported_tokens
+    ON CONFLICT DO NOTHING;
+
+    INSERT INTO tokens (network_id, contract_address, creator_address, updated_on)
+    SELECT DISTINCT ON (network_id, contract_address)
+        network_id, contract_address, creator_address, now()
+    FROM reported_tokens
+    ORDER BY network_id, contract_address, creator_address
+    ON CONFLICT (network_id, contract_address) DO UPDATE
+        SET creator_address = COALESCE(tokens.creator_address, EXCLUDED.creator_address);
+
+    INSERT INTO legit_tokens (address, notes, creator_network, creator_address)
+    SELECT c.legit_contract_address, c.notes, c.network_of_legit_token, c.address
+    FROM legit_token_creators c
+    WHERE NOT EXISTS (
+        SELECT 1 FROM legit_tokens l
+        WHERE l.creator_network = c.network_of_legit_token
+            AND l.address = c.legit_contract_address
+            AND l.creator_address = c.address
+    );
+
+    CREATE INDEX scam_tokens_contract_idx ON scam_tokens (scam_creator_network, address);
+    CREATE INDEX scam_token_creators_contract_idx
+        ON scam_token_creators (network_of_scammed_token, scammed_contract_address);
+    CREATE INDEX legit_tokens_contract_idx ON legit_tokens (creator_network, address);
+COMMIT;
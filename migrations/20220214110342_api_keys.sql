@@ -0,0 +1,11 @@
+-- Keys for the write routes. Only a hash of each key is kept; the key itself is shown once, when
+-- it is minted.
+CREATE TABLE api_keys(
+    api_key_id uuid NOT NULL,
+    PRIMARY KEY (api_key_id),
+    key_hash BYTEA NOT NULL UNIQUE,
+    name TEXT NOT NULL,
+    scopes TEXT[] NOT NULL,
+    created_on timestamptz NOT NULL,
+    revoked_on timestamptz
+);
@@ -0,0 +1,17 @@
+-- Editors who may publish newsletters. Passwords are stored as Argon2id PHC strings.
+CREATE TABLE users(
+    user_id uuid NOT NULL,
+    PRIMARY KEY (user_id),
+    username TEXT NOT NULL UNIQUE,
+    password_hash TEXT NOT NULL,
+    created_on timestamptz NOT NULL
+);
+
+-- Sessions started from the login form. Only a hash of the cookie's token is kept.
+CREATE TABLE user_sessions(
+    session_hash BYTEA NOT NULL,
+    PRIMARY KEY (session_hash),
+    user_id uuid NOT NULL REFERENCES users (user_id) ON DELETE CASCADE,
+    created_on timestamptz NOT NULL,
+    expires_on timestamptz NOT NULL
+);
@@ -0,0 +1,24 @@
+-- Newsletter issues as they were published.
+CREATE TABLE newsletter_issues(
+    newsletter_issue_id uuid NOT NULL,
+    PRIMARY KEY (newsletter_issue_id),
+    title TEXT NOT NULL,
+    text_content TEXT NOT NULL,
+    html_content TEXT NOT NULL,
+    published_on timestamptz NOT NULL
+);
+
+-- One row per issue and recipient. The worker sends `pending` rows once `execute_after` has
+-- passed and leaves them `delivered`, or `failed` when it runs out of retries.
+CREATE TABLE issue_delivery_queue(
+    newsletter_issue_id uuid NOT NULL REFERENCES newsletter_issues (newsletter_issue_id),
+    subscriber_email TEXT NOT NULL,
+    PRIMARY KEY (newsletter_issue_id, subscriber_email),
+    status TEXT NOT NULL DEFAULT 'pending',
+    n_retries SMALLINT NOT NULL DEFAULT 0,
+    execute_after timestamptz NOT NULL,
+    last_error TEXT,
+    delivered_on timestamptz
+);
+CREATE INDEX issue_delivery_queue_pending_idx ON issue_delivery_queue (execute_after)
+    WHERE status = 'pending';
@@ -0,0 +1,13 @@
+-- One row per email Postmark accepted, keyed by the MessageID it answered with. Bounce and
+-- spam-complaint webhooks name that MessageID, and their event is kept on the row.
+CREATE TABLE email_sends(
+    provider_message_id TEXT NOT NULL,
+    PRIMARY KEY (provider_message_id),
+    recipient TEXT NOT NULL,
+    newsletter_issue_id uuid REFERENCES newsletter_issues (newsletter_issue_id),
+    sent_on timestamptz NOT NULL,
+    event TEXT,
+    event_detail TEXT,
+    event_on timestamptz
+);
+CREATE INDEX email_sends_newsletter_issue_id_idx ON email_sends (newsletter_issue_id, recipient);
@@ -0,0 +1,13 @@
+-- Repeated legit creator reports added identical legit_tokens rows. Keep the first of each and
+-- stop new duplicates.
+BEGIN;
+    DELETE FROM legit_tokens a
+    USING legit_tokens b
+    WHERE a.ctid > b.ctid
+        AND a.creator_network = b.creator_network
+        AND a.address = b.address
+        AND a.creator_address = b.creator_address;
+
+    CREATE UNIQUE INDEX legit_tokens_creator_contract_idx
+        ON legit_tokens (creator_network, address, creator_address);
+COMMIT;
@@ -0,0 +1,9 @@
+-- changed_by was whatever the X-Changed-By header said. It now names the API key that made the
+-- change; the header is kept as on_behalf_of. Names recorded so far were self-declared, so they
+-- move there.
+BEGIN;
+    ALTER TABLE holder_description_history
+        ADD COLUMN changed_by_api_key_id uuid REFERENCES api_keys (api_key_id);
+    ALTER TABLE holder_description_history ADD COLUMN on_behalf_of TEXT;
+    UPDATE holder_description_history SET on_behalf_of = changed_by, changed_by = NULL;
+COMMIT;
@@ -0,0 +1,9 @@
+-- Idempotency keys were shared by every caller, so one API key could be sent another key's saved
+-- response. Rows now belong to the key that made the request: its api_key_id, or 'admin'.
+-- Saved responses only live for a day, so the existing ones are dropped rather than guessed at.
+BEGIN;
+    DELETE FROM idempotency;
+    ALTER TABLE idempotency DROP CONSTRAINT idempotency_pkey;
+    ALTER TABLE idempotency ADD COLUMN caller TEXT NOT NULL;
+    ALTER TABLE idempotency ADD PRIMARY KEY (caller, idempotency_key, request_path);
+COMMIT;
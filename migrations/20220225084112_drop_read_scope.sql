@@ -0,0 +1,2 @@
+-- Reads are public, so the read scope granted nothing. Take it off the keys that were given it.
+UPDATE api_keys SET scopes = array_remove(scopes, 'read');
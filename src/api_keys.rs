@@ -0,0 +1,203 @@
+//! Middleware that keeps write routes for callers holding an API key with the right scope. Keys
+//! are sent in the `X-Api-Key` header and looked up by their hash.
+use crate::domain::ApiScope;
+use actix_web::body::BoxBody;
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::error::ErrorInternalServerError;
+use actix_web::http::header::HeaderMap;
+use actix_web::{web, Error, HttpMessage, HttpResponse};
+use blake2::{Blake2b, Digest};
+use futures::future::{ready, LocalBoxFuture, Ready};
+use rand::distributions::Alphanumeric;
+use rand::{thread_rng, Rng};
+use sqlx::PgPool;
+use std::rc::Rc;
+use uuid::Uuid;
+
+pub const API_KEY_HEADER: &str = "X-Api-Key";
+
+/// The key from the configuration. It has every scope, including minting other keys.
+pub struct AdminApiKey(pub String);
+
+/// Who a request was let through as. `RequireScope` puts it in the request's extensions, so
+/// handlers and inner middleware can attribute what the request does.
+#[derive(Clone, Debug)]
+pub struct ApiCaller {
+    /// `None` for the admin key from the configuration.
+    pub api_key_id: Option<Uuid>,
+    pub name: String,
+}
+
+impl ApiCaller {
+    fn admin() -> Self {
+        Self {
+            api_key_id: None,
+            name: "admin".to_string(),
+        }
+    }
+
+    /// The key's id, or `admin` for the admin key.
+    pub fn id(&self) -> String {
+        self.api_key_id
+            .map_or_else(|| "admin".to_string(), |id| id.to_string())
+    }
+}
+
+/// Wrap a resource with this to require a key with `scope` on it.
+#[derive(Clone)]
+pub struct RequireScope(pub ApiScope);
+
+impl<S> Transform<S, ServiceRequest> for RequireScope
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<BoxBody>, Error = Error> + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Transform = RequireScopeMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequireScopeMiddleware {
+            service: Rc::new(service),
+            scope: self.0,
+        }))
+    }
+}
+
+pub struct RequireScopeMiddleware<S> {
+    service: Rc<S>,
+    scope: ApiScope,
+}
+
+impl<S> Service<ServiceRequest> for RequireScopeMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<BoxBody>, Error = Error> + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_web::dev::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let scope = self.scope;
+        Box::pin(async move {
+            let key = match api_key(req.headers()) {
+                Some(key) => key,
+                None => {
+                    return Ok(req.into_response(
+                        HttpResponse::Unauthorized()
+                            .body(format!("Send an API key in the {} header.", API_KEY_HEADER)),
+                    ))
+                }
+            };
+            let pool = req
+                .app_data::<web::Data<PgPool>>()
+                .cloned()
+                .ok_or_else(|| ErrorInternalServerError("No database pool was configured."))?;
+            let admin_api_key = req.app_data::<web::Data<AdminApiKey>>().cloned();
+            match check_api_key(
+                &pool,
+                admin_api_key.as_ref().map(|a| a.get_ref()),
+                &key,
+                scope,
+            )
+            .await
+            .map_err(ErrorInternalServerError)?
+            {
+                Ok(caller) => {
+                    req.extensions_mut().insert(caller);
+                }
+                Err(rejection @ ApiKeyRejection::Unknown) => {
+                    return Ok(
+                        req.into_response(HttpResponse::Unauthorized().body(rejection.to_string()))
+                    )
+                }
+                Err(rejection @ ApiKeyRejection::MissingScope(_)) => {
+                    return Ok(
+                        req.into_response(HttpResponse::Forbidden().body(rejection.to_string()))
+                    )
+                }
+            }
+            service.call(req).await
+        })
+    }
+}
+
+/// The key sent in the `X-Api-Key` header, if any.
+pub fn api_key(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(API_KEY_HEADER)
+        .and_then(|h| h.to_str().ok())
+        .map(|k| k.trim().to_string())
+        .filter(|k| !k.is_empty())
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ApiKeyRejection {
+    #[error("The API key is unknown or was revoked.")]
+    Unknown,
+    #[error("The API key does not have the {} scope.", .0.as_ref())]
+    MissingScope(ApiScope),
+}
+
+/// Who `key` belongs to, when it may be used for `scope`. The admin key may be used for
+/// anything.
+pub async fn check_api_key(
+    pool: &PgPool,
+    admin_api_key: Option<&AdminApiKey>,
+    key: &str,
+    scope: ApiScope,
+) -> Result<Result<ApiCaller, ApiKeyRejection>, sqlx::Error> {
+    if let Some(admin_api_key) = admin_api_key {
+        if hash_api_key(&admin_api_key.0) == hash_api_key(key) {
+            return Ok(Ok(ApiCaller::admin()));
+        }
+    }
+    Ok(match find_api_key(pool, key).await? {
+        None => Err(ApiKeyRejection::Unknown),
+        Some((_, scopes)) if !scopes.iter().any(|s| s == scope.as_ref()) => {
+            Err(ApiKeyRejection::MissingScope(scope))
+        }
+        Some((caller, _)) => Ok(caller),
+    })
+}
+
+/// A new key. It is long and random enough that a fast hash is safe to store it with.
+pub fn generate_api_key() -> String {
+    let mut rng = thread_rng();
+    std::iter::repeat_with(|| rng.sample(Alphanumeric))
+        .map(char::from)
+        .take(40)
+        .collect()
+}
+
+pub fn hash_api_key(key: &str) -> Vec<u8> {
+    Blake2b::digest(key.as_bytes()).to_vec()
+}
+
+/// The owner and scopes of a key that exists and was not revoked.
+#[tracing::instrument(name = "Looking up an API key", skip(pool, key))]
+async fn find_api_key(
+    pool: &PgPool,
+    key: &str,
+) -> Result<Option<(ApiCaller, Vec<String>)>, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"
+        SELECT api_key_id, name, scopes FROM api_keys
+        WHERE key_hash = $1 AND revoked_on IS NULL;
+        "#,
+        hash_api_key(key),
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.map(|r| {
+        let caller = ApiCaller {
+            api_key_id: Some(r.api_key_id),
+            name: r.name,
+        };
+        (caller, r.scopes)
+    }))
+}
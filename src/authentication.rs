@@ -0,0 +1,212 @@
+//! Editor logins: Argon2id password checks for Basic authentication and the login form, and the
+//! sessions the login form starts.
+use actix_web::http::header::HeaderMap;
+use anyhow::Context;
+use argon2::password_hash::SaltString;
+use argon2::{Algorithm, Argon2, Params, PasswordHash, PasswordHasher, PasswordVerifier, Version};
+use blake2::{Blake2b, Digest};
+use chrono::{Duration, Utc};
+use rand::distributions::Alphanumeric;
+use rand::{thread_rng, Rng};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub const SESSION_COOKIE: &str = "session";
+const SESSION_HOURS: i64 = 12;
+
+/// Compared against when the username is unknown, so a wrong username takes as long to reject
+/// as a wrong password.
+const DUMMY_PASSWORD_HASH: &str = "$argon2id$v=19$m=15000,t=2,p=1$\
+    gZiV/M1gPc22ElAH/Jh1Hw$CWOrkoo7oJBQ/iyh7uJ0LO2aLEfrHwTWllSAxT0zRno";
+
+pub struct Credentials {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum AuthError {
+    #[error("Invalid credentials.")]
+    InvalidCredentials(#[source] anyhow::Error),
+    #[error(transparent)]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+/// The credentials of an `Authorization: Basic` header. `Ok(None)` when the header is absent.
+pub fn basic_authentication(headers: &HeaderMap) -> Result<Option<Credentials>, anyhow::Error> {
+    let header = match headers.get("Authorization") {
+        Some(header) => header
+            .to_str()
+            .context("The 'Authorization' header was not a valid UTF8 string.")?,
+        None => return Ok(None),
+    };
+    let encoded = header
+        .strip_prefix("Basic ")
+        .context("The authorization scheme was not 'Basic'.")?;
+    let decoded = base64::decode_config(encoded, base64::STANDARD)
+        .context("Failed to base64-decode 'Basic' credentials.")?;
+    let decoded = String::from_utf8(decoded).context("The decoded credentials are not UTF8.")?;
+    let (username, password) = decoded
+        .split_once(':')
+        .context("The 'Basic' credentials have no ':' between username and password.")?;
+    Ok(Some(Credentials {
+        username: username.to_string(),
+        password: password.to_string(),
+    }))
+}
+
+/// The id of the user the credentials belong to.
+#[tracing::instrument(name = "Validating credentials", skip(credentials, pool))]
+pub async fn validate_credentials(
+    credentials: Credentials,
+    pool: &PgPool,
+) -> Result<Uuid, AuthError> {
+    let stored = sqlx::query!(
+        r#"
+        SELECT user_id, password_hash FROM users WHERE username = $1;
+        "#,
+        credentials.username,
+    )
+    .fetch_optional(pool)
+    .await
+    .context("Failed to fetch the user from the database.")?;
+    let (user_id, password_hash) = match stored {
+        Some(row) => (Some(row.user_id), row.password_hash),
+        None => (None, DUMMY_PASSWORD_HASH.to_string()),
+    };
+    // Hashing takes tens of milliseconds, too long to hold up the executor.
+    actix_web::rt::task::spawn_blocking(move || {
+        verify_password_hash(&password_hash, &credentials.password)
+    })
+    .await
+    .context("Failed to spawn the password check.")??;
+    user_id
+        .ok_or_else(|| anyhow::anyhow!("Unknown username."))
+        .map_err(AuthError::InvalidCredentials)
+}
+
+fn verify_password_hash(password_hash: &str, password: &str) -> Result<(), AuthError> {
+    let password_hash =
+        PasswordHash::new(password_hash).context("Failed to parse the stored password hash.")?;
+    Argon2::default()
+        .verify_password(password.as_bytes(), &password_hash)
+        .context("Invalid password.")
+        .map_err(AuthError::InvalidCredentials)
+}
+
+/// An Argon2id PHC string for a new password.
+pub fn compute_password_hash(password: &str) -> Result<String, anyhow::Error> {
+    let salt = SaltString::generate(&mut thread_rng());
+    let password_hash = Argon2::new(
+        Algorithm::Argon2id,
+        Version::V0x13,
+        Params::new(15000, 2, 1, None).context("Invalid Argon2 parameters.")?,
+    )
+    .hash_password(password.as_bytes(), &salt)
+    .context("Failed to hash the password.")?
+    .to_string();
+    Ok(password_hash)
+}
+
+/// Session tokens are random like API keys, so a fast hash is enough to keep them.
+fn hash_session_token(token: &str) -> Vec<u8> {
+    Blake2b::digest(token.as_bytes()).to_vec()
+}
+
+/// Starts a session for the user and returns the token for its cookie.
+#[tracing::instrument(name = "Starting a session", skip(pool))]
+pub async fn start_session(pool: &PgPool, user_id: Uuid) -> Result<String, sqlx::Error> {
+    let mut rng = thread_rng();
+    let token: String = std::iter::repeat_with(|| rng.sample(Alphanumeric))
+        .map(char::from)
+        .take(40)
+        .collect();
+    let now = Utc::now();
+    sqlx::query!(
+        r#"
+        INSERT INTO user_sessions (session_hash, user_id, created_on, expires_on)
+        VALUES ($1, $2, $3, $4);
+        "#,
+        hash_session_token(&token),
+        user_id,
+        now,
+        now + Duration::hours(SESSION_HOURS),
+    )
+    .execute(pool)
+    .await?;
+    Ok(token)
+}
+
+/// The user and username of a session that has not expired.
+#[tracing::instrument(name = "Looking up a session", skip(pool, token))]
+pub async fn session_user(
+    pool: &PgPool,
+    token: &str,
+) -> Result<Option<(Uuid, String)>, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"
+        SELECT u.user_id, u.username FROM user_sessions s
+        INNER JOIN users u
+            ON u.user_id = s.user_id
+        WHERE s.session_hash = $1 AND s.expires_on > now();
+        "#,
+        hash_session_token(token),
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.map(|r| (r.user_id, r.username)))
+}
+
+#[tracing::instrument(name = "Ending a session", skip(pool, token))]
+pub async fn end_session(pool: &PgPool, token: &str) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        DELETE FROM user_sessions WHERE session_hash = $1 OR expires_on <= now();
+        "#,
+        hash_session_token(token),
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        basic_authentication, compute_password_hash, verify_password_hash, DUMMY_PASSWORD_HASH,
+    };
+    use actix_web::http::header::{HeaderMap, HeaderName, HeaderValue};
+    use claim::{assert_err, assert_ok};
+
+    fn authorization(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            HeaderName::from_static("authorization"),
+            HeaderValue::from_str(value).unwrap(),
+        );
+        headers
+    }
+
+    #[test]
+    fn basic_credentials_are_decoded() {
+        let encoded = base64::encode("editor:pass:word");
+        let credentials = basic_authentication(&authorization(&format!("Basic {}", encoded)))
+            .unwrap()
+            .unwrap();
+        assert_eq!(credentials.username, "editor");
+        assert_eq!(credentials.password, "pass:word");
+        assert!(basic_authentication(&HeaderMap::new()).unwrap().is_none());
+        assert!(basic_authentication(&authorization("Bearer abc")).is_err());
+    }
+
+    #[test]
+    fn a_password_matches_only_its_own_hash() {
+        let password_hash = compute_password_hash("correct horse battery").unwrap();
+        assert_ok!(verify_password_hash(
+            &password_hash,
+            "correct horse battery"
+        ));
+        assert_err!(verify_password_hash(&password_hash, "wrong horse battery"));
+        assert_err!(verify_password_hash(DUMMY_PASSWORD_HASH, ""));
+    }
+}
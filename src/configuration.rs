@@ -1,14 +1,22 @@
 use crate::domain::Email;
+use crate::email_client::EmailClient;
+use crate::scoring::ScoringSettings;
 use serde_aux::field_attributes::deserialize_number_from_string;
 use sqlx::postgres::{PgConnectOptions, PgSslMode};
 use sqlx::ConnectOptions;
 use std::convert::{TryFrom, TryInto};
+use std::net::IpAddr;
+
+/// The admin key in `local.yaml`. It is public, so it only unlocks a server that listens on a
+/// loopback address.
+pub const DEV_ADMIN_API_KEY: &str = "my-admin-api-key";
 
 #[derive(serde::Deserialize, Clone)]
 pub struct Settings {
     pub database: DatabaseSettings,
     pub application: ApplicationSettings,
     pub email_client: EmailClientSettings,
+    pub scoring: ScoringSettings,
 }
 
 #[derive(serde::Deserialize, Clone)]
@@ -26,6 +34,16 @@ impl EmailClientSettings {
     pub fn timeout(&self) -> std::time::Duration {
         std::time::Duration::from_millis(self.timeout_milliseconds)
     }
+    pub fn client(self) -> EmailClient {
+        let sender_email = self.sender().expect("Invalid sender email address.");
+        let timeout = self.timeout();
+        EmailClient::new(
+            self.base_url,
+            sender_email,
+            self.authorization_token,
+            timeout,
+        )
+    }
 }
 
 #[derive(serde::Deserialize, Clone)]
@@ -34,6 +52,29 @@ pub struct ApplicationSettings {
     pub port: u16,
     pub host: String,
     pub base_url: String,
+    pub admin_api_key: String,
+    /// Signs unsubscribe links.
+    pub hmac_secret: String,
+}
+
+impl ApplicationSettings {
+    /// The admin key has every scope, so a server must not start with a missing or public one.
+    pub fn check_admin_api_key(&self) -> Result<(), String> {
+        if self.admin_api_key.trim().is_empty() {
+            return Err("application.admin_api_key is not set.".to_string());
+        }
+        let loopback = match self.host.parse::<IpAddr>() {
+            Ok(ip) => ip.is_loopback(),
+            Err(_) => self.host == "localhost",
+        };
+        if self.admin_api_key == DEV_ADMIN_API_KEY && !loopback {
+            return Err(
+                "application.admin_api_key is the development key. Set APP_APPLICATION__ADMIN_API_KEY."
+                    .to_string(),
+            );
+        }
+        Ok(())
+    }
 }
 
 #[derive(serde::Deserialize, Clone)]
@@ -111,3 +152,36 @@ impl TryFrom<String> for Environment {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{ApplicationSettings, DEV_ADMIN_API_KEY};
+
+    fn settings(host: &str, admin_api_key: &str) -> ApplicationSettings {
+        ApplicationSettings {
+            port: 8000,
+            host: host.to_string(),
+            base_url: "http://127.0.0.1".to_string(),
+            admin_api_key: admin_api_key.to_string(),
+            hmac_secret: "secret".to_string(),
+        }
+    }
+
+    #[test]
+    fn the_development_admin_key_only_works_on_loopback() {
+        assert!(settings("127.0.0.1", DEV_ADMIN_API_KEY)
+            .check_admin_api_key()
+            .is_ok());
+        assert!(settings("0.0.0.0", DEV_ADMIN_API_KEY)
+            .check_admin_api_key()
+            .is_err());
+        assert!(settings("0.0.0.0", "a-long-random-key")
+            .check_admin_api_key()
+            .is_ok());
+    }
+
+    #[test]
+    fn an_empty_admin_key_is_refused() {
+        assert!(settings("127.0.0.1", " ").check_admin_api_key().is_err());
+    }
+}
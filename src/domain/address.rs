@@ -1,70 +1,443 @@
-use super::MAX_LIMIT_CHARACTERS;
+use super::{Network, MAX_LIMIT_CHARACTERS};
+use bech32::FromBase32;
+use blake2::{Blake2b, Digest};
+use tiny_keccak::{Hasher, Keccak};
 use unicode_segmentation::UnicodeSegmentation;
 
+/// `as_ref` gives the canonical form that is stored and compared on, `display` the form the
+/// address was submitted in.
 #[derive(Debug)]
-pub struct Address(String);
+pub struct Address {
+    canonical: String,
+    display: String,
+}
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum AddressError {
+    #[error("{0} is not a valid address.")]
+    Malformed(String),
+    #[error("{0} is not a valid address on any supported network.")]
+    UnknownFormat(String),
+    #[error("{address} is not a valid {network} address: expected 0x followed by 40 hexadecimal characters.")]
+    InvalidHex { address: String, network: String },
+    #[error("{address} is not a valid {network} address: the EIP-55 checksum does not match.")]
+    InvalidChecksum { address: String, network: String },
+    #[error("{address} is not a valid {network} address: it is not valid base58.")]
+    InvalidBase58 { address: String, network: String },
+    #[error("{address} is not a valid {network} address: {reason}.")]
+    InvalidBech32 {
+        address: String,
+        network: String,
+        reason: String,
+    },
+    #[error("{address} is not a valid {network} address: expected the {expected} prefix, found {found}.")]
+    InvalidPrefix {
+        address: String,
+        network: String,
+        expected: String,
+        found: String,
+    },
+    #[error(
+        "{address} is not a valid {network} address: expected {expected} bytes, found {found}."
+    )]
+    InvalidLength {
+        address: String,
+        network: String,
+        expected: String,
+        found: usize,
+    },
+    #[error("{address} is not a valid {network} address: the SS58 checksum does not match.")]
+    InvalidSs58Checksum { address: String, network: String },
+}
+
+impl From<AddressError> for String {
+    fn from(e: AddressError) -> Self {
+        e.to_string()
+    }
+}
+
+const SS58_PREFIX: &[u8] = b"SS58PRE";
+const POLKADOT_SS58_FORMAT: u8 = 0;
 
 impl Address {
-    pub fn parse(s: String) -> Result<Address, String> {
+    pub fn parse(s: String, network: &Network) -> Result<Address, AddressError> {
         let is_empty_or_whitespace = s.trim().is_empty();
         let is_too_long = s.graphemes(true).count() > MAX_LIMIT_CHARACTERS;
         let forbidden_characters = ['/', '(', ')', '"', '<', '>', '\\', '{', '}'];
         let contains_forbidden_characters = s.chars().any(|g| forbidden_characters.contains(&g));
         if is_empty_or_whitespace || is_too_long || contains_forbidden_characters {
-            Err(format!("{} is not a valid address.", s))
+            return Err(AddressError::Malformed(s));
+        }
+        match network {
+            Network::ETH
+            | Network::BSC
+            | Network::AVAX
+            | Network::MATIC
+            | Network::FTM
+            | Network::MOVR => validate_eip55(&s, network)?,
+            Network::SOL => validate_base58_key(&s, network)?,
+            Network::ADA => validate_bech32(&s, network, &["addr", "stake"], None)?,
+            Network::LUNA => validate_bech32(&s, network, &["terra"], Some(&[20, 32]))?,
+            Network::DOT => validate_ss58(&s, network)?,
+        }
+        Ok(Self {
+            canonical: canonicalize(&s, network),
+            display: s,
+        })
+    }
+
+    /// For lookups that don't name a network: the address only has to be valid on one of them.
+    pub fn parse_for_any_network(s: String) -> Result<Address, AddressError> {
+        match Network::ALL
+            .iter()
+            .find_map(|network| Address::parse(s.clone(), network).ok())
+        {
+            Some(address) => Ok(address),
+            None => match Address::parse(s.clone(), &Network::ETH) {
+                Err(AddressError::Malformed(s)) => Err(AddressError::Malformed(s)),
+                _ => Err(AddressError::UnknownFormat(s)),
+            },
+        }
+    }
+
+    pub fn display(&self) -> &str {
+        &self.display
+    }
+}
+
+/// Hex and bech32 addresses don't depend on case, so they are lowercased. Base58 is case
+/// sensitive and is kept as it is.
+fn canonicalize(s: &str, network: &Network) -> String {
+    match network {
+        Network::ETH
+        | Network::BSC
+        | Network::AVAX
+        | Network::MATIC
+        | Network::FTM
+        | Network::MOVR
+        | Network::ADA
+        | Network::LUNA => s.to_lowercase(),
+        Network::SOL | Network::DOT => s.to_string(),
+    }
+}
+
+fn validate_eip55(s: &str, network: &Network) -> Result<(), AddressError> {
+    let hex = match s.strip_prefix("0x") {
+        Some(hex) if hex.len() == 40 && hex.chars().all(|c| c.is_ascii_hexdigit()) => hex,
+        _ => {
+            return Err(AddressError::InvalidHex {
+                address: s.to_string(),
+                network: network.as_ref().to_string(),
+            })
+        }
+    };
+    let is_single_case = hex == hex.to_lowercase().as_str() || hex == hex.to_uppercase().as_str();
+    if is_single_case {
+        // Addresses in a single case carry no checksum.
+        return Ok(());
+    }
+
+    let mut hash = [0u8; 32];
+    let mut hasher = Keccak::v256();
+    hasher.update(hex.to_lowercase().as_bytes());
+    hasher.finalize(&mut hash);
+    let checksum_matches = hex.chars().enumerate().all(|(i, c)| {
+        let nibble = if i % 2 == 0 {
+            hash[i / 2] >> 4
+        } else {
+            hash[i / 2] & 0x0f
+        };
+        if c.is_ascii_digit() {
+            true
+        } else if nibble >= 8 {
+            c.is_ascii_uppercase()
         } else {
-            Ok(Self(s))
+            c.is_ascii_lowercase()
         }
+    });
+    if checksum_matches {
+        Ok(())
+    } else {
+        Err(AddressError::InvalidChecksum {
+            address: s.to_string(),
+            network: network.as_ref().to_string(),
+        })
+    }
+}
+
+fn decode_base58(s: &str, network: &Network) -> Result<Vec<u8>, AddressError> {
+    bs58::decode(s)
+        .into_vec()
+        .map_err(|_| AddressError::InvalidBase58 {
+            address: s.to_string(),
+            network: network.as_ref().to_string(),
+        })
+}
+
+fn validate_base58_key(s: &str, network: &Network) -> Result<(), AddressError> {
+    let bytes = decode_base58(s, network)?;
+    if bytes.len() != 32 {
+        return Err(AddressError::InvalidLength {
+            address: s.to_string(),
+            network: network.as_ref().to_string(),
+            expected: "32".to_string(),
+            found: bytes.len(),
+        });
+    }
+    Ok(())
+}
+
+fn validate_bech32(
+    s: &str,
+    network: &Network,
+    prefixes: &[&str],
+    lengths: Option<&[usize]>,
+) -> Result<(), AddressError> {
+    let invalid_bech32 = |reason: String| AddressError::InvalidBech32 {
+        address: s.to_string(),
+        network: network.as_ref().to_string(),
+        reason,
+    };
+    let (hrp, data, _) = bech32::decode(s).map_err(|e| invalid_bech32(e.to_string()))?;
+    if !prefixes.contains(&hrp.as_str()) {
+        return Err(AddressError::InvalidPrefix {
+            address: s.to_string(),
+            network: network.as_ref().to_string(),
+            expected: prefixes.join(" or "),
+            found: hrp,
+        });
+    }
+    let bytes = Vec::<u8>::from_base32(&data).map_err(|e| invalid_bech32(e.to_string()))?;
+    match lengths {
+        Some(lengths) if !lengths.contains(&bytes.len()) => Err(AddressError::InvalidLength {
+            address: s.to_string(),
+            network: network.as_ref().to_string(),
+            expected: lengths
+                .iter()
+                .map(|l| l.to_string())
+                .collect::<Vec<_>>()
+                .join(" or "),
+            found: bytes.len(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+fn validate_ss58(s: &str, network: &Network) -> Result<(), AddressError> {
+    let bytes = decode_base58(s, network)?;
+    // One format byte, a 32 byte public key and a two byte checksum.
+    if bytes.len() != 35 {
+        return Err(AddressError::InvalidLength {
+            address: s.to_string(),
+            network: network.as_ref().to_string(),
+            expected: "35".to_string(),
+            found: bytes.len(),
+        });
     }
+    if bytes[0] != POLKADOT_SS58_FORMAT {
+        return Err(AddressError::InvalidPrefix {
+            address: s.to_string(),
+            network: network.as_ref().to_string(),
+            expected: POLKADOT_SS58_FORMAT.to_string(),
+            found: bytes[0].to_string(),
+        });
+    }
+    let (payload, checksum) = bytes.split_at(33);
+    let mut hasher = Blake2b::new();
+    hasher.update(SS58_PREFIX);
+    hasher.update(payload);
+    let hash = hasher.finalize();
+    if &hash[..2] != checksum {
+        return Err(AddressError::InvalidSs58Checksum {
+            address: s.to_string(),
+            network: network.as_ref().to_string(),
+        });
+    }
+    Ok(())
 }
 
 impl AsRef<str> for Address {
     fn as_ref(&self) -> &str {
-        &self.0
+        &self.canonical
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Address;
-    use crate::domain::MAX_LIMIT_CHARACTERS;
+    use super::{Address, AddressError};
+    use crate::domain::{Network, MAX_LIMIT_CHARACTERS};
+    use bech32::{ToBase32, Variant};
     use claim::{assert_err, assert_ok};
 
-    #[test]
-    fn a_256_grapheme_long_name_is_valid() {
-        let address = "a".repeat(MAX_LIMIT_CHARACTERS);
-        assert_ok!(Address::parse(address));
-    }
-
     #[test]
     fn a_name_longer_than_256_graphemes_is_rejected() {
         let address = "a".repeat(MAX_LIMIT_CHARACTERS + 1);
-        assert_err!(Address::parse(address));
+        assert_err!(Address::parse(address, &Network::ETH));
     }
 
     #[test]
     fn whitespace_only_names_are_rejected() {
         let address = " ".to_string();
-        assert_err!(Address::parse(address));
+        assert_err!(Address::parse(address, &Network::ETH));
     }
 
     #[test]
     fn empty_string_is_rejected() {
         let address = "".to_string();
-        assert_err!(Address::parse(address));
+        assert_err!(Address::parse(address, &Network::ETH));
     }
 
     #[test]
     fn names_containing_an_invalid_character_are_rejected() {
         for name in &['/', '(', ')', '"', '<', '>', '\\', '{', '}'] {
             let address = name.to_string();
-            assert_err!(Address::parse(address));
+            assert_eq!(
+                Address::parse(address.clone(), &Network::ETH).unwrap_err(),
+                AddressError::Malformed(address)
+            );
         }
     }
 
     #[test]
-    fn a_valid_name_is_parsed_successfully() {
+    fn checksummed_evm_addresses_are_parsed_successfully() {
+        for address in &[
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ] {
+            for network in &[Network::ETH, Network::BSC, Network::MATIC, Network::MOVR] {
+                assert_ok!(Address::parse(address.to_string(), network));
+            }
+        }
+    }
+
+    #[test]
+    fn single_case_evm_addresses_skip_the_checksum() {
+        let address = "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed".to_string();
+        assert_ok!(Address::parse(address, &Network::ETH));
+        let address = "0x5AAEB6053F3E94C9B9A09F33669435E7EF1BEAED".to_string();
+        assert_ok!(Address::parse(address, &Network::ETH));
+    }
+
+    #[test]
+    fn evm_addresses_with_a_wrong_checksum_are_rejected() {
+        let address = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD".to_string();
+        assert!(matches!(
+            Address::parse(address, &Network::ETH),
+            Err(AddressError::InvalidChecksum { .. })
+        ));
+    }
+
+    #[test]
+    fn evm_addresses_with_the_wrong_shape_are_rejected() {
+        for address in &[
+            "5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeA",
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeg",
+        ] {
+            assert!(matches!(
+                Address::parse(address.to_string(), &Network::BSC),
+                Err(AddressError::InvalidHex { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn solana_public_keys_are_parsed_successfully() {
+        let address = "So11111111111111111111111111111111111111112".to_string();
+        assert_ok!(Address::parse(address, &Network::SOL));
+    }
+
+    #[test]
+    fn solana_keys_must_be_32_bytes_of_base58() {
+        let address = "So1111111111111111111111111111111111111111".to_string();
+        assert!(matches!(
+            Address::parse(address, &Network::SOL),
+            Err(AddressError::InvalidLength { found: 31, .. })
+        ));
+        let address = "0OIl111111111111111111111111111111111111112".to_string();
+        assert!(matches!(
+            Address::parse(address, &Network::SOL),
+            Err(AddressError::InvalidBase58 { .. })
+        ));
+    }
+
+    #[test]
+    fn cardano_bech32_addresses_are_parsed_successfully() {
+        let address = bech32::encode("addr", [7u8; 57].to_base32(), Variant::Bech32).unwrap();
+        assert_ok!(Address::parse(address, &Network::ADA));
+    }
+
+    #[test]
+    fn terra_addresses_need_the_terra_prefix() {
+        let address = bech32::encode("terra", [7u8; 20].to_base32(), Variant::Bech32).unwrap();
+        assert_ok!(Address::parse(address, &Network::LUNA));
+        let address = bech32::encode("cosmos", [7u8; 20].to_base32(), Variant::Bech32).unwrap();
+        assert!(matches!(
+            Address::parse(address, &Network::LUNA),
+            Err(AddressError::InvalidPrefix { .. })
+        ));
+    }
+
+    #[test]
+    fn bech32_addresses_with_a_bad_checksum_are_rejected() {
+        let mut address = bech32::encode("terra", [7u8; 20].to_base32(), Variant::Bech32).unwrap();
+        let last = if address.ends_with('q') { 'p' } else { 'q' };
+        address.pop();
+        address.push(last);
+        assert!(matches!(
+            Address::parse(address, &Network::LUNA),
+            Err(AddressError::InvalidBech32 { .. })
+        ));
+    }
+
+    #[test]
+    fn polkadot_ss58_addresses_are_parsed_successfully() {
+        let address = "15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5".to_string();
+        assert_ok!(Address::parse(address, &Network::DOT));
+    }
+
+    #[test]
+    fn polkadot_addresses_with_a_bad_checksum_are_rejected() {
+        let address = "15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp6".to_string();
+        assert_err!(Address::parse(address, &Network::DOT));
+    }
+
+    #[test]
+    fn lookups_accept_an_address_from_any_network() {
+        let address = "15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5".to_string();
+        assert_ok!(Address::parse_for_any_network(address));
         let address = "Something or Another".to_string();
-        assert_ok!(Address::parse(address));
+        assert_err!(Address::parse_for_any_network(address));
+    }
+
+    #[test]
+    fn evm_addresses_in_any_case_share_a_canonical_form() {
+        let checksummed = Address::parse(
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed".into(),
+            &Network::ETH,
+        )
+        .unwrap();
+        let lowercase = Address::parse(
+            "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed".into(),
+            &Network::ETH,
+        )
+        .unwrap();
+        assert_eq!(checksummed.as_ref(), lowercase.as_ref());
+        assert_eq!(checksummed.as_ref(), lowercase.display());
+        assert_eq!(
+            checksummed.display(),
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"
+        );
+    }
+
+    #[test]
+    fn base58_addresses_keep_their_case() {
+        let address = "15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5";
+        let parsed = Address::parse(address.to_string(), &Network::DOT).unwrap();
+        assert_eq!(parsed.as_ref(), address);
+        let parsed = Address::parse_for_any_network(address.to_string()).unwrap();
+        assert_eq!(parsed.as_ref(), address);
     }
 }
@@ -0,0 +1,101 @@
+use super::MAX_LIMIT_CHARACTERS;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// What an API key may do.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ApiScope {
+    IngestHolders,
+    WriteLabels,
+    PublishNewsletter,
+    /// Bounce and spam-complaint callbacks from Postmark.
+    EmailWebhooks,
+    /// Minting and revoking keys. Only the admin key from the configuration has it.
+    Admin,
+}
+
+impl ApiScope {
+    /// The scopes a minted key can be given. Reads are public, so there is no read scope.
+    pub const GRANTABLE: [ApiScope; 4] = [
+        ApiScope::IngestHolders,
+        ApiScope::WriteLabels,
+        ApiScope::PublishNewsletter,
+        ApiScope::EmailWebhooks,
+    ];
+
+    pub fn parse(s: String) -> Result<ApiScope, String> {
+        Self::GRANTABLE
+            .iter()
+            .find(|scope| scope.as_ref() == s.trim())
+            .copied()
+            .ok_or_else(|| {
+                format!(
+                    "{} is not a valid scope. Use ingest:holders, write:labels, \
+                    publish:newsletter or webhooks:email.",
+                    s
+                )
+            })
+    }
+}
+
+impl AsRef<str> for ApiScope {
+    fn as_ref(&self) -> &str {
+        match self {
+            ApiScope::IngestHolders => "ingest:holders",
+            ApiScope::WriteLabels => "write:labels",
+            ApiScope::PublishNewsletter => "publish:newsletter",
+            ApiScope::EmailWebhooks => "webhooks:email",
+            ApiScope::Admin => "admin",
+        }
+    }
+}
+
+/// Who a key was minted for, e.g. `bscscan crawler`.
+#[derive(Debug)]
+pub struct ApiKeyName(String);
+
+impl ApiKeyName {
+    pub fn parse(s: String) -> Result<ApiKeyName, String> {
+        let s = s.trim().to_string();
+        let is_empty = s.is_empty();
+        let is_too_long = s.graphemes(true).count() > MAX_LIMIT_CHARACTERS;
+        let forbidden_characters = ['"', '<', '>', '\\', '{', '}'];
+        let contains_forbidden_characters = s.chars().any(|g| forbidden_characters.contains(&g));
+        if is_empty || is_too_long || contains_forbidden_characters {
+            Err(format!("{} is not a valid API key name.", s))
+        } else {
+            Ok(Self(s))
+        }
+    }
+}
+
+impl AsRef<str> for ApiKeyName {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ApiKeyName, ApiScope};
+    use claim::{assert_err, assert_ok};
+
+    #[test]
+    fn grantable_scopes_are_parsed() {
+        for scope in ApiScope::GRANTABLE {
+            assert_eq!(ApiScope::parse(scope.as_ref().to_string()), Ok(scope));
+        }
+    }
+
+    #[test]
+    fn the_admin_scope_cannot_be_granted() {
+        assert_err!(ApiScope::parse("admin".to_string()));
+        assert_err!(ApiScope::parse("write".to_string()));
+        assert_err!(ApiScope::parse("read".to_string()));
+    }
+
+    #[test]
+    fn a_key_needs_a_name() {
+        assert_ok!(ApiKeyName::parse("bscscan crawler".to_string()));
+        assert_err!(ApiKeyName::parse(" ".to_string()));
+    }
+}
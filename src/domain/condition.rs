@@ -1,5 +1,68 @@
+use super::AddressType;
+
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Debug, Clone)]
 pub enum Condition {
     WinConditionMet,
     LoseConditionMet,
-    InconclusiveCondition
-}
\ No newline at end of file
+    InconclusiveCondition,
+}
+
+impl Condition {
+    /// Any sign of a scam outweighs a legit history, since an address that has rugged once
+    /// can't be trusted for having also launched a real token.
+    pub fn for_address(
+        scam_creations: usize,
+        legit_creations: usize,
+        address_types: &[AddressType],
+    ) -> Condition {
+        let tagged_as = |address_type: AddressType| address_types.contains(&address_type);
+        if scam_creations > 0
+            || tagged_as(AddressType::Scammer)
+            || tagged_as(AddressType::SuspiciousHolder)
+        {
+            Condition::LoseConditionMet
+        } else if legit_creations > 0 || tagged_as(AddressType::Legit) {
+            Condition::WinConditionMet
+        } else {
+            Condition::InconclusiveCondition
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Condition;
+    use crate::domain::AddressType;
+
+    #[test]
+    fn a_scam_history_loses_even_with_a_legit_history() {
+        assert_eq!(
+            Condition::for_address(1, 3, &[AddressType::Legit]),
+            Condition::LoseConditionMet
+        );
+        assert_eq!(
+            Condition::for_address(0, 1, &[AddressType::Scammer]),
+            Condition::LoseConditionMet
+        );
+    }
+
+    #[test]
+    fn a_legit_history_wins() {
+        assert_eq!(
+            Condition::for_address(0, 1, &[AddressType::Whale]),
+            Condition::WinConditionMet
+        );
+        assert_eq!(
+            Condition::for_address(0, 0, &[AddressType::Legit]),
+            Condition::WinConditionMet
+        );
+    }
+
+    #[test]
+    fn an_address_without_history_is_inconclusive() {
+        assert_eq!(
+            Condition::for_address(0, 0, &[AddressType::Whale, AddressType::Paperhand]),
+            Condition::InconclusiveCondition
+        );
+    }
+}
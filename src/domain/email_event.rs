@@ -0,0 +1,74 @@
+/// What Postmark reported about a sent email, through its bounce and spam-complaint webhooks.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EmailEventKind {
+    /// The address will not take mail, e.g. it does not exist.
+    HardBounce,
+    /// A bounce that may clear up, e.g. a full mailbox.
+    SoftBounce,
+    SpamComplaint,
+}
+
+impl EmailEventKind {
+    /// Classifies a Postmark bounce by its `Type`. Postmark also sets `Inactive` when it stops
+    /// sending to the address itself, which makes any bounce permanent.
+    pub fn bounce(bounce_type: &str, inactive: bool) -> EmailEventKind {
+        const PERMANENT: [&str; 3] = ["HardBounce", "BadEmailAddress", "ManuallyDeactivated"];
+        if inactive || PERMANENT.contains(&bounce_type) {
+            EmailEventKind::HardBounce
+        } else {
+            EmailEventKind::SoftBounce
+        }
+    }
+
+    /// The `subscriptions.status` a subscriber gets after this event, if it changes.
+    pub fn subscriber_status(&self) -> Option<&'static str> {
+        match self {
+            EmailEventKind::HardBounce => Some("bounced"),
+            EmailEventKind::SoftBounce => None,
+            EmailEventKind::SpamComplaint => Some("complained"),
+        }
+    }
+}
+
+impl AsRef<str> for EmailEventKind {
+    fn as_ref(&self) -> &str {
+        match self {
+            EmailEventKind::HardBounce => "hard_bounce",
+            EmailEventKind::SoftBounce => "soft_bounce",
+            EmailEventKind::SpamComplaint => "spam_complaint",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EmailEventKind;
+
+    #[test]
+    fn only_permanent_bounces_stop_a_subscription() {
+        assert_eq!(
+            EmailEventKind::bounce("HardBounce", false).subscriber_status(),
+            Some("bounced")
+        );
+        assert_eq!(
+            EmailEventKind::bounce("BadEmailAddress", false),
+            EmailEventKind::HardBounce
+        );
+        assert_eq!(
+            EmailEventKind::bounce("SoftBounce", false).subscriber_status(),
+            None
+        );
+        assert_eq!(
+            EmailEventKind::bounce("Transient", true),
+            EmailEventKind::HardBounce
+        );
+    }
+
+    #[test]
+    fn spam_complaints_stop_a_subscription() {
+        assert_eq!(
+            EmailEventKind::SpamComplaint.subscriber_status(),
+            Some("complained")
+        );
+    }
+}
@@ -0,0 +1,19 @@
+use super::{Address, AddressType, Network};
+
+/// Every filter that is given must match. A description matches `address_types` when it has any
+/// of them.
+pub struct HolderDescriptionQuery {
+    pub network: Option<Network>,
+    pub holder_addresses: Option<Vec<Address>>,
+    pub contract_address: Option<Address>,
+    pub address_types: Vec<AddressType>,
+}
+
+impl HolderDescriptionQuery {
+    pub fn is_unfiltered(&self) -> bool {
+        self.network.is_none()
+            && self.holder_addresses.is_none()
+            && self.contract_address.is_none()
+            && self.address_types.is_empty()
+    }
+}
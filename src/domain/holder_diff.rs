@@ -0,0 +1,160 @@
+use sqlx::types::BigDecimal;
+use std::collections::HashMap;
+
+pub struct HolderPosition {
+    pub holder_address: String,
+    pub place: i32,
+    pub amount: BigDecimal,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+pub struct HolderChange {
+    pub holder_address: String,
+    pub previous_place: Option<i32>,
+    pub current_place: Option<i32>,
+    pub place_change: Option<i32>,
+    pub previous_amount: Option<BigDecimal>,
+    pub current_amount: Option<BigDecimal>,
+    pub amount_change: BigDecimal,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct HolderSnapshotDiff {
+    pub changes: Vec<HolderChange>,
+    pub entered: Vec<String>,
+    pub left: Vec<String>,
+}
+
+fn in_top(position: Option<&HolderPosition>, top_n: Option<i32>) -> bool {
+    match (position, top_n) {
+        (Some(p), Some(n)) => p.place <= n,
+        (Some(_), None) => true,
+        (None, _) => false,
+    }
+}
+
+impl HolderSnapshotDiff {
+    /// Compares two holder snapshots of the same contract. A positive `place_change` means the
+    /// holder climbed the list. When `top_n` is given only places up to `top_n` count as being
+    /// in the list for `entered`/`left`.
+    pub fn between(
+        previous: Vec<HolderPosition>,
+        current: Vec<HolderPosition>,
+        top_n: Option<i32>,
+    ) -> Self {
+        let mut previous: HashMap<String, HolderPosition> = previous
+            .into_iter()
+            .map(|p| (p.holder_address.clone(), p))
+            .collect();
+        let mut changes = vec![];
+        let mut entered = vec![];
+        let mut left = vec![];
+
+        for now in current {
+            let before = previous.remove(&now.holder_address);
+            if in_top(Some(&now), top_n) && !in_top(before.as_ref(), top_n) {
+                entered.push(now.holder_address.clone());
+            } else if !in_top(Some(&now), top_n) && in_top(before.as_ref(), top_n) {
+                left.push(now.holder_address.clone());
+            }
+            let previous_amount = before.as_ref().map(|b| b.amount.clone());
+            let amount_change = match &previous_amount {
+                Some(amount) => &now.amount - amount,
+                None => now.amount.clone(),
+            };
+            changes.push(HolderChange {
+                place_change: before.as_ref().map(|b| b.place - now.place),
+                previous_place: before.as_ref().map(|b| b.place),
+                current_place: Some(now.place),
+                previous_amount,
+                current_amount: Some(now.amount),
+                amount_change,
+                holder_address: now.holder_address,
+            });
+        }
+
+        let mut dropped: Vec<HolderPosition> = previous.into_values().collect();
+        dropped.sort_by_key(|p| p.place);
+        for before in dropped {
+            if in_top(Some(&before), top_n) {
+                left.push(before.holder_address.clone());
+            }
+            changes.push(HolderChange {
+                place_change: None,
+                previous_place: Some(before.place),
+                current_place: None,
+                amount_change: -before.amount.clone(),
+                previous_amount: Some(before.amount),
+                current_amount: None,
+                holder_address: before.holder_address,
+            });
+        }
+
+        Self {
+            changes,
+            entered,
+            left,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HolderPosition, HolderSnapshotDiff};
+    use sqlx::types::BigDecimal;
+    use std::str::FromStr;
+
+    fn position(holder_address: &str, place: i32, amount: &str) -> HolderPosition {
+        HolderPosition {
+            holder_address: holder_address.to_string(),
+            place,
+            amount: BigDecimal::from_str(amount).unwrap(),
+        }
+    }
+
+    #[test]
+    fn changes_in_amount_and_place_are_reported() {
+        let previous = vec![position("a", 1, "100"), position("b", 2, "50")];
+        let current = vec![position("b", 1, "150"), position("a", 2, "90.5")];
+        let diff = HolderSnapshotDiff::between(previous, current, None);
+
+        assert_eq!(diff.changes[0].holder_address, "b");
+        assert_eq!(diff.changes[0].place_change, Some(1));
+        assert_eq!(diff.changes[0].amount_change, BigDecimal::from(100));
+        assert_eq!(diff.changes[1].holder_address, "a");
+        assert_eq!(diff.changes[1].place_change, Some(-1));
+        assert_eq!(
+            diff.changes[1].amount_change,
+            BigDecimal::from_str("-9.5").unwrap()
+        );
+        assert!(diff.entered.is_empty());
+        assert!(diff.left.is_empty());
+    }
+
+    #[test]
+    fn new_and_missing_holders_enter_and_leave() {
+        let previous = vec![position("a", 1, "100"), position("b", 2, "50")];
+        let current = vec![position("a", 1, "100"), position("c", 2, "70")];
+        let diff = HolderSnapshotDiff::between(previous, current, None);
+
+        assert_eq!(diff.entered, vec!["c".to_string()]);
+        assert_eq!(diff.left, vec!["b".to_string()]);
+        let b = diff
+            .changes
+            .iter()
+            .find(|c| c.holder_address == "b")
+            .unwrap();
+        assert_eq!(b.current_place, None);
+        assert_eq!(b.amount_change, BigDecimal::from(-50));
+    }
+
+    #[test]
+    fn top_n_only_counts_places_within_the_limit() {
+        let previous = vec![position("a", 1, "100"), position("b", 2, "50")];
+        let current = vec![position("b", 1, "150"), position("a", 2, "90")];
+        let diff = HolderSnapshotDiff::between(previous, current, Some(1));
+
+        assert_eq!(diff.entered, vec!["b".to_string()]);
+        assert_eq!(diff.left, vec!["a".to_string()]);
+    }
+}
@@ -0,0 +1,109 @@
+use super::HolderTotals;
+use bigdecimal::{ToPrimitive, Zero};
+use sqlx::types::BigDecimal;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+pub struct ConcentrationMetrics {
+    pub holder_count: usize,
+    pub total_amount: BigDecimal,
+    pub top_1_share: f64,
+    pub top_10_share: f64,
+    pub top_50_share: f64,
+    pub herfindahl_index: f64,
+    pub gini_coefficient: f64,
+}
+
+impl ConcentrationMetrics {
+    /// Shares are fractions of the summed amounts that were passed in, not of the token's
+    /// total supply. Returns `None` when there is nothing to measure.
+    pub fn from_amounts(mut amounts: Vec<BigDecimal>) -> Option<Self> {
+        amounts.retain(|a| a > &BigDecimal::zero());
+        if amounts.is_empty() {
+            return None;
+        }
+        amounts.sort_by(|a, b| b.cmp(a));
+        let total_amount: BigDecimal = amounts.iter().sum();
+        let total = total_amount.to_f64()?;
+        let shares: Vec<f64> = amounts
+            .iter()
+            .map(|a| a.to_f64().unwrap_or(0.0) / total)
+            .collect();
+        let top_share = |n: usize| shares.iter().take(n).sum::<f64>();
+
+        let herfindahl_index = shares.iter().map(|s| s * s).sum();
+
+        // Gini over the shares in ascending order: (2 * sum(i * x_i)) / n - (n + 1) / n,
+        // where the shares already sum to one.
+        let n = shares.len() as f64;
+        let weighted: f64 = shares
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(i, s)| (i as f64 + 1.0) * s)
+            .sum();
+        let gini_coefficient = (2.0 * weighted / n - (n + 1.0) / n).max(0.0);
+
+        Some(Self {
+            holder_count: amounts.len(),
+            top_1_share: top_share(1),
+            top_10_share: top_share(10),
+            top_50_share: top_share(50),
+            herfindahl_index,
+            gini_coefficient,
+            total_amount,
+        })
+    }
+}
+
+impl HolderTotals {
+    pub fn concentration(&self) -> Option<ConcentrationMetrics> {
+        ConcentrationMetrics::from_amounts(
+            self.holders
+                .iter()
+                .map(|h| h.amount.as_ref().clone())
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ConcentrationMetrics;
+    use sqlx::types::BigDecimal;
+
+    fn amounts(values: &[i64]) -> Vec<BigDecimal> {
+        values.iter().map(|v| BigDecimal::from(*v)).collect()
+    }
+
+    fn assert_close(left: f64, right: f64) {
+        assert!((left - right).abs() < 1e-9, "{} != {}", left, right);
+    }
+
+    #[test]
+    fn equal_holders_have_no_concentration() {
+        let metrics = ConcentrationMetrics::from_amounts(amounts(&[25, 25, 25, 25])).unwrap();
+        assert_eq!(metrics.holder_count, 4);
+        assert_close(metrics.top_1_share, 0.25);
+        assert_close(metrics.top_10_share, 1.0);
+        assert_close(metrics.herfindahl_index, 0.25);
+        assert_close(metrics.gini_coefficient, 0.0);
+    }
+
+    #[test]
+    fn a_single_dominant_holder_is_concentrated() {
+        let metrics = ConcentrationMetrics::from_amounts(amounts(&[0, 1, 1, 98])).unwrap();
+        assert_eq!(metrics.holder_count, 3);
+        assert_close(metrics.top_1_share, 0.98);
+        assert_close(metrics.herfindahl_index, 0.98 * 0.98 + 0.0001 + 0.0001);
+        assert_close(
+            metrics.gini_coefficient,
+            2.0 * (0.01 + 0.02 + 0.98 * 3.0) / 3.0 - 4.0 / 3.0,
+        );
+    }
+
+    #[test]
+    fn no_holders_have_no_metrics() {
+        assert!(ConcentrationMetrics::from_amounts(vec![]).is_none());
+        assert!(ConcentrationMetrics::from_amounts(amounts(&[0])).is_none());
+    }
+}
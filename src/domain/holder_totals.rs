@@ -1,15 +1,79 @@
-use super::{Address, Network, TokenName};
-use sqlx::types::BigDecimal;
+use super::{
+    Address, AmountFormat, Network, SnapshotSource, TokenAmount, TokenMetadata, TokenName,
+};
+use chrono::{DateTime, Duration, Utc};
 
 pub struct HolderTotals {
     pub network: Network,
     pub token_name: TokenName,
     pub contract_address: Address,
+    pub source: Option<SnapshotSource>,
+    pub checked_on: Option<DateTime<Utc>>,
+    pub block_number: Option<i64>,
+    pub amount_format: AmountFormat,
+    pub metadata: TokenMetadata,
+    /// In `amount_format`.
+    pub total_supply: Option<TokenAmount>,
     pub holders: Vec<HolderInfo>,
 }
 
+#[derive(Debug)]
 pub struct HolderInfo {
     pub holder_address: Address,
     pub place: i32,
-    pub amount: BigDecimal,
+    pub amount: TokenAmount,
+}
+
+/// How far ahead of the server's clock a submitted `checked_on` may be before it is rejected.
+const ALLOWED_CLOCK_SKEW_SECONDS: i64 = 60;
+
+/// Rejects a `checked_on` more than `ALLOWED_CLOCK_SKEW_SECONDS` (a minute) ahead of `now`, so a
+/// crawler whose clock runs slightly fast isn't turned away.
+pub fn parse_checked_on(
+    checked_on: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Result<DateTime<Utc>, String> {
+    if checked_on > now + Duration::seconds(ALLOWED_CLOCK_SKEW_SECONDS) {
+        return Err(format!("checked_on {} is in the future.", checked_on));
+    }
+    Ok(checked_on)
+}
+
+/// Only negative block numbers are rejected. The server doesn't follow the chain head, and
+/// backfills may submit blocks older than ones already stored, so a block number that hasn't been
+/// mined yet is accepted as given.
+pub fn parse_block_number(block_number: i64) -> Result<i64, String> {
+    if block_number < 0 {
+        return Err(format!("{} is not a valid block number.", block_number));
+    }
+    Ok(block_number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_block_number, parse_checked_on};
+    use chrono::{Duration, Utc};
+    use claim::{assert_err, assert_ok};
+
+    #[test]
+    fn a_past_checked_on_is_accepted() {
+        let now = Utc::now();
+        assert_ok!(parse_checked_on(now - Duration::days(365), now));
+        assert_ok!(parse_checked_on(now + Duration::seconds(30), now));
+    }
+
+    #[test]
+    fn a_future_checked_on_is_rejected() {
+        let now = Utc::now();
+        assert_err!(parse_checked_on(now + Duration::hours(1), now));
+        assert_ok!(parse_checked_on(now + Duration::seconds(60), now));
+        assert_err!(parse_checked_on(now + Duration::seconds(61), now));
+    }
+
+    #[test]
+    fn a_negative_block_number_is_rejected() {
+        assert_ok!(parse_block_number(0));
+        assert_ok!(parse_block_number(14_000_000));
+        assert_err!(parse_block_number(-1));
+    }
 }
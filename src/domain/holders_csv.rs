@@ -0,0 +1,242 @@
+use super::{Address, HolderInfo, Network, TokenAmount};
+use csv::{ReaderBuilder, StringRecord};
+use std::collections::HashSet;
+
+/// A data row of an uploaded holders CSV that could not be used. `row` counts the header as row
+/// 1, the way spreadsheets number lines.
+#[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+pub struct HolderRowError {
+    pub row: usize,
+    pub error: String,
+}
+
+#[derive(Debug)]
+pub struct HoldersCsv {
+    pub holders: Vec<HolderInfo>,
+    pub errors: Vec<HolderRowError>,
+}
+
+struct Columns {
+    place: Option<usize>,
+    holder_address: usize,
+    amount: usize,
+}
+
+fn normalize(header: &str) -> String {
+    header
+        .trim()
+        .trim_start_matches('\u{feff}')
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect::<String>()
+        .to_lowercase()
+}
+
+impl Columns {
+    /// Matches the column names used by block explorer exports, e.g. `Rank`, `Address`,
+    /// `Quantity` and `Percentage`, or `HolderAddress` and `Balance`. Other columns are ignored.
+    fn find(header: &StringRecord) -> Result<Columns, String> {
+        let position = |names: &[&str]| {
+            header
+                .iter()
+                .position(|h| names.contains(&normalize(h).as_str()))
+        };
+        let holder_address = position(&["address", "holderaddress", "holder", "wallet"])
+            .ok_or("The CSV has no address column.")?;
+        let amount = position(&["quantity", "balance", "amount", "quantitytoken"])
+            .ok_or("The CSV has no quantity column.")?;
+        Ok(Columns {
+            place: position(&["rank", "place"]),
+            holder_address,
+            amount,
+        })
+    }
+}
+
+impl HoldersCsv {
+    /// Rows that fail validation are reported in `errors` and left out of `holders`, and so is a
+    /// holder listed a second time. Without a rank column, holders are placed in the order they
+    /// are listed.
+    pub fn parse(csv: &str, network: &Network) -> Result<HoldersCsv, String> {
+        let mut reader = ReaderBuilder::new()
+            .flexible(true)
+            .from_reader(csv.as_bytes());
+        let header = reader
+            .headers()
+            .map_err(|e| format!("The CSV header is unreadable: {}", e))?;
+        if header.is_empty() {
+            return Err("The CSV is empty.".to_string());
+        }
+        let columns = Columns::find(header)?;
+        let mut holders = vec![];
+        let mut errors = vec![];
+        let mut seen = HashSet::new();
+        let mut next_place = 1;
+        let mut row = 1;
+        let mut counted_up_to = 0;
+        for record in reader.records() {
+            let record = record.map_err(|e| format!("The CSV is unreadable: {}", e))?;
+            if record.iter().all(|field| field.trim().is_empty()) {
+                continue;
+            }
+            // The reader skips blank lines without counting them, and a record's position is
+            // before the blank lines it skipped, so rows are counted here to match the line
+            // numbers a spreadsheet shows.
+            let mut start = record
+                .position()
+                .map_or(counted_up_to, |p| p.byte() as usize);
+            while matches!(csv.as_bytes().get(start), Some(b'\r' | b'\n')) {
+                start += 1;
+            }
+            row += count_newlines(&csv.as_bytes()[counted_up_to..start]);
+            counted_up_to = start;
+            match parse_holder(&record, &columns, network, next_place) {
+                Ok(holder) if !seen.insert(holder.holder_address.as_ref().to_string()) => errors
+                    .push(HolderRowError {
+                        row,
+                        error: format!(
+                            "{} is listed more than once.",
+                            holder.holder_address.as_ref()
+                        ),
+                    }),
+                Ok(holder) => {
+                    next_place = holder.place + 1;
+                    holders.push(holder);
+                }
+                Err(error) => errors.push(HolderRowError { row, error }),
+            }
+        }
+        Ok(HoldersCsv { holders, errors })
+    }
+}
+
+fn count_newlines(bytes: &[u8]) -> usize {
+    bytes.iter().filter(|b| **b == b'\n').count()
+}
+
+fn parse_holder(
+    record: &StringRecord,
+    columns: &Columns,
+    network: &Network,
+    next_place: i32,
+) -> Result<HolderInfo, String> {
+    let field = |i: usize| {
+        record
+            .get(i)
+            .map(|f| f.trim())
+            .filter(|f| !f.is_empty())
+            .ok_or_else(|| format!("Column {} is empty.", i + 1))
+    };
+    let place = match columns.place {
+        Some(i) => {
+            let place = field(i)?;
+            place
+                .parse::<i32>()
+                .ok()
+                .filter(|p| *p > 0)
+                .ok_or_else(|| format!("{} is not a valid rank.", place))?
+        }
+        None => next_place,
+    };
+    let holder_address = Address::parse(field(columns.holder_address)?.to_string(), network)?;
+    let amount = TokenAmount::parse(field(columns.amount)?.to_string())?;
+    Ok(HolderInfo {
+        holder_address,
+        place,
+        amount,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HolderRowError, HoldersCsv};
+    use crate::domain::Network;
+    use claim::assert_err;
+    use sqlx::types::BigDecimal;
+
+    fn bsc() -> Network {
+        Network::parse("bsc".to_string()).unwrap()
+    }
+
+    #[test]
+    fn an_explorer_export_is_parsed() {
+        let csv = "\"Rank\",\"Address\",\"Quantity\",\"Percentage\"\r\n\
+            \"1\",\"0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed\",\"1,000,000.5\",\"50.00%\"\r\n\
+            \"2\",\"0xfb6916095ca1df60bb79ce92ce3ea74c37c5d359\",\"250\",\"12.50%\"\r\n";
+        let parsed = HoldersCsv::parse(csv, &bsc()).unwrap();
+        assert!(parsed.errors.is_empty());
+        assert_eq!(parsed.holders.len(), 2);
+        assert_eq!(parsed.holders[0].place, 1);
+        assert_eq!(
+            parsed.holders[0].amount.as_ref(),
+            &"1000000.5".parse::<BigDecimal>().unwrap()
+        );
+        assert_eq!(
+            parsed.holders[1].holder_address.as_ref(),
+            "0xfb6916095ca1df60bb79ce92ce3ea74c37c5d359"
+        );
+    }
+
+    #[test]
+    fn holders_are_placed_in_order_without_a_rank_column() {
+        let csv = "HolderAddress,Balance,PendingBalanceUpdate\n\
+            0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed,10,No\n\
+            0xfb6916095ca1df60bb79ce92ce3ea74c37c5d359,5,No\n";
+        let parsed = HoldersCsv::parse(csv, &bsc()).unwrap();
+        let places: Vec<i32> = parsed.holders.iter().map(|h| h.place).collect();
+        assert_eq!(places, vec![1, 2]);
+    }
+
+    #[test]
+    fn bad_rows_are_reported_and_skipped() {
+        let csv = "Rank,Address,Quantity\n\
+            1,0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed,10\n\
+            2,someholderaddress,5\n\
+            first,0xfb6916095ca1df60bb79ce92ce3ea74c37c5d359,5\n\
+            \n\
+            4,0xdbf03b407c01e7cd3cbea99509d93f8dddc8c6fb,lots\n";
+        let parsed = HoldersCsv::parse(csv, &bsc()).unwrap();
+        assert_eq!(parsed.holders.len(), 1);
+        let rows: Vec<usize> = parsed.errors.iter().map(|e| e.row).collect();
+        assert_eq!(rows, vec![3, 4, 6]);
+        assert_eq!(
+            parsed.errors[1],
+            HolderRowError {
+                row: 4,
+                error: "first is not a valid rank.".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn a_csv_without_the_needed_columns_is_rejected() {
+        assert_err!(HoldersCsv::parse("Rank,Percentage\n1,50%\n", &bsc()));
+        assert_err!(HoldersCsv::parse("", &bsc()));
+    }
+
+    #[test]
+    fn a_holder_listed_twice_is_reported_on_the_later_row() {
+        let csv = "Address,Quantity\n\
+            0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed,10\n\
+            0xfb6916095ca1df60bb79ce92ce3ea74c37c5d359,5\n\
+            0x5AAEB6053F3E94C9B9A09F33669435E7EF1BEAED,1\n";
+        let parsed = HoldersCsv::parse(csv, &bsc()).unwrap();
+        assert_eq!(parsed.holders.len(), 2);
+        assert_eq!(parsed.errors.len(), 1);
+        assert_eq!(parsed.errors[0].row, 4);
+    }
+
+    #[test]
+    fn quoted_fields_may_hold_commas_and_line_breaks() {
+        let csv = "Address,Quantity,Note\n\
+            0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed,\"1,000\",\"team\nwallet\"\n\
+            0xfb6916095ca1df60bb79ce92ce3ea74c37c5d359,\"5\",\"said \"\"hi\"\"\"\n";
+        let parsed = HoldersCsv::parse(csv, &bsc()).unwrap();
+        assert!(parsed.errors.is_empty());
+        assert_eq!(parsed.holders.len(), 2);
+        assert_eq!(
+            parsed.holders[0].amount.as_ref(),
+            &"1000".parse::<BigDecimal>().unwrap()
+        );
+    }
+}
@@ -0,0 +1,95 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+
+/// Points just past the last holder row of a page. Rows are ordered by `checked_on`, then
+/// `snapshot_id`, `place` and `holder_address`, so the next page starts after this key.
+/// Submissions can share a `checked_on`, so the snapshot keeps them apart.
+#[derive(Debug, PartialEq)]
+pub struct HoldersCursor {
+    pub checked_on: DateTime<Utc>,
+    pub snapshot_id: i64,
+    pub place: i32,
+    pub holder_address: String,
+}
+
+impl HoldersCursor {
+    pub fn encode(&self) -> String {
+        base64::encode_config(
+            format!(
+                "{}|{}|{}|{}",
+                self.checked_on.to_rfc3339_opts(SecondsFormat::Nanos, true),
+                self.snapshot_id,
+                self.place,
+                self.holder_address
+            ),
+            base64::URL_SAFE_NO_PAD,
+        )
+    }
+
+    pub fn decode(s: &str) -> Result<HoldersCursor, String> {
+        let invalid = || format!("{} is not a valid cursor.", s);
+        let decoded = base64::decode_config(s, base64::URL_SAFE_NO_PAD)
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .ok_or_else(invalid)?;
+        let mut parts = decoded.splitn(4, '|');
+        let checked_on = parts
+            .next()
+            .and_then(|c| DateTime::parse_from_rfc3339(c).ok())
+            .ok_or_else(invalid)?
+            .with_timezone(&Utc);
+        let snapshot_id = parts
+            .next()
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(invalid)?;
+        let place = parts
+            .next()
+            .and_then(|p| p.parse().ok())
+            .ok_or_else(invalid)?;
+        let holder_address = parts
+            .next()
+            .filter(|h| !h.is_empty())
+            .ok_or_else(invalid)?
+            .to_string();
+        Ok(HoldersCursor {
+            checked_on,
+            snapshot_id,
+            place,
+            holder_address,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HoldersCursor;
+    use chrono::{TimeZone, Utc};
+    use claim::assert_err;
+
+    #[test]
+    fn a_cursor_survives_a_round_trip() {
+        let cursor = HoldersCursor {
+            checked_on: Utc.timestamp(1_643_000_000, 123_456_789),
+            snapshot_id: 7,
+            place: 42,
+            holder_address: "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed".to_string(),
+        };
+        assert_eq!(HoldersCursor::decode(&cursor.encode()).unwrap(), cursor);
+    }
+
+    #[test]
+    fn garbage_is_not_a_cursor() {
+        assert_err!(HoldersCursor::decode("not a cursor"));
+        assert_err!(HoldersCursor::decode(&base64::encode_config(
+            "2022-01-24T00:00:00Z|7|first|0x00",
+            base64::URL_SAFE_NO_PAD
+        )));
+        assert_err!(HoldersCursor::decode(&base64::encode_config(
+            "2022-01-24T00:00:00Z|7|1",
+            base64::URL_SAFE_NO_PAD
+        )));
+        assert_err!(HoldersCursor::decode(&base64::encode_config(
+            "2022-01-24T00:00:00Z|1|0x00",
+            base64::URL_SAFE_NO_PAD
+        )));
+    }
+}
@@ -1,31 +1,59 @@
 mod address;
 mod address_type;
+mod api_key;
+mod condition;
 mod email;
+mod email_event;
 mod holder_description;
+mod holder_description_query;
+mod holder_diff;
+mod holder_metrics;
 mod holder_totals;
+mod holders_csv;
+mod holders_cursor;
 mod legit_token_creator;
 mod network;
 mod new_subscriber;
 mod notes;
 mod scam_creator;
+mod scam_token;
 mod scam_type;
+mod snapshot_source;
 mod subscriber_name;
+mod token;
+mod token_amount;
 mod token_creator_query;
+mod token_metadata;
 mod token_name;
+mod user;
 
-pub use address::Address;
+pub use address::{Address, AddressError};
 pub use address_type::AddressType;
+pub use api_key::{ApiKeyName, ApiScope};
+pub use condition::Condition;
 pub use email::Email;
+pub use email_event::EmailEventKind;
 pub use holder_description::{HolderDescription, HolderDescriptions};
-pub use holder_totals::{HolderInfo, HolderTotals};
+pub use holder_description_query::HolderDescriptionQuery;
+pub use holder_diff::{HolderChange, HolderPosition, HolderSnapshotDiff};
+pub use holder_metrics::ConcentrationMetrics;
+pub use holder_totals::{parse_block_number, parse_checked_on, HolderInfo, HolderTotals};
+pub use holders_csv::{HolderRowError, HoldersCsv};
+pub use holders_cursor::HoldersCursor;
 pub use legit_token_creator::LegitTokenCreator;
 pub use network::Network;
 pub use new_subscriber::NewSubscriber;
 pub use notes::Notes;
 pub use scam_creator::ScamCreator;
+pub use scam_token::ScamToken;
 pub use scam_type::ScamType;
+pub use snapshot_source::SnapshotSource;
 pub use subscriber_name::SubscriberName;
+pub use token::{NewToken, TokenStatus};
+pub use token_amount::TokenAmount;
 pub use token_creator_query::TokenCreatorQuery;
+pub use token_metadata::{parse_decimals, AmountFormat, ScaledAmount, TokenMetadata, TokenSymbol};
 pub use token_name::TokenName;
+pub use user::{NewPassword, Username};
 
-const MAX_LIMIT_CHARACTERS: usize = 255;
+pub(crate) const MAX_LIMIT_CHARACTERS: usize = 255;
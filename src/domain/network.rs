@@ -1,5 +1,4 @@
 use super::MAX_LIMIT_CHARACTERS;
-use sqlx::Type;
 use unicode_segmentation::UnicodeSegmentation;
 
 #[derive(serde::Deserialize, serde::Serialize, PartialEq, Debug, Clone, sqlx::Type)]
@@ -44,6 +43,19 @@ fn derive_network(s: &str) -> Result<Network, String> {
 }
 
 impl Network {
+    pub const ALL: [Network; 10] = [
+        Network::ETH,
+        Network::BSC,
+        Network::ADA,
+        Network::AVAX,
+        Network::MATIC,
+        Network::FTM,
+        Network::SOL,
+        Network::LUNA,
+        Network::DOT,
+        Network::MOVR,
+    ];
+
     pub fn parse(s: String) -> Result<Network, String> {
         let is_empty_or_whitespace = s.trim().is_empty();
         let is_too_long = s.graphemes(true).count() > MAX_LIMIT_CHARACTERS;
@@ -0,0 +1,9 @@
+use super::{Address, Network, Notes, ScamType};
+
+pub struct ScamToken {
+    pub address: Address,
+    pub notes: Notes,
+    pub scam_creator_network: Network,
+    pub scam_creator_address: Address,
+    pub scam_type: ScamType,
+}
@@ -0,0 +1,46 @@
+use super::MAX_LIMIT_CHARACTERS;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Where a holders snapshot was read from, e.g. `bscscan`.
+#[derive(Debug)]
+pub struct SnapshotSource(String);
+
+impl SnapshotSource {
+    pub fn parse(s: String) -> Result<SnapshotSource, String> {
+        let s = s.trim().to_string();
+        let is_empty = s.is_empty();
+        let is_too_long = s.graphemes(true).count() > MAX_LIMIT_CHARACTERS;
+        let forbidden_characters = ['"', '<', '>', '\\', '{', '}'];
+        let contains_forbidden_characters = s.chars().any(|g| forbidden_characters.contains(&g));
+        if is_empty || is_too_long || contains_forbidden_characters {
+            Err(format!("{} is not a valid snapshot source.", s))
+        } else {
+            Ok(Self(s))
+        }
+    }
+}
+
+impl AsRef<str> for SnapshotSource {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SnapshotSource;
+    use claim::{assert_err, assert_ok};
+
+    #[test]
+    fn a_source_is_trimmed() {
+        let source = SnapshotSource::parse(" bscscan ".to_string()).unwrap();
+        assert_eq!(source.as_ref(), "bscscan");
+        assert_ok!(SnapshotSource::parse("covalent api".to_string()));
+    }
+
+    #[test]
+    fn an_empty_source_or_one_with_markup_is_rejected() {
+        assert_err!(SnapshotSource::parse("   ".to_string()));
+        assert_err!(SnapshotSource::parse("<script>".to_string()));
+    }
+}
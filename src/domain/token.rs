@@ -0,0 +1,47 @@
+use super::{Address, Network, TokenMetadata, TokenName};
+
+/// A token as registered through `POST /tokens`.
+pub struct NewToken {
+    pub network: Network,
+    pub contract_address: Address,
+    pub token_name: TokenName,
+    pub metadata: TokenMetadata,
+}
+
+/// What reports say about a token.
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenStatus {
+    Scam,
+    Legit,
+    Unreported,
+}
+
+impl TokenStatus {
+    /// A scam report wins over a legit one, the same way a scam history outweighs a legit one in
+    /// an address report.
+    pub fn from_reports(reported_as_scam: bool, reported_as_legit: bool) -> TokenStatus {
+        if reported_as_scam {
+            TokenStatus::Scam
+        } else if reported_as_legit {
+            TokenStatus::Legit
+        } else {
+            TokenStatus::Unreported
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TokenStatus;
+
+    #[test]
+    fn a_scam_report_outweighs_a_legit_one() {
+        assert_eq!(TokenStatus::from_reports(true, true), TokenStatus::Scam);
+        assert_eq!(TokenStatus::from_reports(false, true), TokenStatus::Legit);
+        assert_eq!(
+            TokenStatus::from_reports(false, false),
+            TokenStatus::Unreported
+        );
+    }
+}
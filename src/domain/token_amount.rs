@@ -0,0 +1,326 @@
+use sqlx::types::BigDecimal;
+use std::convert::TryFrom;
+use std::str::FromStr;
+
+/// Exponents in scientific notation beyond this are refused rather than stored as numbers
+/// Postgres cannot hold.
+const MAX_EXPONENT: i64 = 1000;
+
+/// Characters used to group thousands besides `,` and `.`: spaces, including the no-break spaces
+/// French exports use, and the apostrophes of Swiss ones.
+const GROUPING_CHARACTERS: [char; 6] = [' ', '\u{a0}', '\u{202f}', '\u{2009}', '\'', '\u{2019}'];
+
+/// A holder's balance. It is never negative.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenAmount(BigDecimal);
+
+impl TokenAmount {
+    /// Reads amounts the way block explorers and spreadsheets print them: `1,000,000.5`,
+    /// `1.000.000,5`, `1 000 000,5`, `1'000'000.5` or `1.5e6`. A single `,` followed by exactly
+    /// three digits groups thousands, any other single `,` is a decimal separator.
+    pub fn parse(s: String) -> Result<TokenAmount, String> {
+        let invalid = || format!("{} is not a valid amount.", s);
+        let trimmed = s.trim();
+        let (negative, unsigned) = match trimmed.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, trimmed.strip_prefix('+').unwrap_or(trimmed)),
+        };
+        let (mantissa, exponent) = match unsigned.find(|c| c == 'e' || c == 'E') {
+            Some(i) => (&unsigned[..i], Some(&unsigned[i + 1..])),
+            None => (unsigned, None),
+        };
+        let mantissa = normalize_mantissa(mantissa).ok_or_else(invalid)?;
+        let exponent = match exponent {
+            Some(exponent) => parse_exponent(exponent).ok_or_else(invalid)?,
+            None => 0,
+        };
+        let amount =
+            BigDecimal::from_str(&format!("{}e{}", mantissa, exponent)).map_err(|_| invalid())?;
+        if negative && amount != BigDecimal::from(0) {
+            return Err(format!("{} is negative. Amounts cannot be negative.", s));
+        }
+        Ok(Self(amount))
+    }
+
+    /// Scales an on-chain integer balance down by the token's decimals.
+    pub fn from_raw(raw: TokenAmount, decimals: i16) -> Result<TokenAmount, String> {
+        if !raw.0.is_integer() {
+            return Err(format!(
+                "{} is not a valid raw amount: raw amounts are whole numbers.",
+                raw.0
+            ));
+        }
+        let (digits, scale) = raw.0.into_bigint_and_exponent();
+        Ok(Self(BigDecimal::new(digits, scale + i64::from(decimals))))
+    }
+
+    /// The on-chain integer balance for this amount of a token with `decimals` decimals.
+    pub fn to_raw(&self, decimals: i16) -> Result<BigDecimal, String> {
+        let (digits, scale) = self.0.as_bigint_and_exponent();
+        let raw = BigDecimal::new(digits, scale - i64::from(decimals));
+        if !raw.is_integer() {
+            return Err(format!(
+                "{} has more than the token's {} decimals.",
+                self.0, decimals
+            ));
+        }
+        Ok(raw.with_scale(0))
+    }
+}
+
+impl AsRef<BigDecimal> for TokenAmount {
+    fn as_ref(&self) -> &BigDecimal {
+        &self.0
+    }
+}
+
+/// Reads an amount back from the database, where it was stored as a `NUMERIC`.
+impl TryFrom<BigDecimal> for TokenAmount {
+    type Error = String;
+
+    fn try_from(amount: BigDecimal) -> Result<Self, Self::Error> {
+        if amount < BigDecimal::from(0) {
+            return Err(format!(
+                "{} is negative. Amounts cannot be negative.",
+                amount
+            ));
+        }
+        Ok(Self(amount))
+    }
+}
+
+impl From<TokenAmount> for BigDecimal {
+    fn from(amount: TokenAmount) -> Self {
+        amount.0
+    }
+}
+
+fn parse_exponent(exponent: &str) -> Option<i64> {
+    let digits = exponent
+        .strip_prefix(|c| c == '+' || c == '-')
+        .unwrap_or(exponent);
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    exponent
+        .parse::<i64>()
+        .ok()
+        .filter(|e| e.abs() <= MAX_EXPONENT)
+}
+
+/// Rewrites a mantissa with its grouping removed and `.` as the decimal separator.
+fn normalize_mantissa(mantissa: &str) -> Option<String> {
+    if mantissa
+        .chars()
+        .any(|c| !c.is_ascii_digit() && c != ',' && c != '.' && !GROUPING_CHARACTERS.contains(&c))
+    {
+        return None;
+    }
+    let last_dot = mantissa.rfind('.');
+    let last_comma = mantissa.rfind(',');
+    let dots = mantissa.matches('.').count();
+    let commas = mantissa.matches(',').count();
+    let decimal_separator = match (last_dot, last_comma) {
+        (Some(dot), Some(comma)) if dot > comma => Some(dot),
+        (Some(_), Some(comma)) => Some(comma),
+        (Some(dot), None) if dots == 1 => Some(dot),
+        (None, Some(comma)) if commas == 1 && !groups_thousands(mantissa, comma) => Some(comma),
+        _ => None,
+    };
+    let (integer, fraction) = match decimal_separator {
+        Some(i) => (&mantissa[..i], &mantissa[i + 1..]),
+        None => (mantissa, ""),
+    };
+    let integer = ungroup(integer)?;
+    if !fraction.chars().all(|c| c.is_ascii_digit()) || (integer.is_empty() && fraction.is_empty())
+    {
+        return None;
+    }
+    let integer = if integer.is_empty() { "0" } else { &integer };
+    Some(format!(
+        "{}.{}",
+        integer,
+        if fraction.is_empty() { "0" } else { fraction }
+    ))
+}
+
+/// Whether a lone comma at `comma` reads as `1,000` rather than `1,5`.
+fn groups_thousands(mantissa: &str, comma: usize) -> bool {
+    let after = &mantissa[comma + 1..];
+    after.len() == 3
+        && after.chars().all(|c| c.is_ascii_digit())
+        && !mantissa.contains(|c| GROUPING_CHARACTERS.contains(&c))
+}
+
+/// Removes the thousands separators of an integer part after checking they are used
+/// consistently: one kind of separator, and groups of three digits after the first.
+fn ungroup(integer: &str) -> Option<String> {
+    let mut separators = integer.chars().filter(|c| !c.is_ascii_digit());
+    let separator = match separators.next() {
+        Some(separator) => separator,
+        None => return Some(integer.to_string()),
+    };
+    if separators.any(|c| c != separator) {
+        return None;
+    }
+    let mut groups = integer.split(separator);
+    let first = groups.next()?;
+    if first.is_empty() || first.len() > 3 || !groups.all(|g| g.len() == 3) {
+        return None;
+    }
+    Some(integer.replace(separator, ""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TokenAmount;
+    use claim::{assert_err, assert_ok};
+    use sqlx::types::BigDecimal;
+    use std::str::FromStr;
+
+    fn amount(s: &str) -> BigDecimal {
+        TokenAmount::parse(s.to_string()).unwrap().into()
+    }
+
+    fn decimal(s: &str) -> BigDecimal {
+        BigDecimal::from_str(s).unwrap()
+    }
+
+    /// Separators for thousands and decimals in the styles exports come in.
+    const STYLES: [(&str, &str); 4] = [(",", "."), (".", ","), ("\u{a0}", ","), ("'", ".")];
+
+    /// Fraction lengths, leaving out three digits: `1,234` is read as a thousand and more.
+    const FRACTION_LENGTHS: [usize; 6] = [1, 2, 4, 5, 6, 18];
+
+    #[derive(Debug, Clone)]
+    struct FormattedAmountFixture {
+        text: String,
+        value: BigDecimal,
+    }
+
+    impl quickcheck::Arbitrary for FormattedAmountFixture {
+        fn arbitrary<G: quickcheck::Gen>(g: &mut G) -> Self {
+            let (grouping, decimal_separator) = STYLES[usize::arbitrary(g) % STYLES.len()];
+            let integer = u64::arbitrary(g).to_string();
+            let fraction_length = FRACTION_LENGTHS[usize::arbitrary(g) % FRACTION_LENGTHS.len()];
+            let fraction: String = (0..fraction_length)
+                .map(|_| char::from(b'0' + u8::arbitrary(g) % 10))
+                .collect();
+            let mut groups = vec![];
+            let mut rest = integer.as_str();
+            while rest.len() > 3 {
+                let (head, tail) = rest.split_at(rest.len() - 3);
+                groups.insert(0, tail);
+                rest = head;
+            }
+            groups.insert(0, rest);
+            Self {
+                text: format!("{}{}{}", groups.join(grouping), decimal_separator, fraction),
+                value: decimal(&format!("{}.{}", integer, fraction)),
+            }
+        }
+    }
+
+    #[quickcheck_macros::quickcheck]
+    fn formatted_amounts_are_parsed_to_their_value(fixture: FormattedAmountFixture) -> bool {
+        TokenAmount::parse(fixture.text).map(BigDecimal::from) == Ok(fixture.value)
+    }
+
+    #[quickcheck_macros::quickcheck]
+    fn scientific_notation_is_parsed(mantissa: u32, exponent: i8) -> bool {
+        let text = format!("{}e{}", mantissa, exponent);
+        TokenAmount::parse(text.clone()).map(BigDecimal::from) == Ok(decimal(&text))
+    }
+
+    #[quickcheck_macros::quickcheck]
+    fn negative_amounts_are_rejected(amount: u64) -> bool {
+        amount == 0 || TokenAmount::parse(format!("-{}", amount)).is_err()
+    }
+
+    #[quickcheck_macros::quickcheck]
+    fn parsing_never_panics(text: String) -> bool {
+        let _ = TokenAmount::parse(text);
+        true
+    }
+
+    #[quickcheck_macros::quickcheck]
+    fn raw_amounts_survive_scaling(raw: u64, decimals: u8) -> bool {
+        let decimals = i16::from(decimals);
+        let raw_amount = TokenAmount::parse(raw.to_string()).unwrap();
+        TokenAmount::from_raw(raw_amount, decimals)
+            .and_then(|scaled| scaled.to_raw(decimals))
+            .map(|r| r.to_string())
+            == Ok(raw.to_string())
+    }
+
+    #[test]
+    fn amounts_are_scaled_by_the_decimals() {
+        let raw = TokenAmount::parse("1500000000000000000".to_string()).unwrap();
+        let scaled = TokenAmount::from_raw(raw, 18).unwrap();
+        assert_eq!(scaled.as_ref(), &decimal("1.5"));
+        assert_eq!(scaled.to_raw(6).unwrap(), decimal("1500000"));
+    }
+
+    #[test]
+    fn fractions_of_the_smallest_unit_are_rejected() {
+        assert_err!(TokenAmount::from_raw(
+            TokenAmount::parse("1.5".to_string()).unwrap(),
+            18
+        ));
+        assert_err!(TokenAmount::parse("0.0000001".to_string())
+            .unwrap()
+            .to_raw(6));
+    }
+
+    #[test]
+    fn a_lone_comma_before_three_digits_groups_thousands() {
+        assert_eq!(amount("1,234"), decimal("1234"));
+        assert_eq!(amount("1,5"), decimal("1.5"));
+        assert_eq!(amount("1.234"), decimal("1.234"));
+        assert_eq!(amount("1 234,567"), decimal("1234.567"));
+    }
+
+    #[test]
+    fn signs_and_partial_numbers_are_accepted() {
+        assert_eq!(amount("+12"), decimal("12"));
+        assert_eq!(amount("-0"), decimal("0"));
+        assert_eq!(amount(".5"), decimal("0.5"));
+        assert_eq!(amount("5."), decimal("5"));
+        assert_eq!(amount(" 1.5E-3 "), decimal("0.0015"));
+    }
+
+    #[test]
+    fn malformed_amounts_are_rejected() {
+        for text in [
+            "",
+            "12.3.4",
+            "1e",
+            "1e+",
+            "e5",
+            "1,2,3",
+            "1,00,000",
+            "1.000,000.5",
+            "1 000.000,0.5",
+            "--1",
+            "1-",
+            "0x10",
+            "NaN",
+            "1e100000",
+            ".",
+            "1,,000",
+        ] {
+            assert_err!(
+                TokenAmount::parse(text.to_string()),
+                "{} was accepted",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn amounts_with_consistent_grouping_are_accepted() {
+        assert_ok!(TokenAmount::parse("12,345,678.9".to_string()));
+        assert_ok!(TokenAmount::parse("12.345.678,9".to_string()));
+        assert_ok!(TokenAmount::parse("12\u{202f}345\u{202f}678,9".to_string()));
+    }
+}
@@ -1,5 +1,81 @@
 use super::{Address, Network};
 
+#[derive(Debug)]
 pub struct TokenCreatorQuery {
     pub token_creator_address: Address,
+    /// The networks to look on: the one asked for, or every network where the address is valid.
+    /// EVM chains share one address format, so an `0x` address is looked up on all of them.
+    pub networks: Vec<Network>,
+}
+
+impl TokenCreatorQuery {
+    pub fn parse(
+        token_creator_address: String,
+        network: Option<String>,
+    ) -> Result<TokenCreatorQuery, String> {
+        match network {
+            Some(network) => {
+                let network = Network::parse(network)?;
+                let token_creator_address = Address::parse(token_creator_address, &network)?;
+                Ok(Self {
+                    token_creator_address,
+                    networks: vec![network],
+                })
+            }
+            None => {
+                let token_creator_address =
+                    Address::parse_for_any_network(token_creator_address.clone())?;
+                let networks = Network::ALL
+                    .iter()
+                    .filter(|network| {
+                        Address::parse(token_creator_address.display().to_string(), network).is_ok()
+                    })
+                    .cloned()
+                    .collect();
+                Ok(Self {
+                    token_creator_address,
+                    networks,
+                })
+            }
+        }
+    }
+
+    pub fn network_names(&self) -> Vec<String> {
+        self.networks
+            .iter()
+            .map(|network| network.as_ref().to_string())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TokenCreatorQuery;
+    use claim::assert_err;
+
+    const EVM_ADDRESS: &str = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+
+    #[test]
+    fn an_evm_address_is_looked_up_on_every_evm_chain() {
+        let query = TokenCreatorQuery::parse(EVM_ADDRESS.to_string(), None).unwrap();
+        assert_eq!(
+            query.network_names(),
+            vec!["eth", "bsc", "avax", "matic", "ftm", "movr"]
+        );
+    }
+
+    #[test]
+    fn a_network_filter_limits_the_lookup() {
+        let query =
+            TokenCreatorQuery::parse(EVM_ADDRESS.to_string(), Some("polygon".to_string())).unwrap();
+        assert_eq!(query.network_names(), vec!["matic"]);
+        assert_err!(TokenCreatorQuery::parse(
+            EVM_ADDRESS.to_string(),
+            Some("sol".to_string())
+        ));
+        assert_err!(TokenCreatorQuery::parse(
+            EVM_ADDRESS.to_string(),
+            Some("somesuperchain".to_string())
+        ));
+    }
 }
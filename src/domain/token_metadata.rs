@@ -0,0 +1,165 @@
+use super::{Address, TokenAmount};
+use chrono::{DateTime, Utc};
+use sqlx::types::BigDecimal;
+
+/// ERC-20 and BEP-20 tokens keep their decimals in a `uint8`.
+const MAX_DECIMALS: i16 = 255;
+const MAX_SYMBOL_CHARACTERS: usize = 32;
+
+/// Whether submitted amounts are on-chain integers (`raw`) or already divided by the token's
+/// decimals (`scaled`), the way explorers show them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AmountFormat {
+    Raw,
+    Scaled,
+}
+
+// Deriving `Default` on an enum needs a newer Rust than the Dockerfile builds with.
+#[allow(clippy::derivable_impls)]
+impl Default for AmountFormat {
+    fn default() -> Self {
+        AmountFormat::Scaled
+    }
+}
+
+impl AmountFormat {
+    pub fn parse(s: String) -> Result<AmountFormat, String> {
+        match s.trim().to_lowercase().as_str() {
+            "raw" => Ok(Self::Raw),
+            "scaled" => Ok(Self::Scaled),
+            _ => Err(format!(
+                "{} is not a valid amount format. Use raw or scaled.",
+                s
+            )),
+        }
+    }
+
+    /// Reads a submitted amount of a token whose decimals may not be known yet.
+    pub fn read(
+        &self,
+        amount: &TokenAmount,
+        decimals: Option<i16>,
+    ) -> Result<ScaledAmount, String> {
+        match (self, decimals) {
+            (Self::Raw, Some(decimals)) => Ok(ScaledAmount {
+                amount: TokenAmount::from_raw(amount.clone(), decimals)?,
+                raw_amount: Some(amount.as_ref().with_scale(0)),
+            }),
+            (Self::Raw, None) => Err(
+                "Raw amounts can only be read once the token's decimals are known. Send decimals."
+                    .to_string(),
+            ),
+            (Self::Scaled, decimals) => Ok(ScaledAmount {
+                amount: amount.clone(),
+                raw_amount: decimals.map(|d| amount.to_raw(d)).transpose()?,
+            }),
+        }
+    }
+}
+
+/// An amount as people read it and, when the token's decimals are known, as the integer the
+/// chain stores.
+#[derive(Debug)]
+pub struct ScaledAmount {
+    pub amount: TokenAmount,
+    pub raw_amount: Option<BigDecimal>,
+}
+
+#[derive(Debug)]
+pub struct TokenSymbol(String);
+
+impl TokenSymbol {
+    pub fn parse(s: String) -> Result<TokenSymbol, String> {
+        let symbol = s.trim();
+        let is_empty = symbol.is_empty();
+        let is_too_long = symbol.chars().count() > MAX_SYMBOL_CHARACTERS;
+        let has_forbidden_characters = symbol
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control() || "\"<>\\{}".contains(c));
+        if is_empty || is_too_long || has_forbidden_characters {
+            Err(format!("{} is not a valid token symbol.", s))
+        } else {
+            Ok(Self(symbol.to_string()))
+        }
+    }
+}
+
+impl AsRef<str> for TokenSymbol {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+pub fn parse_decimals(decimals: i16) -> Result<i16, String> {
+    if !(0..=MAX_DECIMALS).contains(&decimals) {
+        return Err(format!(
+            "{} is not a valid number of decimals. Tokens have between 0 and {}.",
+            decimals, MAX_DECIMALS
+        ));
+    }
+    Ok(decimals)
+}
+
+/// What a submission says about its token. Fields left out keep the values stored before.
+#[derive(Debug, Default)]
+pub struct TokenMetadata {
+    pub symbol: Option<TokenSymbol>,
+    pub decimals: Option<i16>,
+    pub creator_address: Option<Address>,
+    pub launched_on: Option<DateTime<Utc>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_decimals, AmountFormat, TokenSymbol};
+    use crate::domain::TokenAmount;
+    use claim::{assert_err, assert_ok};
+    use sqlx::types::BigDecimal;
+    use std::str::FromStr;
+
+    fn amount(s: &str) -> TokenAmount {
+        TokenAmount::parse(s.to_string()).unwrap()
+    }
+
+    #[test]
+    fn raw_amounts_are_scaled_by_the_decimals() {
+        let read = AmountFormat::Raw
+            .read(&amount("1234500000"), Some(9))
+            .unwrap();
+        assert_eq!(
+            read.amount.as_ref(),
+            &BigDecimal::from_str("1.2345").unwrap()
+        );
+        assert_eq!(read.raw_amount, Some(BigDecimal::from(1234500000)));
+    }
+
+    #[test]
+    fn scaled_amounts_get_a_raw_amount_once_decimals_are_known() {
+        let read = AmountFormat::Scaled.read(&amount("1.5"), Some(2)).unwrap();
+        assert_eq!(read.raw_amount, Some(BigDecimal::from(150)));
+        let read = AmountFormat::Scaled.read(&amount("1.5"), None).unwrap();
+        assert_eq!(read.raw_amount, None);
+    }
+
+    #[test]
+    fn amounts_that_do_not_fit_the_decimals_are_rejected() {
+        assert_err!(AmountFormat::Raw.read(&amount("100"), None));
+        assert_err!(AmountFormat::Raw.read(&amount("100.5"), Some(2)));
+        assert_err!(AmountFormat::Scaled.read(&amount("1.005"), Some(2)));
+    }
+
+    #[test]
+    fn formats_symbols_and_decimals_are_validated() {
+        assert_eq!(
+            AmountFormat::parse(" RAW ".to_string()),
+            Ok(AmountFormat::Raw)
+        );
+        assert_err!(AmountFormat::parse("wei".to_string()));
+        assert_ok!(TokenSymbol::parse("CAKE".to_string()));
+        assert_err!(TokenSymbol::parse("SAFE MOON".to_string()));
+        assert_err!(TokenSymbol::parse("".to_string()));
+        assert_ok!(parse_decimals(18));
+        assert_err!(parse_decimals(-1));
+        assert_err!(parse_decimals(256));
+    }
+}
@@ -0,0 +1,73 @@
+const MAX_USERNAME_CHARACTERS: usize = 64;
+const MIN_PASSWORD_CHARACTERS: usize = 12;
+const MAX_PASSWORD_CHARACTERS: usize = 128;
+
+/// An editor's login name: letters, digits, `.`, `_` and `-`.
+#[derive(Debug)]
+pub struct Username(String);
+
+impl Username {
+    pub fn parse(s: String) -> Result<Username, String> {
+        let is_empty = s.is_empty();
+        let is_too_long = s.chars().count() > MAX_USERNAME_CHARACTERS;
+        let has_forbidden_characters = s
+            .chars()
+            .any(|c| !(c.is_ascii_alphanumeric() || c == '.' || c == '_' || c == '-'));
+        if is_empty || is_too_long || has_forbidden_characters {
+            Err(format!("{} is not a valid username.", s))
+        } else {
+            Ok(Self(s))
+        }
+    }
+}
+
+impl AsRef<str> for Username {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+/// A password being set. It is never printed, not even in errors.
+pub struct NewPassword(String);
+
+impl NewPassword {
+    pub fn parse(s: String) -> Result<NewPassword, String> {
+        let length = s.chars().count();
+        if !(MIN_PASSWORD_CHARACTERS..=MAX_PASSWORD_CHARACTERS).contains(&length) {
+            return Err(format!(
+                "Passwords must be between {} and {} characters long.",
+                MIN_PASSWORD_CHARACTERS, MAX_PASSWORD_CHARACTERS
+            ));
+        }
+        Ok(Self(s))
+    }
+}
+
+impl AsRef<str> for NewPassword {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NewPassword, Username};
+    use claim::{assert_err, assert_ok};
+
+    #[test]
+    fn usernames_are_plain_ascii() {
+        assert_ok!(Username::parse("jo.moderator-1".to_string()));
+        assert_err!(Username::parse("".to_string()));
+        assert_err!(Username::parse("jo moderator".to_string()));
+        assert_err!(Username::parse("<jo>".to_string()));
+        assert_err!(Username::parse("j".repeat(65)));
+    }
+
+    #[test]
+    fn passwords_must_be_long_enough() {
+        // Passwords have no `Debug`, so they cannot end up in logs or assertion messages.
+        assert!(NewPassword::parse("short".to_string()).is_err());
+        assert!(NewPassword::parse("correct horse battery".to_string()).is_ok());
+        assert!(NewPassword::parse("p".repeat(129)).is_err());
+    }
+}
@@ -1,5 +1,7 @@
 use crate::domain::Email;
+use chrono::Utc;
 use reqwest::Client;
+use uuid::Uuid;
 
 pub struct EmailClient {
     http_client: Client,
@@ -16,6 +18,22 @@ struct SendEmailRequest<'a> {
     subject: &'a str,
     html_body: &'a str,
     text_body: &'a str,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    headers: &'a [EmailHeader<'a>],
+}
+
+/// An extra header for Postmark to put on the email, e.g. `List-Unsubscribe`.
+#[derive(serde::Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct EmailHeader<'a> {
+    pub name: &'a str,
+    pub value: &'a str,
+}
+
+#[derive(serde::Deserialize)]
+struct SendEmailResponse {
+    #[serde(rename = "MessageID")]
+    message_id: String,
 }
 
 impl EmailClient {
@@ -33,13 +51,27 @@ impl EmailClient {
             authorization_token,
         }
     }
+    /// Returns the MessageID Postmark gave the email. It is `None` when Postmark accepted the
+    /// email but its answer could not be read, so callers do not send it again.
     pub async fn send_email(
         &self,
         recipient: &Email,
         subject: &str,
         html_content: &str,
         text_content: &str,
-    ) -> Result<(), reqwest::Error> {
+    ) -> Result<Option<String>, reqwest::Error> {
+        self.send_email_with_headers(recipient, subject, html_content, text_content, &[])
+            .await
+    }
+
+    pub async fn send_email_with_headers(
+        &self,
+        recipient: &Email,
+        subject: &str,
+        html_content: &str,
+        text_content: &str,
+        headers: &[EmailHeader<'_>],
+    ) -> Result<Option<String>, reqwest::Error> {
         let url = format!("{}/email", self.base_url);
         let request_body = SendEmailRequest {
             from: self.sender.as_ref(),
@@ -47,23 +79,61 @@ impl EmailClient {
             subject,
             html_body: html_content,
             text_body: text_content,
+            headers,
         };
         //build
-        self.http_client
+        let response = self
+            .http_client
             .post(&url)
             .header("X-Postmark-Server-Token", &self.authorization_token)
             .json(&request_body)
             .send()
             .await?
             .error_for_status()?;
-        Ok(())
+        match response.json::<SendEmailResponse>().await {
+            Ok(response) => Ok(Some(response.message_id)),
+            Err(error) => {
+                tracing::warn!(
+                    error.cause_chain = ?error,
+                    "Postmark accepted an email without a readable MessageID.",
+                );
+                Ok(None)
+            }
+        }
     }
 }
 
+/// Keeps the MessageID of a sent email, so that webhooks about it can be traced back to it.
+#[tracing::instrument(name = "Storing an email send", skip(executor, recipient))]
+pub async fn store_email_send<'c, E>(
+    executor: E,
+    provider_message_id: &str,
+    recipient: &Email,
+    newsletter_issue_id: Option<Uuid>,
+) -> Result<(), sqlx::Error>
+where
+    E: sqlx::Executor<'c, Database = sqlx::Postgres>,
+{
+    sqlx::query!(
+        r#"
+        INSERT INTO email_sends (provider_message_id, recipient, newsletter_issue_id, sent_on)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (provider_message_id) DO NOTHING;
+        "#,
+        provider_message_id,
+        recipient.as_ref(),
+        newsletter_issue_id,
+        Utc::now(),
+    )
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use crate::domain::Email;
-    use crate::email_client::EmailClient;
+    use crate::email_client::{EmailClient, EmailHeader};
     use claim::{assert_err, assert_ok};
     use fake::faker::internet::en::SafeEmail;
     use fake::faker::lorem::en::{Paragraph, Sentence};
@@ -129,6 +199,72 @@ mod tests {
             .await;
     }
 
+    #[tokio::test]
+    async fn send_email_returns_the_postmark_message_id() {
+        let mock_server = MockServer::start().await;
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "To": "receiver@example.com",
+                "MessageID": "b7bc2f4a-e38e-4336-af7d-e6c392c2f817",
+                "ErrorCode": 0,
+                "Message": "OK"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let outcome = email_client(mock_server.uri())
+            .send_email(&email(), &subject(), &content(), &content())
+            .await;
+
+        assert_eq!(
+            outcome.unwrap().as_deref(),
+            Some("b7bc2f4a-e38e-4336-af7d-e6c392c2f817")
+        );
+    }
+
+    #[tokio::test]
+    async fn headers_are_only_sent_when_there_are_some() {
+        let mock_server = MockServer::start().await;
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(200))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+        let email_client = email_client(mock_server.uri());
+
+        let _ = email_client
+            .send_email(&email(), &subject(), &content(), &content())
+            .await;
+        let _ = email_client
+            .send_email_with_headers(
+                &email(),
+                &subject(),
+                &content(),
+                &content(),
+                &[EmailHeader {
+                    name: "List-Unsubscribe",
+                    value: "<https://example.com/unsubscribe>",
+                }],
+            )
+            .await;
+
+        let bodies: Vec<serde_json::Value> = mock_server
+            .received_requests()
+            .await
+            .unwrap()
+            .iter()
+            .map(|r| serde_json::from_slice(&r.body).unwrap())
+            .collect();
+        assert!(bodies[0].get("Headers").is_none());
+        assert_eq!(
+            bodies[1]["Headers"],
+            serde_json::json!([
+                {"Name": "List-Unsubscribe", "Value": "<https://example.com/unsubscribe>"}
+            ])
+        );
+    }
+
     #[tokio::test]
     async fn send_email_succeeds_if_the_server_returns_200() {
         let mock_server = MockServer::start().await;
@@ -0,0 +1,325 @@
+//! Middleware that makes retried writes safe. A request carrying an `Idempotency-Key` header runs
+//! once per caller, key and path; repeats get the saved response back instead of running again.
+use crate::api_keys::ApiCaller;
+use crate::domain::MAX_LIMIT_CHARACTERS;
+use actix_web::body::{to_bytes, BoxBody};
+use actix_web::dev::{Payload, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::error::{ErrorInternalServerError, ErrorPayloadTooLarge, PayloadError};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::http::StatusCode;
+use actix_web::{web, Error, HttpMessage, HttpResponse};
+use blake2::{Blake2b, Digest};
+use futures::future::{ready, LocalBoxFuture, Ready};
+use futures::StreamExt;
+use sqlx::PgPool;
+use std::convert::TryFrom;
+use std::rc::Rc;
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// The default body limit, the same as for JSON bodies.
+const DEFAULT_BODY_LIMIT: usize = 2 * 1024 * 1024;
+
+/// Wrap a resource with this to honour `Idempotency-Key` on it. Requests without the header pass
+/// straight through. Keys are kept per API key, so wrap `RequireScope` around it.
+#[derive(Clone)]
+pub struct Idempotency {
+    body_limit: usize,
+}
+
+impl Idempotency {
+    /// The body is buffered to fingerprint it, so it is capped like the handler's extractor
+    /// would cap it. Larger bodies get a 413.
+    pub fn with_body_limit(body_limit: usize) -> Self {
+        Self { body_limit }
+    }
+}
+
+impl Default for Idempotency {
+    fn default() -> Self {
+        Self::with_body_limit(DEFAULT_BODY_LIMIT)
+    }
+}
+
+impl<S> Transform<S, ServiceRequest> for Idempotency
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<BoxBody>, Error = Error> + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Transform = IdempotencyMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(IdempotencyMiddleware {
+            service: Rc::new(service),
+            body_limit: self.body_limit,
+        }))
+    }
+}
+
+pub struct IdempotencyMiddleware<S> {
+    service: Rc<S>,
+    body_limit: usize,
+}
+
+impl<S> Service<ServiceRequest> for IdempotencyMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<BoxBody>, Error = Error> + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_web::dev::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let body_limit = self.body_limit;
+        Box::pin(async move {
+            let key = match idempotency_key(&req) {
+                Ok(Some(key)) => key,
+                Ok(None) => return service.call(req).await,
+                Err(e) => return Ok(req.into_response(HttpResponse::BadRequest().body(e))),
+            };
+            let pool = req
+                .app_data::<web::Data<PgPool>>()
+                .cloned()
+                .ok_or_else(|| ErrorInternalServerError("No database pool was configured."))?;
+            let caller = req
+                .extensions()
+                .get::<ApiCaller>()
+                .map(ApiCaller::id)
+                .ok_or_else(|| ErrorInternalServerError("The request has no API key."))?;
+            let mut req = req;
+            let body = read_payload(&mut req, body_limit).await?;
+            let request_hash = Blake2b::digest(&body).to_vec();
+            let request_path = req.path().to_string();
+
+            let claimed = try_claim(&pool, &caller, &key, &request_path, &request_hash)
+                .await
+                .map_err(ErrorInternalServerError)?;
+            if !claimed {
+                let response = saved_response(&pool, &caller, &key, &request_path, &request_hash)
+                    .await
+                    .map_err(ErrorInternalServerError)?;
+                return Ok(req.into_response(response));
+            }
+
+            let response = match service.call(req).await {
+                Ok(response) => response,
+                Err(e) => {
+                    release(&pool, &caller, &key, &request_path)
+                        .await
+                        .map_err(ErrorInternalServerError)?;
+                    return Err(e);
+                }
+            };
+            let (request, response) = response.into_parts();
+            if response.status().is_server_error() {
+                // Let the client retry with the same key after a failure on our side.
+                release(&pool, &caller, &key, &request_path)
+                    .await
+                    .map_err(ErrorInternalServerError)?;
+                return Ok(ServiceResponse::new(request, response));
+            }
+            let (response, body) = response.into_parts();
+            let body = to_bytes(body).await.map_err(ErrorInternalServerError)?;
+            save_response(&pool, &caller, &key, &request_path, &response, &body)
+                .await
+                .map_err(ErrorInternalServerError)?;
+            Ok(ServiceResponse::new(
+                request,
+                response.set_body(body).map_into_boxed_body(),
+            ))
+        })
+    }
+}
+
+fn idempotency_key(req: &ServiceRequest) -> Result<Option<String>, String> {
+    let header = match req.headers().get(IDEMPOTENCY_KEY_HEADER) {
+        Some(header) => header,
+        None => return Ok(None),
+    };
+    let key = header
+        .to_str()
+        .map_err(|_| format!("The {} header must be plain text.", IDEMPOTENCY_KEY_HEADER))?
+        .trim();
+    if key.is_empty() || key.chars().count() > MAX_LIMIT_CHARACTERS {
+        return Err(format!(
+            "The {} header must be between 1 and {} characters long.",
+            IDEMPOTENCY_KEY_HEADER, MAX_LIMIT_CHARACTERS
+        ));
+    }
+    Ok(Some(key.to_string()))
+}
+
+/// Reads the whole body so it can be fingerprinted, then puts it back for the handler. Stops
+/// with a 413 once the body is over `limit` bytes.
+async fn read_payload(req: &mut ServiceRequest, limit: usize) -> Result<web::Bytes, Error> {
+    let mut payload = req.take_payload();
+    let mut body = web::BytesMut::new();
+    while let Some(chunk) = payload.next().await {
+        let chunk = chunk?;
+        if body.len() + chunk.len() > limit {
+            return Err(ErrorPayloadTooLarge("The request body is too large."));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    let body = body.freeze();
+    let replay = body.clone();
+    req.set_payload(Payload::Stream(Box::pin(futures::stream::once(
+        async move { Ok::<_, PayloadError>(replay) },
+    ))));
+    Ok(body)
+}
+
+/// Claims the key for this request. A key can be claimed again once its response has expired or
+/// when the request that claimed it never finished.
+#[tracing::instrument(name = "Claiming an idempotency key", skip(pool, request_hash))]
+async fn try_claim(
+    pool: &PgPool,
+    caller: &str,
+    key: &str,
+    request_path: &str,
+    request_hash: &[u8],
+) -> Result<bool, sqlx::Error> {
+    let claimed = sqlx::query!(
+        r#"
+        INSERT INTO idempotency (caller, idempotency_key, request_path, request_hash, created_on)
+        VALUES ($1, $2, $3, $4, now())
+        ON CONFLICT (caller, idempotency_key, request_path) DO UPDATE
+            SET request_hash = EXCLUDED.request_hash,
+                response_status_code = NULL,
+                response_header_names = NULL,
+                response_header_values = NULL,
+                response_body = NULL,
+                created_on = EXCLUDED.created_on
+            WHERE idempotency.created_on < now() - INTERVAL '24 hours'
+                OR (idempotency.response_status_code IS NULL
+                    AND idempotency.created_on < now() - INTERVAL '5 minutes')
+        RETURNING idempotency_key;
+        "#,
+        caller,
+        key,
+        request_path,
+        request_hash,
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(claimed.is_some())
+}
+
+/// The response for a key someone else claimed: the saved one when it is done, otherwise a
+/// conflict. A key reused for a different body is rejected.
+async fn saved_response(
+    pool: &PgPool,
+    caller: &str,
+    key: &str,
+    request_path: &str,
+    request_hash: &[u8],
+) -> Result<HttpResponse, anyhow::Error> {
+    let saved = sqlx::query!(
+        r#"
+        SELECT request_hash, response_status_code, response_header_names,
+            response_header_values, response_body
+        FROM idempotency
+        WHERE caller = $1 AND idempotency_key = $2 AND request_path = $3;
+        "#,
+        caller,
+        key,
+        request_path,
+    )
+    .fetch_optional(pool)
+    .await?;
+    let saved = match saved {
+        Some(saved) => saved,
+        // The claim was released between our insert and this read.
+        None => {
+            return Ok(HttpResponse::Conflict()
+                .body("A request with this Idempotency-Key failed. Retry it."))
+        }
+    };
+    if saved.request_hash != request_hash {
+        return Ok(HttpResponse::UnprocessableEntity()
+            .body("This Idempotency-Key was already used for a different request."));
+    }
+    let status_code = match saved.response_status_code {
+        Some(status_code) => StatusCode::from_u16(u16::try_from(status_code)?)?,
+        None => {
+            return Ok(HttpResponse::Conflict()
+                .body("A request with this Idempotency-Key is still being processed."))
+        }
+    };
+    let mut response = HttpResponse::build(status_code);
+    let names = saved.response_header_names.unwrap_or_default();
+    let values = saved.response_header_values.unwrap_or_default();
+    for (name, value) in names.into_iter().zip(values) {
+        response.append_header((HeaderName::try_from(name)?, HeaderValue::try_from(value)?));
+    }
+    Ok(response.body(saved.response_body.unwrap_or_default()))
+}
+
+#[tracing::instrument(name = "Saving an idempotent response", skip(pool, response, body))]
+async fn save_response(
+    pool: &PgPool,
+    caller: &str,
+    key: &str,
+    request_path: &str,
+    response: &HttpResponse<()>,
+    body: &[u8],
+) -> Result<(), sqlx::Error> {
+    let (names, values): (Vec<String>, Vec<String>) = response
+        .headers()
+        .iter()
+        .filter_map(|(name, value)| {
+            value
+                .to_str()
+                .ok()
+                .map(|value| (name.as_str().to_string(), value.to_string()))
+        })
+        .unzip();
+    sqlx::query!(
+        r#"
+        UPDATE idempotency
+        SET response_status_code = $4,
+            response_header_names = $5,
+            response_header_values = $6,
+            response_body = $7
+        WHERE caller = $1 AND idempotency_key = $2 AND request_path = $3;
+        "#,
+        caller,
+        key,
+        request_path,
+        response.status().as_u16() as i16,
+        &names[..],
+        &values[..],
+        body,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+#[tracing::instrument(name = "Releasing an idempotency key", skip(pool))]
+async fn release(
+    pool: &PgPool,
+    caller: &str,
+    key: &str,
+    request_path: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        DELETE FROM idempotency
+        WHERE caller = $1 AND idempotency_key = $2 AND request_path = $3
+            AND response_status_code IS NULL;
+        "#,
+        caller,
+        key,
+        request_path,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,317 @@
+//! Sends queued newsletter issues in the background. Each task is one row of
+//! `issue_delivery_queue`, locked with `FOR UPDATE SKIP LOCKED` so several workers can share the
+//! queue without sending an email twice.
+use crate::configuration::Settings;
+use crate::domain::Email;
+use crate::email_client::{store_email_send, EmailClient, EmailHeader};
+use crate::startup::get_connection_pool;
+use crate::unsubscribe::UnsubscribeLinks;
+use anyhow::Context;
+use chrono::{Duration, Utc};
+use sqlx::{PgPool, Postgres, Transaction};
+use tracing::{field::display, Span};
+use uuid::Uuid;
+
+/// Sends are attempted this many times before a delivery is marked `failed`.
+pub const MAX_ATTEMPTS: i16 = 6;
+
+pub enum ExecutionOutcome {
+    TaskCompleted,
+    EmptyQueue,
+}
+
+pub async fn run_worker_until_stopped(configuration: Settings) -> Result<(), anyhow::Error> {
+    let pool = get_connection_pool(&configuration.database);
+    let email_client = configuration.email_client.client();
+    let unsubscribe_links = UnsubscribeLinks::new(
+        configuration.application.base_url,
+        configuration.application.hmac_secret,
+    );
+    worker_loop(pool, email_client, unsubscribe_links).await
+}
+
+async fn worker_loop(
+    pool: PgPool,
+    email_client: EmailClient,
+    unsubscribe_links: UnsubscribeLinks,
+) -> Result<(), anyhow::Error> {
+    loop {
+        match try_execute_task(&pool, &email_client, &unsubscribe_links).await {
+            Ok(ExecutionOutcome::EmptyQueue) => {
+                actix_web::rt::time::sleep(std::time::Duration::from_secs(10)).await;
+            }
+            Err(_) => {
+                actix_web::rt::time::sleep(std::time::Duration::from_secs(1)).await;
+            }
+            Ok(ExecutionOutcome::TaskCompleted) => {}
+        }
+    }
+}
+
+/// How long to wait before the next attempt, after `n_retries` failed ones: 30 seconds, doubling
+/// up to an hour.
+pub fn retry_delay(n_retries: i16) -> Duration {
+    let seconds = 30i64.saturating_mul(1 << n_retries.clamp(0, 7));
+    Duration::seconds(seconds.min(3600))
+}
+
+/// Sends one due delivery, if there is any.
+#[tracing::instrument(
+    skip_all,
+    fields(newsletter_issue_id = tracing::field::Empty, subscriber_email = tracing::field::Empty),
+    err
+)]
+pub async fn try_execute_task(
+    pool: &PgPool,
+    email_client: &EmailClient,
+    unsubscribe_links: &UnsubscribeLinks,
+) -> Result<ExecutionOutcome, anyhow::Error> {
+    let (mut transaction, task) = match dequeue_task(pool).await? {
+        Some(dequeued) => dequeued,
+        None => return Ok(ExecutionOutcome::EmptyQueue),
+    };
+    Span::current()
+        .record("newsletter_issue_id", &display(task.newsletter_issue_id))
+        .record("subscriber_email", &display(&task.subscriber_email));
+    let subscriber_id = match subscriber(&mut transaction, &task).await? {
+        Some((subscriber_id, status)) if status == "confirmed" => subscriber_id,
+        subscriber => {
+            let status = subscriber.map_or_else(|| "removed".to_string(), |(_, status)| status);
+            skip_delivery(&mut transaction, &task, &status).await?;
+            transaction
+                .commit()
+                .await
+                .context("Failed to commit the skipped delivery.")?;
+            return Ok(ExecutionOutcome::TaskCompleted);
+        }
+    };
+    let issue = get_issue(pool, task.newsletter_issue_id).await?;
+    let unsubscribe_link = unsubscribe_links.link(subscriber_id);
+    let html_content = format!(
+        "{}<p><a href=\"{}\">Unsubscribe</a></p>",
+        issue.html_content, unsubscribe_link
+    );
+    let text_content = format!(
+        "{}\n\nUnsubscribe: {}",
+        issue.text_content, unsubscribe_link
+    );
+    let list_unsubscribe = format!("<{}>", unsubscribe_link);
+    // RFC 8058: mail clients may POST to the link to unsubscribe in one click.
+    let headers = [
+        EmailHeader {
+            name: "List-Unsubscribe",
+            value: &list_unsubscribe,
+        },
+        EmailHeader {
+            name: "List-Unsubscribe-Post",
+            value: "List-Unsubscribe=One-Click",
+        },
+    ];
+    match Email::parse(task.subscriber_email.clone()) {
+        Ok(email) => {
+            match email_client
+                .send_email_with_headers(
+                    &email,
+                    &issue.title,
+                    &html_content,
+                    &text_content,
+                    &headers,
+                )
+                .await
+            {
+                Ok(provider_message_id) => {
+                    if let Some(provider_message_id) = provider_message_id {
+                        store_email_send(
+                            &mut transaction,
+                            &provider_message_id,
+                            &email,
+                            Some(task.newsletter_issue_id),
+                        )
+                        .await
+                        .context("Failed to store the email send.")?;
+                    }
+                    mark_delivered(&mut transaction, &task).await?
+                }
+                Err(error) => {
+                    tracing::warn!(
+                        error.cause_chain = ?error,
+                        "Failed to deliver issue to a confirmed subscriber.",
+                    );
+                    record_failure(&mut transaction, &task, &error.to_string(), true).await?;
+                }
+            }
+        }
+        Err(error) => {
+            tracing::error!(
+                error.cause_chain = ?error,
+                "Skipping a confirmed subscriber. Their stored contact details are invalid",
+            );
+            record_failure(&mut transaction, &task, &error, false).await?;
+        }
+    }
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the delivery.")?;
+    Ok(ExecutionOutcome::TaskCompleted)
+}
+
+struct Task {
+    newsletter_issue_id: Uuid,
+    subscriber_email: String,
+    n_retries: i16,
+}
+
+type PgTransaction = Transaction<'static, Postgres>;
+
+#[tracing::instrument(skip_all)]
+async fn dequeue_task(pool: &PgPool) -> Result<Option<(PgTransaction, Task)>, anyhow::Error> {
+    let mut transaction = pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+    let task = sqlx::query_as!(
+        Task,
+        r#"
+        SELECT newsletter_issue_id, subscriber_email, n_retries
+        FROM issue_delivery_queue
+        WHERE status = 'pending' AND execute_after <= now()
+        ORDER BY execute_after
+        FOR UPDATE
+        SKIP LOCKED
+        LIMIT 1;
+        "#,
+    )
+    .fetch_optional(&mut transaction)
+    .await
+    .context("Failed to dequeue a delivery.")?;
+    Ok(task.map(|task| (transaction, task)))
+}
+
+async fn mark_delivered(transaction: &mut PgTransaction, task: &Task) -> Result<(), anyhow::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE issue_delivery_queue
+        SET status = 'delivered', delivered_on = now(), last_error = NULL
+        WHERE newsletter_issue_id = $1 AND subscriber_email = $2;
+        "#,
+        task.newsletter_issue_id,
+        task.subscriber_email,
+    )
+    .execute(transaction)
+    .await
+    .context("Failed to mark the delivery as delivered.")?;
+    Ok(())
+}
+
+/// The subscriber's id and current status. Subscribers who bounced, complained or unsubscribed
+/// since the issue was published are skipped.
+async fn subscriber(
+    transaction: &mut PgTransaction,
+    task: &Task,
+) -> Result<Option<(Uuid, String)>, anyhow::Error> {
+    let subscriber = sqlx::query!(
+        r#"
+        SELECT id, status FROM subscriptions WHERE email = $1;
+        "#,
+        task.subscriber_email,
+    )
+    .fetch_optional(transaction)
+    .await
+    .context("Failed to fetch the subscriber.")?;
+    Ok(subscriber.map(|r| (r.id, r.status)))
+}
+
+async fn skip_delivery(
+    transaction: &mut PgTransaction,
+    task: &Task,
+    subscriber_status: &str,
+) -> Result<(), anyhow::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE issue_delivery_queue
+        SET status = 'skipped', last_error = $3
+        WHERE newsletter_issue_id = $1 AND subscriber_email = $2;
+        "#,
+        task.newsletter_issue_id,
+        task.subscriber_email,
+        format!("The subscriber is {}.", subscriber_status),
+    )
+    .execute(transaction)
+    .await
+    .context("Failed to skip the delivery.")?;
+    Ok(())
+}
+
+/// Schedules another attempt with backoff, or gives up when `retry` is false or the attempts are
+/// used up.
+async fn record_failure(
+    transaction: &mut PgTransaction,
+    task: &Task,
+    error: &str,
+    retry: bool,
+) -> Result<(), anyhow::Error> {
+    let n_retries = task.n_retries + 1;
+    let status = if retry && n_retries < MAX_ATTEMPTS {
+        "pending"
+    } else {
+        "failed"
+    };
+    sqlx::query!(
+        r#"
+        UPDATE issue_delivery_queue
+        SET status = $3, n_retries = $4, execute_after = $5, last_error = $6
+        WHERE newsletter_issue_id = $1 AND subscriber_email = $2;
+        "#,
+        task.newsletter_issue_id,
+        task.subscriber_email,
+        status,
+        n_retries,
+        Utc::now() + retry_delay(task.n_retries),
+        error,
+    )
+    .execute(transaction)
+    .await
+    .context("Failed to record the failed delivery.")?;
+    Ok(())
+}
+
+struct NewsletterIssue {
+    title: String,
+    text_content: String,
+    html_content: String,
+}
+
+async fn get_issue(
+    pool: &PgPool,
+    newsletter_issue_id: Uuid,
+) -> Result<NewsletterIssue, anyhow::Error> {
+    let issue = sqlx::query_as!(
+        NewsletterIssue,
+        r#"
+        SELECT title, text_content, html_content
+        FROM newsletter_issues
+        WHERE newsletter_issue_id = $1;
+        "#,
+        newsletter_issue_id,
+    )
+    .fetch_one(pool)
+    .await
+    .context("Failed to fetch the newsletter issue.")?;
+    Ok(issue)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::retry_delay;
+    use chrono::Duration;
+
+    #[test]
+    fn retries_back_off_up_to_an_hour() {
+        assert_eq!(retry_delay(0), Duration::seconds(30));
+        assert_eq!(retry_delay(1), Duration::seconds(60));
+        assert_eq!(retry_delay(3), Duration::seconds(240));
+        assert_eq!(retry_delay(7), Duration::seconds(3600));
+        assert_eq!(retry_delay(i16::MAX), Duration::seconds(3600));
+    }
+}
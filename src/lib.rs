@@ -1,7 +1,13 @@
 #![allow(clippy::toplevel_ref_arg)]
+pub mod api_keys;
+pub mod authentication;
 pub mod configuration;
 pub mod domain;
 pub mod email_client;
+pub mod idempotency;
+pub mod issue_delivery_worker;
 pub mod routes;
+pub mod scoring;
 pub mod startup;
 pub mod telemetry;
+pub mod unsubscribe;
@@ -1,9 +1,11 @@
+use futures::future::{select, Either};
 use whale_watcher_server::configuration::get_configuration;
+use whale_watcher_server::issue_delivery_worker::run_worker_until_stopped;
 use whale_watcher_server::startup::Application;
 use whale_watcher_server::telemetry::{get_subscriber, init_subscriber};
 
 #[actix_web::main]
-async fn main() -> std::io::Result<()> {
+async fn main() -> anyhow::Result<()> {
     let subscriber = get_subscriber(
         "whale_watcher_server".into(),
         "info".into(),
@@ -11,7 +13,13 @@ async fn main() -> std::io::Result<()> {
     );
     init_subscriber(subscriber);
     let configuration = get_configuration().expect("Failed to read configuration.");
-    let application = Application::build(configuration).await?;
-    application.run_until_stopped().await?;
+    let application = Application::build(configuration.clone()).await?;
+    let application_task = Box::pin(application.run_until_stopped());
+    let worker_task = Box::pin(run_worker_until_stopped(configuration));
+    // Whichever stops first takes the process down with it.
+    match select(application_task, worker_task).await {
+        Either::Left((outcome, _)) => outcome?,
+        Either::Right((outcome, _)) => outcome?,
+    }
     Ok(())
 }
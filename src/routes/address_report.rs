@@ -0,0 +1,244 @@
+use super::BlockchainAppError;
+use crate::domain::{Address, AddressType, Condition, Network};
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use sqlx::types::BigDecimal;
+use sqlx::PgPool;
+
+#[derive(serde::Deserialize)]
+pub struct ReportPath {
+    network: String,
+    address: String,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct ScamCreation {
+    pub contract_address: String,
+    pub notes: Option<String>,
+    pub scam_type: Option<String>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct LegitCreation {
+    pub contract_address: String,
+    pub notes: Option<String>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct AddressDescription {
+    pub contract_address: String,
+    pub notes: Option<String>,
+    pub address_types: Vec<String>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct Holding {
+    pub contract_address: String,
+    pub token_name: String,
+    pub place: i32,
+    pub amount: BigDecimal,
+    pub checked_on: DateTime<Utc>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct AddressReport {
+    pub network: String,
+    pub address: String,
+    pub verdict: Condition,
+    pub scam_creations: Vec<ScamCreation>,
+    pub legit_creations: Vec<LegitCreation>,
+    pub descriptions: Vec<AddressDescription>,
+    pub holdings: Vec<Holding>,
+}
+
+#[allow(clippy::async_yields_async)]
+#[tracing::instrument(
+    name = "Building an address report.",
+    skip(path, pool),
+    fields(
+        network = % path.network,
+        address = % path.address
+    )
+)]
+pub async fn get_address_report(
+    path: web::Path<ReportPath>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, BlockchainAppError> {
+    let path = path.into_inner();
+    let network = Network::parse(path.network).map_err(BlockchainAppError::ValidationError)?;
+    let address = Address::parse(path.address, &network)
+        .map_err(|e| BlockchainAppError::ValidationError(e.into()))?;
+
+    let scam_creations = get_scam_creations(&pool, &network, &address)
+        .await
+        .context("Failed to fetch the scams created by the address.")?;
+    let legit_creations = get_legit_creations(&pool, &network, &address)
+        .await
+        .context("Failed to fetch the legit tokens created by the address.")?;
+    let descriptions = get_descriptions(&pool, &network, &address)
+        .await
+        .context("Failed to fetch the descriptions of the address.")?;
+    let holdings = get_latest_holdings(&pool, &network, &address)
+        .await
+        .context("Failed to fetch the holdings of the address.")?;
+
+    let address_types: Vec<AddressType> = descriptions
+        .iter()
+        .flat_map(|d| d.address_types.iter())
+        .filter_map(|t| AddressType::parse(t.to_string()).ok())
+        .collect();
+    let verdict =
+        Condition::for_address(scam_creations.len(), legit_creations.len(), &address_types);
+
+    Ok(HttpResponse::Ok().json(AddressReport {
+        network: network.as_ref().to_string(),
+        address: address.display().to_string(),
+        verdict,
+        scam_creations,
+        legit_creations,
+        descriptions,
+        holdings,
+    }))
+}
+
+#[tracing::instrument(name = "Fetching scam creations.", skip(pool, network, address))]
+async fn get_scam_creations(
+    pool: &PgPool,
+    network: &Network,
+    address: &Address,
+) -> Result<Vec<ScamCreation>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT COALESCE(c.display_address, s.scammed_contract_address) AS "contract_address!",
+            s.notes, NULL::text AS scam_type
+        FROM scam_token_creators s
+        INNER JOIN networks n
+            ON n.network_id = s.network_of_scammed_token AND n.network_name = $1
+        LEFT JOIN addresses c
+            ON c.network_id = s.network_of_scammed_token AND c.address = s.scammed_contract_address
+        WHERE s.address = $2
+        UNION ALL
+        SELECT COALESCE(c.display_address, t.address) AS "contract_address!",
+            t.notes, t.scam_type::text AS scam_type
+        FROM scam_tokens t
+        INNER JOIN networks n
+            ON n.network_id = t.scam_creator_network AND n.network_name = $1
+        LEFT JOIN addresses c
+            ON c.network_id = t.scam_creator_network AND c.address = t.address
+        WHERE t.scam_creator_address = $2;
+        "#,
+        network.as_ref(),
+        address.as_ref(),
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|r| ScamCreation {
+            contract_address: r.contract_address,
+            notes: r.notes,
+            scam_type: r.scam_type,
+        })
+        .collect())
+}
+
+#[tracing::instrument(name = "Fetching legit creations.", skip(pool, network, address))]
+async fn get_legit_creations(
+    pool: &PgPool,
+    network: &Network,
+    address: &Address,
+) -> Result<Vec<LegitCreation>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT COALESCE(c.display_address, l.legit_contract_address) AS "contract_address!",
+            l.notes
+        FROM legit_token_creators l
+        INNER JOIN networks n
+            ON n.network_id = l.network_of_legit_token AND n.network_name = $1
+        LEFT JOIN addresses c
+            ON c.network_id = l.network_of_legit_token AND c.address = l.legit_contract_address
+        WHERE l.address = $2;
+        "#,
+        network.as_ref(),
+        address.as_ref(),
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|r| LegitCreation {
+            contract_address: r.contract_address,
+            notes: r.notes,
+        })
+        .collect())
+}
+
+#[tracing::instrument(name = "Fetching address descriptions.", skip(pool, network, address))]
+async fn get_descriptions(
+    pool: &PgPool,
+    network: &Network,
+    address: &Address,
+) -> Result<Vec<AddressDescription>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT COALESCE(c.display_address, h.contract_address) AS "contract_address!",
+            h.notes, h.address_types
+        FROM holder_descriptions h
+        INNER JOIN networks n
+            ON n.network_id = h.network_id AND n.network_name = $1
+        LEFT JOIN addresses c
+            ON c.network_id = h.network_id AND c.address = h.contract_address
+        WHERE h.holder_address = $2;
+        "#,
+        network.as_ref(),
+        address.as_ref(),
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|r| AddressDescription {
+            contract_address: r.contract_address,
+            notes: r.notes,
+            address_types: r.address_types,
+        })
+        .collect())
+}
+
+#[tracing::instrument(name = "Fetching latest holdings.", skip(pool, network, address))]
+async fn get_latest_holdings(
+    pool: &PgPool,
+    network: &Network,
+    address: &Address,
+) -> Result<Vec<Holding>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT DISTINCT ON (h.contract_address)
+            c.display_address AS contract_address, t.token_name AS "token_name!", h.place, h.amount, h.checked_on
+        FROM holder_totals h
+        INNER JOIN networks n
+            ON n.network_id = h.network_id AND n.network_name = $1
+        INNER JOIN tokens t
+            ON t.network_id = h.network_id AND t.contract_address = h.contract_address
+        INNER JOIN addresses c
+            ON c.network_id = h.network_id AND c.address = h.contract_address
+        WHERE h.holder_address = $2
+        ORDER BY h.contract_address, h.checked_on DESC;
+        "#,
+        network.as_ref(),
+        address.as_ref(),
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|r| Holding {
+            contract_address: r.contract_address,
+            token_name: r.token_name,
+            place: r.place,
+            amount: r.amount,
+            checked_on: r.checked_on,
+        })
+        .collect())
+}
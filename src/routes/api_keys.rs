@@ -0,0 +1,152 @@
+use super::BlockchainAppError;
+use crate::api_keys::{generate_api_key, hash_api_key};
+use crate::domain::{ApiKeyName, ApiScope};
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use std::convert::{TryFrom, TryInto};
+use uuid::Uuid;
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct ApiKeyFormData {
+    name: String,
+    scopes: Vec<String>,
+}
+
+pub struct NewApiKey {
+    name: ApiKeyName,
+    scopes: Vec<ApiScope>,
+}
+
+impl TryFrom<ApiKeyFormData> for NewApiKey {
+    type Error = String;
+
+    fn try_from(value: ApiKeyFormData) -> Result<Self, Self::Error> {
+        let name = ApiKeyName::parse(value.name)?;
+        let mut scopes = vec![];
+        for scope in value.scopes {
+            let scope = ApiScope::parse(scope)?;
+            if !scopes.contains(&scope) {
+                scopes.push(scope);
+            }
+        }
+        if scopes.is_empty() {
+            return Err("An API key needs at least one scope.".to_string());
+        }
+        Ok(Self { name, scopes })
+    }
+}
+
+/// A minted key. `api_key` is only ever shown in this response.
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct MintedApiKey {
+    pub api_key_id: String,
+    pub api_key: String,
+    pub name: String,
+    pub scopes: Vec<String>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct ApiKeyData {
+    pub api_key_id: String,
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub created_on: DateTime<Utc>,
+    pub revoked_on: Option<DateTime<Utc>>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct ApiKeysResponse {
+    pub data: Vec<ApiKeyData>,
+}
+
+#[allow(clippy::async_yields_async)]
+#[tracing::instrument(
+    name = "Minting an API key.",
+    skip(form, pool),
+    fields(name = %form.name)
+)]
+pub async fn mint_api_key(
+    form: web::Json<ApiKeyFormData>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, BlockchainAppError> {
+    let new_api_key: NewApiKey = form
+        .0
+        .try_into()
+        .map_err(BlockchainAppError::ValidationError)?;
+    let api_key_id = Uuid::new_v4();
+    let api_key = generate_api_key();
+    let scopes: Vec<String> = new_api_key
+        .scopes
+        .iter()
+        .map(|s| s.as_ref().to_string())
+        .collect();
+    sqlx::query!(
+        r#"
+        INSERT INTO api_keys (api_key_id, key_hash, name, scopes, created_on)
+        VALUES ($1, $2, $3, $4, $5);
+        "#,
+        api_key_id,
+        hash_api_key(&api_key),
+        new_api_key.name.as_ref(),
+        &scopes[..],
+        Utc::now(),
+    )
+    .execute(pool.get_ref())
+    .await
+    .context("Failed to store the API key in the database.")?;
+    Ok(HttpResponse::Ok().json(MintedApiKey {
+        api_key_id: api_key_id.to_string(),
+        api_key,
+        name: new_api_key.name.as_ref().to_string(),
+        scopes,
+    }))
+}
+
+#[allow(clippy::async_yields_async)]
+#[tracing::instrument(name = "Listing API keys.", skip(pool))]
+pub async fn get_api_keys(pool: web::Data<PgPool>) -> Result<HttpResponse, BlockchainAppError> {
+    let data = sqlx::query_as!(
+        ApiKeyData,
+        r#"
+        SELECT api_key_id::text AS "api_key_id!", name, scopes, created_on, revoked_on
+        FROM api_keys
+        ORDER BY created_on;
+        "#,
+    )
+    .fetch_all(pool.get_ref())
+    .await
+    .context("Failed to fetch the API keys from the database.")?;
+    Ok(HttpResponse::Ok().json(ApiKeysResponse { data }))
+}
+
+#[allow(clippy::async_yields_async)]
+#[tracing::instrument(name = "Revoking an API key.", skip(path, pool))]
+pub async fn revoke_api_key(
+    path: web::Path<String>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, BlockchainAppError> {
+    let api_key_id = path.into_inner();
+    let api_key_id = Uuid::parse_str(&api_key_id).map_err(|_| {
+        BlockchainAppError::ValidationError(format!("{} is not a valid API key id.", api_key_id))
+    })?;
+    let revoked = sqlx::query!(
+        r#"
+        UPDATE api_keys SET revoked_on = now()
+        WHERE api_key_id = $1 AND revoked_on IS NULL
+        RETURNING api_key_id;
+        "#,
+        api_key_id,
+    )
+    .fetch_optional(pool.get_ref())
+    .await
+    .context("Failed to revoke the API key in the database.")?;
+    match revoked {
+        Some(_) => Ok(HttpResponse::Ok().finish()),
+        None => Err(BlockchainAppError::NotFoundError(format!(
+            "There is no active API key {}.",
+            api_key_id
+        ))),
+    }
+}
@@ -0,0 +1,91 @@
+use super::BlockchainAppError;
+use crate::domain::{Address, Network, TokenStatus};
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct CreatorTokenData {
+    pub contract_address: String,
+    pub token_name: Option<String>,
+    pub symbol: Option<String>,
+    pub launched_on: Option<DateTime<Utc>>,
+    pub status: TokenStatus,
+    pub scam_types: Vec<String>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct CreatorTokensResponse {
+    pub network: String,
+    pub creator_address: String,
+    pub tokens: Vec<CreatorTokenData>,
+}
+
+/// Every token the address deployed, whatever it was reported as, oldest launch first.
+#[allow(clippy::async_yields_async)]
+#[tracing::instrument(name = "Getting the tokens of a creator.", skip(path, pool))]
+pub async fn get_creator_tokens(
+    path: web::Path<(String, String)>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, BlockchainAppError> {
+    let (network, creator_address) = path.into_inner();
+    let network = Network::parse(network).map_err(BlockchainAppError::ValidationError)?;
+    let creator_address = Address::parse(creator_address, &network)
+        .map_err(|e| BlockchainAppError::ValidationError(e.into()))?;
+
+    let rows = sqlx::query!(
+        r#"
+        SELECT c.display_address AS contract_address, t.token_name, t.symbol, t.launched_on,
+            (
+                EXISTS (
+                    SELECT 1 FROM scam_tokens s
+                    WHERE s.scam_creator_network = t.network_id AND s.address = t.contract_address
+                ) OR EXISTS (
+                    SELECT 1 FROM scam_token_creators s
+                    WHERE s.network_of_scammed_token = t.network_id
+                        AND s.scammed_contract_address = t.contract_address
+                )
+            ) AS "reported_as_scam!",
+            EXISTS (
+                SELECT 1 FROM legit_tokens l
+                WHERE l.creator_network = t.network_id AND l.address = t.contract_address
+            ) AS "reported_as_legit!",
+            ARRAY(
+                SELECT DISTINCT s.scam_type::text FROM scam_tokens s
+                WHERE s.scam_creator_network = t.network_id
+                    AND s.address = t.contract_address
+                    AND s.scam_type IS NOT NULL
+            ) AS "scam_types!"
+        FROM tokens t
+        INNER JOIN networks n
+            ON n.network_id = t.network_id
+        INNER JOIN addresses c
+            ON c.network_id = t.network_id AND c.address = t.contract_address
+        WHERE n.network_name = $1 AND t.creator_address = $2
+        ORDER BY t.launched_on NULLS LAST, t.contract_address;
+        "#,
+        network.as_ref(),
+        creator_address.as_ref(),
+    )
+    .fetch_all(pool.get_ref())
+    .await
+    .context("Failed to fetch the tokens of the creator from the database.")?;
+
+    let tokens = rows
+        .into_iter()
+        .map(|r| CreatorTokenData {
+            contract_address: r.contract_address,
+            token_name: r.token_name,
+            symbol: r.symbol,
+            launched_on: r.launched_on,
+            status: TokenStatus::from_reports(r.reported_as_scam, r.reported_as_legit),
+            scam_types: r.scam_types,
+        })
+        .collect();
+    Ok(HttpResponse::Ok().json(CreatorTokensResponse {
+        network: network.as_ref().to_string(),
+        creator_address: creator_address.display().to_string(),
+        tokens,
+    }))
+}
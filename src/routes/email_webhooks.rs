@@ -0,0 +1,110 @@
+use super::BlockchainAppError;
+use crate::domain::EmailEventKind;
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+/// The fields of Postmark's bounce and spam-complaint webhooks that matter here. Other record
+/// types, such as deliveries and opens, are accepted and ignored.
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PostmarkWebhook {
+    record_type: String,
+    #[serde(rename = "MessageID")]
+    message_id: Option<String>,
+    email: Option<String>,
+    #[serde(rename = "Type")]
+    bounce_type: Option<String>,
+    #[serde(default)]
+    inactive: bool,
+    description: Option<String>,
+    bounced_at: Option<DateTime<Utc>>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct EmailWebhookResponse {
+    /// `hard_bounce`, `soft_bounce` or `spam_complaint`. `None` for ignored record types.
+    pub event: Option<String>,
+    /// The subscriber's new status, when the event ends their subscription.
+    pub subscriber_status: Option<String>,
+}
+
+/// Records a Postmark event on the send it is about and stops mailing subscribers whose address
+/// bounced for good or who marked an email as spam.
+#[allow(clippy::async_yields_async)]
+#[tracing::instrument(
+    name = "Receiving an email webhook",
+    skip(webhook, pool),
+    fields(record_type = %webhook.record_type, message_id = ?webhook.message_id)
+)]
+pub async fn receive_email_webhook(
+    webhook: web::Json<PostmarkWebhook>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, BlockchainAppError> {
+    let webhook = webhook.into_inner();
+    let kind = match webhook.record_type.as_str() {
+        "Bounce" => EmailEventKind::bounce(
+            webhook.bounce_type.as_deref().unwrap_or_default(),
+            webhook.inactive,
+        ),
+        "SpamComplaint" => EmailEventKind::SpamComplaint,
+        _ => {
+            return Ok(HttpResponse::Ok().json(EmailWebhookResponse {
+                event: None,
+                subscriber_status: None,
+            }))
+        }
+    };
+    let email = webhook.email.ok_or_else(|| {
+        BlockchainAppError::ValidationError(format!(
+            "A {} webhook needs the Email it is about.",
+            webhook.record_type
+        ))
+    })?;
+    let event_on = webhook.bounced_at.unwrap_or_else(Utc::now);
+    let mut transaction = pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+    if let Some(message_id) = &webhook.message_id {
+        sqlx::query!(
+            r#"
+            UPDATE email_sends SET event = $2, event_detail = $3, event_on = $4
+            WHERE provider_message_id = $1;
+            "#,
+            message_id,
+            kind.as_ref(),
+            webhook.description,
+            event_on,
+        )
+        .execute(&mut transaction)
+        .await
+        .context("Failed to record the event on the email send.")?;
+    }
+    let subscriber_status = match kind.subscriber_status() {
+        // A complaint outranks a bounce, so a bounce never replaces one.
+        Some(status) => sqlx::query!(
+            r#"
+            UPDATE subscriptions SET status = $2
+            WHERE email = $1 AND (status <> 'complained' OR $2 = 'complained')
+            RETURNING status;
+            "#,
+            email,
+            status,
+        )
+        .fetch_optional(&mut transaction)
+        .await
+        .context("Failed to update the subscriber's status.")?
+        .map(|r| r.status),
+        None => None,
+    };
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the email event.")?;
+    Ok(HttpResponse::Ok().json(EmailWebhookResponse {
+        event: Some(kind.as_ref().to_string()),
+        subscriber_status,
+    }))
+}
@@ -1,16 +1,15 @@
 use super::{
-    error_chain_fmt, insert_address, insert_network, insert_token_name, BlockchainAppError,
+    change_author, error_chain_fmt, insert_address, insert_network, lock_holder_description,
+    record_holder_description_change, BlockchainAppError, DescriptionState,
 };
-use crate::domain::{Address, AddressType, HolderDescription, HolderDescriptions, Network, Notes};
-use actix_web::ResponseError;
-use actix_web::{web, HttpResponse};
+use crate::domain::{
+    Address, AddressType, HolderDescription, HolderDescriptionQuery, HolderDescriptions, Network,
+    Notes,
+};
+use actix_web::{web, HttpRequest, HttpResponse};
 use anyhow::Context;
-use chrono::{DateTime, Utc};
-use sqlx::types::BigDecimal;
 use sqlx::{PgPool, Postgres, Transaction};
 use std::convert::{TryFrom, TryInto};
-use tracing_futures::Instrument;
-use uuid::Uuid;
 
 #[derive(serde::Deserialize, serde::Serialize)]
 pub struct HolderData {
@@ -34,8 +33,8 @@ impl TryFrom<FormData> for HolderDescriptions {
         let network = Network::parse(value.network_name)?;
         for holder in value.holder_descriptions {
             let mut address_types = vec![];
-            let holder_address = Address::parse(holder.holder_address)?;
-            let contract_address = Address::parse(holder.contract_address)?;
+            let holder_address = Address::parse(holder.holder_address, &network)?;
+            let contract_address = Address::parse(holder.contract_address, &network)?;
             for address_type in holder.address_types {
                 let at = AddressType::parse(address_type)?;
                 address_types.push(at)
@@ -60,11 +59,13 @@ impl TryFrom<FormData> for HolderDescriptions {
 pub async fn add_holder_descriptions(
     form: web::Json<FormData>,
     pool: web::Data<PgPool>,
+    request: HttpRequest,
 ) -> Result<HttpResponse, BlockchainAppError> {
     let holder_descriptions: HolderDescriptions = form
         .0
         .try_into()
         .map_err(BlockchainAppError::ValidationError)?;
+    let author = change_author(&request)?;
 
     let mut transaction = pool
         .begin()
@@ -95,9 +96,20 @@ pub async fn add_holder_descriptions(
             "Failed to insert contract address {} in the database.",
             &holder.contract_address.as_ref()
         ))?;
-        insert_holder_description(
+        let previous = lock_holder_description(
+            &mut transaction,
+            holder_descriptions.network.as_ref(),
+            &holder.holder_address,
+            &holder.contract_address,
+        )
+        .await
+        .context(format!(
+            "Failed to lock the description of holder {}.",
+            &holder.holder_address.as_ref()
+        ))?;
+        let new = insert_holder_description(
             &mut transaction,
-            &holder_descriptions.network.as_ref(),
+            holder_descriptions.network.as_ref(),
             &holder,
         )
         .await
@@ -105,6 +117,20 @@ pub async fn add_holder_descriptions(
             "Failed to insert contract address {} in the database.",
             &holder.contract_address.as_ref()
         ))?;
+        record_holder_description_change(
+            &mut transaction,
+            holder_descriptions.network.as_ref(),
+            &holder.holder_address,
+            &holder.contract_address,
+            previous.as_ref(),
+            Some(&new),
+            &author,
+        )
+        .await
+        .context(format!(
+            "Failed to record the description change of holder {}.",
+            &holder.holder_address.as_ref()
+        ))?;
     }
 
     transaction
@@ -114,6 +140,15 @@ pub async fn add_holder_descriptions(
     Ok(HttpResponse::Ok().finish())
 }
 
+fn address_type_names(address_types: &[AddressType]) -> Vec<String> {
+    address_types
+        .iter()
+        .map(|at| at.as_ref().to_string())
+        .collect()
+}
+
+/// Describing a holder again merges the new address types into the ones it already has and
+/// replaces the notes when new ones are given.
 #[tracing::instrument(
     name = "Saving new holder totals details in the database",
     skip(transaction, network_name, holder_description)
@@ -122,13 +157,8 @@ pub async fn insert_holder_description(
     transaction: &mut Transaction<'_, Postgres>,
     network_name: &str,
     holder_description: &HolderDescription,
-) -> Result<(), StoreHolderDescriptionError> {
-    let address_types = holder_description
-        .address_types
-        .iter()
-        .map(|hd| hd.as_ref().to_string())
-        .collect::<Vec<String>>();
-    sqlx::query!(
+) -> Result<DescriptionState, StoreHolderDescriptionError> {
+    let row = sqlx::query!(
         r#"
         INSERT INTO holder_descriptions (network_id, holder_address, contract_address, notes, address_types)
         VALUES (
@@ -137,18 +167,31 @@ pub async fn insert_holder_description(
             $3,
             $4,
             $5
-        );
+        )
+        ON CONFLICT (network_id, holder_address, contract_address) DO UPDATE
+            SET notes = COALESCE(NULLIF(EXCLUDED.notes, ''), holder_descriptions.notes),
+                address_types = ARRAY(
+                    SELECT t
+                    FROM unnest(holder_descriptions.address_types || EXCLUDED.address_types)
+                        WITH ORDINALITY AS u(t, position)
+                    GROUP BY t
+                    ORDER BY MIN(position)
+                )
+        RETURNING notes, address_types;
         "#,
         network_name,
         holder_description.holder_address.as_ref(),
         holder_description.contract_address.as_ref(),
         holder_description.notes.as_ref(),
-        &address_types[..],
+        &address_type_names(&holder_description.address_types)[..],
     )
-        .execute(transaction)
-        .await
-        .map_err(|e| { StoreHolderDescriptionError(e) })?;
-    Ok(())
+    .fetch_one(transaction)
+    .await
+    .map_err(StoreHolderDescriptionError)?;
+    Ok(DescriptionState {
+        notes: row.notes,
+        address_types: row.address_types,
+    })
 }
 
 pub struct StoreHolderDescriptionError(sqlx::Error);
@@ -176,7 +219,39 @@ impl std::fmt::Display for StoreHolderDescriptionError {
 
 #[derive(serde::Deserialize)]
 pub struct Parameters {
-    holder_addresses: Vec<String>,
+    network_name: Option<String>,
+    holder_addresses: Option<Vec<String>>,
+    contract_address: Option<String>,
+    #[serde(default)]
+    address_types: Vec<String>,
+}
+
+impl TryFrom<Parameters> for HolderDescriptionQuery {
+    type Error = String;
+
+    fn try_from(value: Parameters) -> Result<Self, Self::Error> {
+        let network = value.network_name.map(Network::parse).transpose()?;
+        let parse_address = |address: String| match &network {
+            Some(network) => Address::parse(address, network).map_err(String::from),
+            None => Address::parse_for_any_network(address).map_err(String::from),
+        };
+        let holder_addresses = value
+            .holder_addresses
+            .map(|addresses| addresses.into_iter().map(parse_address).collect())
+            .transpose()?;
+        let contract_address = value.contract_address.map(parse_address).transpose()?;
+        let address_types = value
+            .address_types
+            .into_iter()
+            .map(AddressType::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            network,
+            holder_addresses,
+            contract_address,
+            address_types,
+        })
+    }
 }
 
 #[derive(serde::Deserialize, serde::Serialize)]
@@ -185,7 +260,7 @@ pub struct HolderRowData {
     contract_address: String,
     holder_address: String,
     notes: Option<String>,
-    address_types: Option<Vec<String>>,
+    address_types: Vec<String>,
 }
 
 #[derive(serde::Deserialize, serde::Serialize)]
@@ -193,44 +268,64 @@ pub struct HolderDescriptionsResponse {
     pub data: Vec<HolderRowData>,
 }
 
+#[allow(clippy::async_yields_async)]
+#[tracing::instrument(name = "Fetching holder descriptions.", skip(form, pool))]
 pub async fn get_holder_descriptions(
     form: web::Json<Parameters>,
     pool: web::Data<PgPool>,
-) -> HttpResponse {
-    let mut holders: HolderDescriptionsResponse = HolderDescriptionsResponse { data: vec![] };
-    for holder_address in &form.holder_addresses {
-        let holder_descriptions =
-            match get_holder_description_from_holder_address(&pool, holder_address.to_string())
-                .await
-            {
-                Ok(holder_descriptions) => holder_descriptions,
-                Err(_) => return HttpResponse::InternalServerError().finish(),
-            };
-        for hd in holder_descriptions {
-            holders.data.push(hd)
-        }
+) -> Result<HttpResponse, BlockchainAppError> {
+    let query: HolderDescriptionQuery = form
+        .into_inner()
+        .try_into()
+        .map_err(BlockchainAppError::ValidationError)?;
+    if query.is_unfiltered() {
+        return Err(BlockchainAppError::ValidationError(
+            "At least one of network_name, holder_addresses, contract_address or address_types is required.".to_string(),
+        ));
     }
-    HttpResponse::Ok().json(holders)
+    let data = find_holder_descriptions(&pool, &query)
+        .await
+        .context("Failed to fetch holder descriptions.")?;
+    Ok(HttpResponse::Ok().json(HolderDescriptionsResponse { data }))
 }
 
-#[tracing::instrument(name = "Get holder from holder_address", skip(holder_address, pool))]
-pub async fn get_holder_description_from_holder_address(
+#[tracing::instrument(name = "Finding holder descriptions", skip(pool, query))]
+pub async fn find_holder_descriptions(
     pool: &PgPool,
-    holder_address: String,
+    query: &HolderDescriptionQuery,
 ) -> Result<Vec<HolderRowData>, sqlx::Error> {
+    let holder_addresses = query.holder_addresses.as_ref().map(|addresses| {
+        addresses
+            .iter()
+            .map(|a| a.as_ref().to_string())
+            .collect::<Vec<_>>()
+    });
+    let address_types = Some(address_type_names(&query.address_types)).filter(|t| !t.is_empty());
     let results = sqlx::query!(
         r#"
-        SELECT h.*, n.network_name FROM holder_descriptions h
+        SELECT a.display_address AS holder_address,
+            COALESCE(c.display_address, h.contract_address) AS "contract_address!",
+            h.notes, h.address_types, n.network_name
+        FROM holder_descriptions h
         INNER JOIN addresses a
-            ON a.address = h.holder_address AND a.network_id = h.network_id AND h.holder_address = $1
+            ON a.address = h.holder_address AND a.network_id = h.network_id
+        LEFT JOIN addresses c
+            ON c.address = h.contract_address AND c.network_id = h.network_id
         INNER JOIN networks n
             ON n.network_id = h.network_id
-        ;
+        WHERE ($1::text IS NULL OR n.network_name = $1)
+            AND ($2::text[] IS NULL OR h.holder_address = ANY($2))
+            AND ($3::text IS NULL OR h.contract_address = $3)
+            AND ($4::text[] IS NULL OR h.address_types && $4)
+        ORDER BY n.network_name, h.contract_address, h.holder_address;
         "#,
-        holder_address,
+        query.network.as_ref().map(|n| n.as_ref()),
+        holder_addresses.as_deref(),
+        query.contract_address.as_ref().map(|c| c.as_ref()),
+        address_types.as_deref(),
     )
-        .fetch_all(pool)
-        .await?;
+    .fetch_all(pool)
+    .await?;
     let holder_descriptions = results
         .into_iter()
         .map(|r| HolderRowData {
@@ -243,3 +338,233 @@ pub async fn get_holder_description_from_holder_address(
         .collect();
     Ok(holder_descriptions)
 }
+
+#[derive(serde::Deserialize)]
+pub struct HolderDescriptionPath {
+    network: String,
+    holder_address: String,
+    contract_address: String,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct UpdateHolderDescriptionData {
+    address_types: Vec<String>,
+    notes: Option<String>,
+}
+
+fn parse_path(path: HolderDescriptionPath) -> Result<(Network, Address, Address), String> {
+    let network = Network::parse(path.network)?;
+    let holder_address = Address::parse(path.holder_address, &network)?;
+    let contract_address = Address::parse(path.contract_address, &network)?;
+    Ok((network, holder_address, contract_address))
+}
+
+#[allow(clippy::async_yields_async)]
+#[tracing::instrument(
+    name = "Replacing a holder description.",
+    skip(path, form, pool),
+    fields(
+        network = %path.network,
+        holder_address = %path.holder_address,
+        contract_address = %path.contract_address
+    )
+)]
+pub async fn update_holder_description(
+    path: web::Path<HolderDescriptionPath>,
+    form: web::Json<UpdateHolderDescriptionData>,
+    pool: web::Data<PgPool>,
+    request: HttpRequest,
+) -> Result<HttpResponse, BlockchainAppError> {
+    let (network, holder_address, contract_address) =
+        parse_path(path.into_inner()).map_err(BlockchainAppError::ValidationError)?;
+    let author = change_author(&request)?;
+    let form = form.into_inner();
+    let address_types = form
+        .address_types
+        .into_iter()
+        .map(AddressType::parse)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(BlockchainAppError::ValidationError)?;
+    let notes = Notes::parse(form.notes).map_err(BlockchainAppError::ValidationError)?;
+    let holder = HolderDescription {
+        holder_address,
+        contract_address,
+        address_types,
+        notes,
+    };
+
+    let mut transaction = pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+    insert_network(&mut transaction, &network)
+        .await
+        .context("Failed to insert network in the database.")?;
+    insert_address(&mut transaction, &network, &holder.holder_address)
+        .await
+        .context(format!(
+            "Failed to insert holder address {} in the database.",
+            &holder.holder_address.as_ref()
+        ))?;
+    insert_address(&mut transaction, &network, &holder.contract_address)
+        .await
+        .context(format!(
+            "Failed to insert contract address {} in the database.",
+            &holder.contract_address.as_ref()
+        ))?;
+    let previous = lock_holder_description(
+        &mut transaction,
+        network.as_ref(),
+        &holder.holder_address,
+        &holder.contract_address,
+    )
+    .await
+    .context(format!(
+        "Failed to lock the description of holder {}.",
+        &holder.holder_address.as_ref()
+    ))?;
+    let new = replace_holder_description(&mut transaction, &network, &holder)
+        .await
+        .context(format!(
+            "Failed to replace the description of holder {}.",
+            &holder.holder_address.as_ref()
+        ))?;
+    record_holder_description_change(
+        &mut transaction,
+        network.as_ref(),
+        &holder.holder_address,
+        &holder.contract_address,
+        previous.as_ref(),
+        Some(&new),
+        &author,
+    )
+    .await
+    .context(format!(
+        "Failed to record the description change of holder {}.",
+        &holder.holder_address.as_ref()
+    ))?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to replace a holder description.")?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[tracing::instrument(
+    name = "Replacing a holder description in the database",
+    skip(transaction, network, holder_description)
+)]
+async fn replace_holder_description(
+    transaction: &mut Transaction<'_, Postgres>,
+    network: &Network,
+    holder_description: &HolderDescription,
+) -> Result<DescriptionState, StoreHolderDescriptionError> {
+    let row = sqlx::query!(
+        r#"
+        INSERT INTO holder_descriptions (network_id, holder_address, contract_address, notes, address_types)
+        VALUES (
+            (SELECT network_id FROM networks WHERE network_name = $1),
+            $2,
+            $3,
+            $4,
+            $5
+        )
+        ON CONFLICT (network_id, holder_address, contract_address) DO UPDATE
+            SET notes = EXCLUDED.notes, address_types = EXCLUDED.address_types
+        RETURNING notes, address_types;
+        "#,
+        network.as_ref(),
+        holder_description.holder_address.as_ref(),
+        holder_description.contract_address.as_ref(),
+        holder_description.notes.as_ref(),
+        &address_type_names(&holder_description.address_types)[..],
+    )
+    .fetch_one(transaction)
+    .await
+    .map_err(StoreHolderDescriptionError)?;
+    Ok(DescriptionState {
+        notes: row.notes,
+        address_types: row.address_types,
+    })
+}
+
+#[allow(clippy::async_yields_async)]
+#[tracing::instrument(
+    name = "Deleting a holder description.",
+    skip(path, pool),
+    fields(
+        network = %path.network,
+        holder_address = %path.holder_address,
+        contract_address = %path.contract_address
+    )
+)]
+pub async fn delete_holder_description(
+    path: web::Path<HolderDescriptionPath>,
+    pool: web::Data<PgPool>,
+    request: HttpRequest,
+) -> Result<HttpResponse, BlockchainAppError> {
+    let (network, holder_address, contract_address) =
+        parse_path(path.into_inner()).map_err(BlockchainAppError::ValidationError)?;
+    let author = change_author(&request)?;
+
+    let mut transaction = pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+    let previous = lock_holder_description(
+        &mut transaction,
+        network.as_ref(),
+        &holder_address,
+        &contract_address,
+    )
+    .await
+    .context(format!(
+        "Failed to lock the description of holder {}.",
+        holder_address.as_ref()
+    ))?;
+    if previous.is_none() {
+        return Err(BlockchainAppError::NotFoundError(format!(
+            "No description of holder {} for contract address {}.",
+            holder_address.as_ref(),
+            contract_address.as_ref()
+        )));
+    }
+    sqlx::query!(
+        r#"
+        DELETE FROM holder_descriptions h
+        USING networks n
+        WHERE n.network_id = h.network_id
+            AND n.network_name = $1
+            AND h.holder_address = $2
+            AND h.contract_address = $3;
+        "#,
+        network.as_ref(),
+        holder_address.as_ref(),
+        contract_address.as_ref(),
+    )
+    .execute(&mut transaction)
+    .await
+    .context(format!(
+        "Failed to delete the description of holder {}.",
+        holder_address.as_ref()
+    ))?;
+    record_holder_description_change(
+        &mut transaction,
+        network.as_ref(),
+        &holder_address,
+        &contract_address,
+        previous.as_ref(),
+        None,
+        &author,
+    )
+    .await
+    .context(format!(
+        "Failed to record the deletion of the description of holder {}.",
+        holder_address.as_ref()
+    ))?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to delete a holder description.")?;
+    Ok(HttpResponse::Ok().finish())
+}
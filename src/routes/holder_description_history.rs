@@ -0,0 +1,261 @@
+use super::BlockchainAppError;
+use crate::api_keys::ApiCaller;
+use crate::domain::{Address, Network, MAX_LIMIT_CHARACTERS};
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Postgres, Transaction};
+
+/// Callers may name who they are acting for with this header. It is recorded next to the API key
+/// that made the change, never instead of it.
+const ON_BEHALF_OF_HEADER: &str = "X-Changed-By";
+
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Debug, Clone)]
+pub struct DescriptionState {
+    pub notes: Option<String>,
+    pub address_types: Vec<String>,
+}
+
+/// Who changed a description: the API key the request was let through with, and whoever the
+/// caller says it acted for.
+pub struct ChangeAuthor {
+    caller: ApiCaller,
+    on_behalf_of: Option<String>,
+}
+
+pub fn change_author(request: &HttpRequest) -> Result<ChangeAuthor, BlockchainAppError> {
+    let caller = request
+        .extensions()
+        .get::<ApiCaller>()
+        .cloned()
+        .context("The request was not authenticated with an API key.")?;
+    let on_behalf_of = on_behalf_of(request).map_err(BlockchainAppError::ValidationError)?;
+    Ok(ChangeAuthor {
+        caller,
+        on_behalf_of,
+    })
+}
+
+fn on_behalf_of(request: &HttpRequest) -> Result<Option<String>, String> {
+    let header = match request.headers().get(ON_BEHALF_OF_HEADER) {
+        Some(header) => header,
+        None => return Ok(None),
+    };
+    let on_behalf_of = header
+        .to_str()
+        .map_err(|_| format!("The {} header must be plain text.", ON_BEHALF_OF_HEADER))?
+        .trim();
+    if on_behalf_of.chars().count() > MAX_LIMIT_CHARACTERS {
+        return Err(format!("The {} header is too long.", ON_BEHALF_OF_HEADER));
+    }
+    Ok(Some(on_behalf_of.to_string()).filter(|o| !o.is_empty()))
+}
+
+/// Locks the description until the transaction ends so the recorded previous state can't go
+/// stale under a concurrent change. A row lock can't cover a description that doesn't exist yet,
+/// so an advisory lock on the holder and contract serialises first writes too.
+#[tracing::instrument(
+    name = "Locking a holder description",
+    skip(transaction, network_name, holder_address, contract_address)
+)]
+pub async fn lock_holder_description(
+    transaction: &mut Transaction<'_, Postgres>,
+    network_name: &str,
+    holder_address: &Address,
+    contract_address: &Address,
+) -> Result<Option<DescriptionState>, sqlx::Error> {
+    sqlx::query!(
+        "SELECT pg_advisory_xact_lock(hashtext($1 || '/' || $2 || '/' || $3));",
+        network_name,
+        holder_address.as_ref(),
+        contract_address.as_ref(),
+    )
+    .fetch_one(&mut *transaction)
+    .await?;
+    let row = sqlx::query!(
+        r#"
+        SELECT h.notes, h.address_types FROM holder_descriptions h
+        INNER JOIN networks n
+            ON n.network_id = h.network_id AND n.network_name = $1
+        WHERE h.holder_address = $2 AND h.contract_address = $3
+        FOR UPDATE OF h;
+        "#,
+        network_name,
+        holder_address.as_ref(),
+        contract_address.as_ref(),
+    )
+    .fetch_optional(transaction)
+    .await?;
+    Ok(row.map(|r| DescriptionState {
+        notes: r.notes,
+        address_types: r.address_types,
+    }))
+}
+
+/// Nothing is recorded when the description ends up the way it was.
+#[tracing::instrument(
+    name = "Recording a holder description change",
+    skip(
+        transaction,
+        network_name,
+        holder_address,
+        contract_address,
+        previous,
+        new,
+        author
+    )
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn record_holder_description_change(
+    transaction: &mut Transaction<'_, Postgres>,
+    network_name: &str,
+    holder_address: &Address,
+    contract_address: &Address,
+    previous: Option<&DescriptionState>,
+    new: Option<&DescriptionState>,
+    author: &ChangeAuthor,
+) -> Result<(), sqlx::Error> {
+    if previous == new {
+        return Ok(());
+    }
+    sqlx::query!(
+        r#"
+        INSERT INTO holder_description_history (
+            network_id, holder_address, contract_address, previous_notes, previous_address_types,
+            new_notes, new_address_types, changed_by, changed_by_api_key_id, on_behalf_of,
+            changed_on
+        )
+        VALUES (
+            (SELECT network_id FROM networks WHERE network_name = $1),
+            $2,
+            $3,
+            $4,
+            $5,
+            $6,
+            $7,
+            $8,
+            $9,
+            $10,
+            $11
+        );
+        "#,
+        network_name,
+        holder_address.as_ref(),
+        contract_address.as_ref(),
+        previous.and_then(|p| p.notes.as_deref()),
+        previous.map(|p| &p.address_types[..]),
+        new.and_then(|n| n.notes.as_deref()),
+        new.map(|n| &n.address_types[..]),
+        author.caller.name,
+        author.caller.api_key_id,
+        author.on_behalf_of,
+        Utc::now(),
+    )
+    .execute(transaction)
+    .await?;
+    Ok(())
+}
+
+#[derive(serde::Deserialize)]
+pub struct HistoryParameters {
+    network: String,
+    holder_address: String,
+    contract_address: Option<String>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct HolderDescriptionChange {
+    pub contract_address: String,
+    pub previous: Option<DescriptionState>,
+    pub new: Option<DescriptionState>,
+    /// The name of the API key that made the change. `None` for changes made before keys were
+    /// recorded.
+    pub changed_by: Option<String>,
+    pub changed_by_api_key_id: Option<String>,
+    pub on_behalf_of: Option<String>,
+    pub changed_on: DateTime<Utc>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct HolderDescriptionHistoryResponse {
+    pub network: String,
+    pub holder_address: String,
+    pub data: Vec<HolderDescriptionChange>,
+}
+
+fn description_state(
+    notes: Option<String>,
+    address_types: Option<Vec<String>>,
+) -> Option<DescriptionState> {
+    address_types.map(|address_types| DescriptionState {
+        notes,
+        address_types,
+    })
+}
+
+#[allow(clippy::async_yields_async)]
+#[tracing::instrument(
+    name = "Fetching the history of a holder description.",
+    skip(parameters, pool),
+    fields(
+        network = %parameters.network,
+        holder_address = %parameters.holder_address
+    )
+)]
+pub async fn get_holder_description_history(
+    parameters: web::Query<HistoryParameters>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, BlockchainAppError> {
+    let parameters = parameters.into_inner();
+    let network =
+        Network::parse(parameters.network).map_err(BlockchainAppError::ValidationError)?;
+    let holder_address = Address::parse(parameters.holder_address, &network)
+        .map_err(|e| BlockchainAppError::ValidationError(e.into()))?;
+    let contract_address = parameters
+        .contract_address
+        .map(|c| Address::parse(c, &network))
+        .transpose()
+        .map_err(|e| BlockchainAppError::ValidationError(e.into()))?;
+
+    let rows = sqlx::query!(
+        r#"
+        SELECT COALESCE(c.display_address, h.contract_address) AS "contract_address!",
+            h.previous_notes, h.previous_address_types, h.new_notes, h.new_address_types,
+            h.changed_by, h.changed_by_api_key_id, h.on_behalf_of, h.changed_on
+        FROM holder_description_history h
+        INNER JOIN networks n
+            ON n.network_id = h.network_id AND n.network_name = $1
+        LEFT JOIN addresses c
+            ON c.network_id = h.network_id AND c.address = h.contract_address
+        WHERE h.holder_address = $2 AND ($3::text IS NULL OR h.contract_address = $3)
+        ORDER BY h.changed_on ASC, h.history_id ASC;
+        "#,
+        network.as_ref(),
+        holder_address.as_ref(),
+        contract_address.as_ref().map(|c| c.as_ref()),
+    )
+    .fetch_all(pool.get_ref())
+    .await
+    .context(format!(
+        "Failed to fetch the description history of holder {}.",
+        holder_address.as_ref()
+    ))?;
+
+    let data = rows
+        .into_iter()
+        .map(|r| HolderDescriptionChange {
+            contract_address: r.contract_address,
+            previous: description_state(r.previous_notes, r.previous_address_types),
+            new: description_state(r.new_notes, r.new_address_types),
+            changed_by: r.changed_by,
+            changed_by_api_key_id: r.changed_by_api_key_id.map(|id| id.to_string()),
+            on_behalf_of: r.on_behalf_of,
+            changed_on: r.changed_on,
+        })
+        .collect();
+    Ok(HttpResponse::Ok().json(HolderDescriptionHistoryResponse {
+        network: network.as_ref().to_string(),
+        holder_address: holder_address.display().to_string(),
+        data,
+    }))
+}
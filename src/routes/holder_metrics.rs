@@ -0,0 +1,112 @@
+use super::BlockchainAppError;
+use crate::domain::{Address, AddressType, ConcentrationMetrics, Network};
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use sqlx::types::BigDecimal;
+use sqlx::PgPool;
+
+#[derive(serde::Deserialize)]
+pub struct MetricsParameters {
+    network: String,
+    contract_address: String,
+    exclude_non_circulating: Option<bool>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct SnapshotMetrics {
+    pub snapshot_id: i64,
+    pub checked_on: DateTime<Utc>,
+    pub metrics: ConcentrationMetrics,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct HolderMetricsResponse {
+    pub data: Vec<SnapshotMetrics>,
+}
+
+/// Holders tagged with these types don't take part in trading, so they can be left out of the
+/// concentration numbers.
+fn non_circulating_address_types() -> Vec<String> {
+    [
+        AddressType::DeadAddress,
+        AddressType::LiquidityLocker,
+        AddressType::Exchange,
+    ]
+    .iter()
+    .map(|at| at.as_ref().to_string())
+    .collect()
+}
+
+#[allow(clippy::async_yields_async)]
+#[tracing::instrument(
+    name = "Computing holder concentration metrics.",
+    skip(parameters, pool),
+    fields(
+        network = % parameters.network,
+        contract_address = % parameters.contract_address
+    )
+)]
+pub async fn get_holder_metrics(
+    parameters: web::Query<MetricsParameters>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, BlockchainAppError> {
+    let parameters = parameters.0;
+    let network =
+        Network::parse(parameters.network).map_err(BlockchainAppError::ValidationError)?;
+    let contract_address = Address::parse(parameters.contract_address, &network)
+        .map_err(|e| BlockchainAppError::ValidationError(e.into()))?;
+    let exclude_non_circulating = parameters.exclude_non_circulating.unwrap_or(false);
+
+    let rows = sqlx::query!(
+        r#"
+        SELECT h.snapshot_id, h.checked_on, h.amount,
+            EXISTS (
+                SELECT 1 FROM holder_descriptions d
+                WHERE d.network_id = h.network_id
+                    AND d.holder_address = h.holder_address
+                    AND d.address_types && $3::text[]
+            ) AS "non_circulating!"
+        FROM holder_totals h
+        INNER JOIN networks n
+            ON n.network_id = h.network_id AND n.network_name = $1
+        WHERE h.contract_address = $2
+        ORDER BY h.checked_on ASC, h.snapshot_id ASC, h.place ASC;
+        "#,
+        network.as_ref(),
+        contract_address.as_ref(),
+        &non_circulating_address_types()[..],
+    )
+    .fetch_all(pool.get_ref())
+    .await
+    .context(format!(
+        "Failed to fetch holders of contract address {} from the database.",
+        contract_address.as_ref()
+    ))?;
+
+    // Submissions can share a checked_on, so rows are grouped by their snapshot.
+    let mut snapshots: Vec<(i64, DateTime<Utc>, Vec<BigDecimal>)> = vec![];
+    for row in rows {
+        if exclude_non_circulating && row.non_circulating {
+            continue;
+        }
+        match snapshots.last_mut() {
+            Some((snapshot_id, _, amounts)) if *snapshot_id == row.snapshot_id => {
+                amounts.push(row.amount)
+            }
+            _ => snapshots.push((row.snapshot_id, row.checked_on, vec![row.amount])),
+        }
+    }
+
+    let data = snapshots
+        .into_iter()
+        .filter_map(|(snapshot_id, checked_on, amounts)| {
+            ConcentrationMetrics::from_amounts(amounts).map(|metrics| SnapshotMetrics {
+                snapshot_id,
+                checked_on,
+                metrics,
+            })
+        })
+        .collect();
+    Ok(HttpResponse::Ok().json(HolderMetricsResponse { data }))
+}
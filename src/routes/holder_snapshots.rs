@@ -0,0 +1,177 @@
+use super::BlockchainAppError;
+use crate::domain::{Address, Network};
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use sqlx::types::BigDecimal;
+use sqlx::PgPool;
+
+#[derive(serde::Deserialize)]
+pub struct SnapshotsParameters {
+    network: String,
+    contract_address: String,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct SnapshotSummary {
+    pub snapshot_id: i64,
+    pub source: Option<String>,
+    pub block_number: Option<i64>,
+    pub checked_on: DateTime<Utc>,
+    pub holder_count: i64,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct SnapshotsResponse {
+    pub network: String,
+    pub contract_address: String,
+    pub data: Vec<SnapshotSummary>,
+}
+
+#[allow(clippy::async_yields_async)]
+#[tracing::instrument(
+    name = "Listing holder snapshots.",
+    skip(parameters, pool),
+    fields(
+        network = % parameters.network,
+        contract_address = % parameters.contract_address
+    )
+)]
+pub async fn get_holder_snapshots(
+    parameters: web::Query<SnapshotsParameters>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, BlockchainAppError> {
+    let parameters = parameters.into_inner();
+    let network =
+        Network::parse(parameters.network).map_err(BlockchainAppError::ValidationError)?;
+    let contract_address = Address::parse(parameters.contract_address, &network)
+        .map_err(|e| BlockchainAppError::ValidationError(e.into()))?;
+
+    let rows = sqlx::query!(
+        r#"
+        SELECT s.snapshot_id, s.source, s.block_number, s.checked_on,
+            (SELECT COUNT(*) FROM holder_totals h WHERE h.snapshot_id = s.snapshot_id) AS "holder_count!"
+        FROM holder_snapshots s
+        INNER JOIN networks n
+            ON n.network_id = s.network_id AND n.network_name = $1
+        WHERE s.contract_address = $2
+        ORDER BY s.checked_on ASC, s.snapshot_id ASC;
+        "#,
+        network.as_ref(),
+        contract_address.as_ref(),
+    )
+    .fetch_all(pool.get_ref())
+    .await
+    .context(format!(
+        "Failed to fetch the snapshots of contract address {}.",
+        contract_address.as_ref()
+    ))?;
+
+    let data = rows
+        .into_iter()
+        .map(|r| SnapshotSummary {
+            snapshot_id: r.snapshot_id,
+            source: r.source,
+            block_number: r.block_number,
+            checked_on: r.checked_on,
+            holder_count: r.holder_count,
+        })
+        .collect();
+    Ok(HttpResponse::Ok().json(SnapshotsResponse {
+        network: network.as_ref().to_string(),
+        contract_address: contract_address.display().to_string(),
+        data,
+    }))
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct SnapshotHolder {
+    pub holder_address: String,
+    pub place: i32,
+    pub amount: BigDecimal,
+    pub raw_amount: Option<BigDecimal>,
+    pub percent_of_supply: Option<BigDecimal>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct SnapshotResponse {
+    pub snapshot_id: i64,
+    pub network: String,
+    pub contract_address: String,
+    pub source: Option<String>,
+    pub block_number: Option<i64>,
+    pub checked_on: DateTime<Utc>,
+    pub holders: Vec<SnapshotHolder>,
+}
+
+#[allow(clippy::async_yields_async)]
+#[tracing::instrument(
+    name = "Fetching a holder snapshot.",
+    skip(snapshot_id, pool),
+    fields(snapshot_id = % snapshot_id)
+)]
+pub async fn get_holder_snapshot(
+    snapshot_id: web::Path<i64>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, BlockchainAppError> {
+    let snapshot_id = snapshot_id.into_inner();
+    let snapshot = sqlx::query!(
+        r#"
+        SELECT s.snapshot_id, n.network_name, c.display_address AS contract_address,
+            s.source, s.block_number, s.checked_on
+        FROM holder_snapshots s
+        INNER JOIN networks n
+            ON n.network_id = s.network_id
+        INNER JOIN addresses c
+            ON c.network_id = s.network_id AND c.address = s.contract_address
+        WHERE s.snapshot_id = $1;
+        "#,
+        snapshot_id,
+    )
+    .fetch_optional(pool.get_ref())
+    .await
+    .context(format!("Failed to fetch snapshot {}.", snapshot_id))?
+    .ok_or_else(|| {
+        BlockchainAppError::NotFoundError(format!("There is no snapshot {}.", snapshot_id))
+    })?;
+
+    let holders = sqlx::query!(
+        r#"
+        SELECT a.display_address AS holder_address, h.place, h.amount, h.raw_amount,
+            h.raw_amount * 100 / NULLIF(t.total_supply, 0) AS percent_of_supply
+        FROM holder_totals h
+        INNER JOIN addresses a
+            ON a.network_id = h.network_id AND a.address = h.holder_address
+        INNER JOIN tokens t
+            ON t.network_id = h.network_id AND t.contract_address = h.contract_address
+        WHERE h.snapshot_id = $1
+        ORDER BY h.place ASC, h.holder_address ASC;
+        "#,
+        snapshot_id,
+    )
+    .fetch_all(pool.get_ref())
+    .await
+    .context(format!(
+        "Failed to fetch the holders of snapshot {}.",
+        snapshot_id
+    ))?
+    .into_iter()
+    .map(|r| SnapshotHolder {
+        holder_address: r.holder_address,
+        place: r.place,
+        amount: r.amount,
+        raw_amount: r.raw_amount,
+        percent_of_supply: r.percent_of_supply,
+    })
+    .collect();
+
+    Ok(HttpResponse::Ok().json(SnapshotResponse {
+        snapshot_id: snapshot.snapshot_id,
+        network: snapshot.network_name,
+        contract_address: snapshot.contract_address,
+        source: snapshot.source,
+        block_number: snapshot.block_number,
+        checked_on: snapshot.checked_on,
+        holders,
+    }))
+}
@@ -1,17 +1,21 @@
 use super::{
-    error_chain_fmt, insert_address, insert_network, insert_token_name, BlockchainAppError,
+    error_chain_fmt, insert_address, insert_network, update_total_supply, upsert_token,
+    BlockchainAppError,
+};
+use crate::domain::{
+    parse_block_number, parse_checked_on, parse_decimals, Address, AmountFormat, HolderInfo,
+    HolderPosition, HolderSnapshotDiff, HolderTotals, HoldersCursor, Network, ScaledAmount,
+    SnapshotSource, TokenAmount, TokenMetadata, TokenName, TokenSymbol,
 };
-use crate::domain::{Address, HolderInfo, HolderTotals, Network, TokenName};
-use actix_web::ResponseError;
 use actix_web::{web, HttpResponse};
 use anyhow::Context;
 use chrono::{DateTime, Utc};
+use futures::channel::{mpsc, oneshot};
+use futures::{SinkExt, TryStreamExt};
 use sqlx::types::BigDecimal;
 use sqlx::{PgPool, Postgres, Transaction};
 use std::convert::{TryFrom, TryInto};
-use std::str::FromStr;
-use tracing_futures::Instrument;
-use uuid::Uuid;
+use tracing::Instrument;
 
 #[derive(serde::Deserialize, serde::Serialize)]
 pub struct HolderData {
@@ -25,6 +29,13 @@ pub struct FormData {
     network: String,
     token_name: String,
     contract_address: String,
+    source: Option<String>,
+    checked_on: Option<DateTime<Utc>>,
+    block_number: Option<i64>,
+    amount_format: Option<String>,
+    symbol: Option<String>,
+    decimals: Option<i16>,
+    total_supply: Option<String>,
     holders: Vec<HolderData>,
 }
 
@@ -36,6 +47,8 @@ pub struct HolderRowData {
     holder_address: String,
     place: i32,
     amount: BigDecimal,
+    raw_amount: Option<BigDecimal>,
+    percent_of_supply: Option<BigDecimal>,
     checked_on: DateTime<Utc>,
 }
 
@@ -45,12 +58,41 @@ impl TryFrom<FormData> for HolderTotals {
     fn try_from(value: FormData) -> Result<Self, Self::Error> {
         let network = Network::parse(value.network)?;
         let token_name = TokenName::parse(value.token_name)?;
-        let contract_address = Address::parse(value.contract_address)?;
+        let contract_address = Address::parse(value.contract_address, &network)?;
+        let source = value.source.map(SnapshotSource::parse).transpose()?;
+        let checked_on = value
+            .checked_on
+            .map(|c| parse_checked_on(c, Utc::now()))
+            .transpose()?;
+        let block_number = value.block_number.map(parse_block_number).transpose()?;
+        let amount_format = value
+            .amount_format
+            .map(AmountFormat::parse)
+            .transpose()?
+            .unwrap_or_default();
+        let metadata = TokenMetadata {
+            symbol: value.symbol.map(TokenSymbol::parse).transpose()?,
+            decimals: value.decimals.map(parse_decimals).transpose()?,
+            creator_address: None,
+            launched_on: None,
+        };
+        let total_supply = value
+            .total_supply
+            .map(TokenAmount::parse)
+            .transpose()
+            .map_err(|e| format!("The total supply is invalid: {}", e))?;
         let mut holders = vec![];
         for holder in value.holders {
-            let holder_address = Address::parse(holder.holder_address)?;
+            let holder_address = Address::parse(holder.holder_address, &network)?;
             let place = holder.place;
-            let amount = BigDecimal::from_str(&holder.amount.replace(",", "")).unwrap(); //::from_str().unwrap();
+            let amount = TokenAmount::parse(holder.amount).map_err(|e| {
+                format!(
+                    "The amount of holder {} at place {} is invalid: {}",
+                    holder_address.as_ref(),
+                    place,
+                    e
+                )
+            })?;
             holders.push(HolderInfo {
                 holder_address,
                 place,
@@ -62,42 +104,93 @@ impl TryFrom<FormData> for HolderTotals {
             network,
             token_name,
             contract_address,
+            source,
+            checked_on,
+            block_number,
+            amount_format,
+            metadata,
+            total_supply,
             holders,
         })
     }
 }
 
+#[tracing::instrument(
+    name = "Saving a new holder snapshot in the database",
+    skip(transaction, holder_totals, checked_on)
+)]
+pub async fn insert_holder_snapshot(
+    transaction: &mut Transaction<'_, Postgres>,
+    holder_totals: &HolderTotals,
+    checked_on: DateTime<Utc>,
+) -> Result<i64, StoreHolderTotalError> {
+    let row = sqlx::query!(
+        r#"
+        INSERT INTO holder_snapshots (network_id, contract_address, source, block_number, checked_on)
+        VALUES (
+            (SELECT network_id FROM networks WHERE network_name = $1),
+            $2,
+            $3,
+            $4,
+            $5
+        )
+        RETURNING snapshot_id;
+        "#,
+        holder_totals.network.as_ref(),
+        holder_totals.contract_address.as_ref(),
+        holder_totals.source.as_ref().map(|s| s.as_ref()),
+        holder_totals.block_number,
+        checked_on,
+    )
+    .fetch_one(transaction)
+    .await
+    .map_err(StoreHolderTotalError)?;
+    Ok(row.snapshot_id)
+}
+
 #[tracing::instrument(
     name = "Saving new holder totals details in the database",
-    skip(transaction, network_name, token_name, contract_address, holder_info)
+    skip(
+        transaction,
+        network_name,
+        contract_address,
+        holder_info,
+        amount,
+        snapshot_id,
+        checked_on
+    )
 )]
 pub async fn insert_holder_totals(
     transaction: &mut Transaction<'_, Postgres>,
     network_name: &str,
-    token_name: &str,
     contract_address: &str,
     holder_info: &HolderInfo,
+    amount: &ScaledAmount,
+    snapshot_id: i64,
+    checked_on: DateTime<Utc>,
 ) -> Result<(), StoreHolderTotalError> {
     sqlx::query!(
         r#"
-        INSERT INTO holder_totals (network_id, holder_address, token_name_id, place, amount, checked_on, contract_address)
+        INSERT INTO holder_totals (network_id, holder_address, place, amount, raw_amount, checked_on, contract_address, snapshot_id)
         VALUES (
             (SELECT network_id FROM networks WHERE network_name = $1),
             $2,
-            (SELECT token_name_id FROM token_names WHERE token_name = $3),
+            $3,
             $4,
             $5,
             $6,
-            $7
+            $7,
+            $8
         );
         "#,
         network_name,
         holder_info.holder_address.as_ref(),
-        token_name,
         holder_info.place,
-        holder_info.amount,
-        Utc::now(),
+        amount.amount.as_ref(),
+        amount.raw_amount.as_ref(),
+        checked_on,
         contract_address,
+        snapshot_id,
     )
         .execute(transaction)
         .await
@@ -105,6 +198,11 @@ pub async fn insert_holder_totals(
     Ok(())
 }
 
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct AddHoldersResponse {
+    pub snapshot_id: i64,
+}
+
 #[allow(clippy::async_yields_async)]
 #[tracing::instrument(
     name = "Adding a new holder.",
@@ -124,6 +222,15 @@ pub async fn add_holders(
         .try_into()
         .map_err(BlockchainAppError::ValidationError)?;
 
+    let snapshot_id = store_holder_totals(&pool, &holder_total).await?;
+    Ok(HttpResponse::Ok().json(AddHoldersResponse { snapshot_id }))
+}
+/// Stores a submission as one snapshot and returns its id. Amounts that do not fit the token's
+/// decimals are rejected.
+pub async fn store_holder_totals(
+    pool: &PgPool,
+    holder_total: &HolderTotals,
+) -> Result<i64, BlockchainAppError> {
     let mut transaction = pool
         .begin()
         .await
@@ -133,12 +240,6 @@ pub async fn add_holders(
         .await
         .context("Failed to insert network in the database.")?;
 
-    insert_token_name(&mut transaction, &holder_total.token_name)
-        .await
-        .context(format!(
-            "Failed to insert token name {}",
-            &holder_total.token_name.as_ref()
-        ))?;
     insert_address(
         &mut transaction,
         &holder_total.network,
@@ -149,7 +250,66 @@ pub async fn add_holders(
         "Failed to insert contract address {} in the database.",
         &holder_total.contract_address.as_ref()
     ))?;
-    for holder in holder_total.holders {
+    let decimals = upsert_token(
+        &mut transaction,
+        &holder_total.network,
+        &holder_total.contract_address,
+        &holder_total.token_name,
+        &holder_total.metadata,
+    )
+    .await?;
+    if let Some(total_supply) = &holder_total.total_supply {
+        let total_supply = holder_total
+            .amount_format
+            .read(total_supply, decimals)
+            .map_err(|e| {
+                BlockchainAppError::ValidationError(format!("The total supply is invalid: {}", e))
+            })?;
+        let raw_total_supply = total_supply.raw_amount.ok_or_else(|| {
+            BlockchainAppError::ValidationError(
+                "A total supply can only be stored once the token's decimals are known. Send decimals."
+                    .to_string(),
+            )
+        })?;
+        update_total_supply(
+            &mut transaction,
+            &holder_total.network,
+            &holder_total.contract_address,
+            &raw_total_supply,
+        )
+        .await
+        .context(format!(
+            "Failed to store the total supply of contract address {} in the database.",
+            &holder_total.contract_address.as_ref()
+        ))?;
+    }
+    let amounts = holder_total
+        .holders
+        .iter()
+        .map(|h| {
+            holder_total
+                .amount_format
+                .read(&h.amount, decimals)
+                .map_err(|e| {
+                    format!(
+                        "The amount of holder {} at place {} is invalid: {}",
+                        h.holder_address.as_ref(),
+                        h.place,
+                        e
+                    )
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(BlockchainAppError::ValidationError)?;
+    // Every row of a submission shares one timestamp so the submission reads back as a snapshot.
+    let checked_on = holder_total.checked_on.unwrap_or_else(Utc::now);
+    let snapshot_id = insert_holder_snapshot(&mut transaction, holder_total, checked_on)
+        .await
+        .context(format!(
+            "Failed to insert a snapshot of contract address {} in the database.",
+            &holder_total.contract_address.as_ref()
+        ))?;
+    for (holder, amount) in holder_total.holders.iter().zip(&amounts) {
         insert_address(
             &mut transaction,
             &holder_total.network,
@@ -164,9 +324,11 @@ pub async fn add_holders(
         insert_holder_totals(
             &mut transaction,
             holder_total.network.as_ref(),
-            holder_total.token_name.as_ref(),
             holder_total.contract_address.as_ref(),
-            &holder,
+            holder,
+            amount,
+            snapshot_id,
+            checked_on,
         )
         .await
         .context(format!(
@@ -180,8 +342,9 @@ pub async fn add_holders(
         .await
         .context("Failed to commit SQL transaction to store holder total.")?;
 
-    Ok(HttpResponse::Ok().finish())
+    Ok(snapshot_id)
 }
+
 pub struct StoreHolderTotalError(sqlx::Error);
 
 impl std::error::Error for StoreHolderTotalError {
@@ -205,17 +368,81 @@ impl std::fmt::Display for StoreHolderTotalError {
     }
 }
 
+/// Pages hold this many rows unless `limit` asks for fewer.
+const DEFAULT_HOLDERS_LIMIT: i64 = 1000;
+const MAX_HOLDERS_LIMIT: i64 = 10000;
+
 #[derive(serde::Deserialize)]
 pub struct Parameters {
     network: String,
     contract_address: String,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    limit: Option<i64>,
+    cursor: Option<String>,
+    #[serde(default)]
+    latest_only: bool,
 }
 
 #[derive(serde::Deserialize, serde::Serialize)]
 pub struct HoldersResponse {
     pub data: Vec<HolderRowData>,
+    pub next_cursor: Option<String>,
+    /// Set when the rows stopped coming partway through the page. `data` holds the rows sent
+    /// before that and `next_cursor` resumes after them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
 
+struct HoldersPage {
+    network: Network,
+    contract_address: Address,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    limit: i64,
+    cursor: Option<HoldersCursor>,
+    latest_only: bool,
+}
+
+impl TryFrom<Parameters> for HoldersPage {
+    type Error = String;
+
+    fn try_from(value: Parameters) -> Result<Self, Self::Error> {
+        let network = Network::parse(value.network)?;
+        let contract_address = Address::parse(value.contract_address, &network)?;
+        if let (Some(since), Some(until)) = (value.since, value.until) {
+            if since > until {
+                return Err("since must not be after until.".to_string());
+            }
+        }
+        let limit = value.limit.unwrap_or(DEFAULT_HOLDERS_LIMIT);
+        if !(1..=MAX_HOLDERS_LIMIT).contains(&limit) {
+            return Err(format!(
+                "limit must be between 1 and {}.",
+                MAX_HOLDERS_LIMIT
+            ));
+        }
+        let cursor = value
+            .cursor
+            .map(|c| HoldersCursor::decode(&c))
+            .transpose()?;
+        Ok(Self {
+            network,
+            contract_address,
+            since: value.since,
+            until: value.until,
+            limit,
+            cursor,
+            latest_only: value.latest_only,
+        })
+    }
+}
+
+type HoldersChunk = Result<web::Bytes, BlockchainAppError>;
+
+/// Rows are streamed to the client as Postgres returns them, so the response body is written by
+/// a separate task. The handler waits for the first row, so a query that fails outright still
+/// gets a 500 instead of a 200.
 #[allow(clippy::async_yields_async)]
 #[tracing::instrument(
     name = "Fetching holders.",
@@ -228,45 +455,317 @@ pub struct HoldersResponse {
 pub async fn get_holder(
     parameters: web::Query<Parameters>,
     pool: web::Data<PgPool>,
-) -> HttpResponse {
-    match sqlx::query!(
+) -> Result<HttpResponse, BlockchainAppError> {
+    let page: HoldersPage = parameters
+        .into_inner()
+        .try_into()
+        .map_err(BlockchainAppError::ValidationError)?;
+    let (mut sender, receiver) = mpsc::channel::<HoldersChunk>(16);
+    let (started, first_row) = oneshot::channel();
+    let pool = pool.into_inner();
+    actix_web::rt::spawn(
+        async move {
+            if let Err(e) = stream_holders(&pool, &page, started, &mut sender).await {
+                tracing::error!(error.cause_chain = ?e, "Failed to stream holders.");
+                let _ = sender
+                    .send(Err(BlockchainAppError::UnexpectedError(e)))
+                    .await;
+            }
+        }
+        .instrument(tracing::Span::current()),
+    );
+    first_row
+        .await
+        .context("Stopped streaming holders before the first row.")??;
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .streaming(receiver))
+}
+
+/// Writes a `HoldersResponse` one row at a time. One row more than the limit is fetched to tell
+/// whether there is a next page.
+///
+/// `started` gets the outcome of fetching the first row, before anything is written. Once the
+/// body has begun a failure can no longer change the status, so the body is closed with the
+/// rows sent so far, a `next_cursor` after them and an `error`.
+async fn stream_holders(
+    pool: &PgPool,
+    page: &HoldersPage,
+    started: oneshot::Sender<Result<(), anyhow::Error>>,
+    sender: &mut mpsc::Sender<HoldersChunk>,
+) -> Result<(), anyhow::Error> {
+    let mut rows = sqlx::query!(
         r#"
-        SELECT h.*, t.token_name, n.network_name FROM holder_totals h
-        INNER JOIN token_names t
-            ON h.token_name_id = t.token_name_id
+        SELECT h.place, h.amount, h.raw_amount, h.checked_on, h.snapshot_id,
+            t.token_name AS "token_name!", n.network_name,
+            h.raw_amount * 100 / NULLIF(t.total_supply, 0) AS percent_of_supply,
+            h.holder_address AS holder_key,
+            a.display_address AS holder_address, c.display_address AS contract_address
+        FROM holder_totals h
+        INNER JOIN tokens t
+            ON t.network_id = h.network_id AND t.contract_address = h.contract_address
         INNER JOIN addresses a
-            ON a.address = h.holder_address AND a.network_id = h.network_id AND h.contract_address = $2
+            ON a.address = h.holder_address AND a.network_id = h.network_id
+        INNER JOIN addresses c
+            ON c.address = h.contract_address AND c.network_id = h.network_id
         INNER JOIN networks n
             ON n.network_id = h.network_id AND n.network_name = $1
-        ORDER BY h.checked_on ASC;
-        ;
+        WHERE h.contract_address = $2
+            AND ($3::timestamptz IS NULL OR h.checked_on >= $3)
+            AND ($4::timestamptz IS NULL OR h.checked_on <= $4)
+            AND (NOT $5 OR h.snapshot_id = (
+                SELECT l.snapshot_id FROM holder_snapshots l
+                WHERE l.network_id = h.network_id
+                    AND l.contract_address = h.contract_address
+                    AND ($3::timestamptz IS NULL OR l.checked_on >= $3)
+                    AND ($4::timestamptz IS NULL OR l.checked_on <= $4)
+                    AND EXISTS (SELECT 1 FROM holder_totals e WHERE e.snapshot_id = l.snapshot_id)
+                ORDER BY l.checked_on DESC, l.snapshot_id DESC
+                LIMIT 1
+            ))
+            AND ($6::timestamptz IS NULL
+                OR (h.checked_on, h.snapshot_id, h.place, h.holder_address)
+                    > ($6, $7::bigint, $8::integer, $9::text))
+        ORDER BY h.checked_on ASC, h.snapshot_id ASC, h.place ASC, h.holder_address ASC
+        LIMIT $10;
         "#,
-        parameters.network,
-        parameters.contract_address,
+        page.network.as_ref(),
+        page.contract_address.as_ref(),
+        page.since,
+        page.until,
+        page.latest_only,
+        page.cursor.as_ref().map(|c| c.checked_on),
+        page.cursor.as_ref().map(|c| c.snapshot_id),
+        page.cursor.as_ref().map(|c| c.place),
+        page.cursor.as_ref().map(|c| c.holder_address.as_str()),
+        page.limit + 1,
     )
-        .fetch_all(pool.get_ref())
-        .await {
-        Ok(rows) => {
-            let mut holders: HoldersResponse = HoldersResponse {
-                data: vec![]
-            };
-            for row in rows {
-
-                let holder = HolderRowData {
-                    network: row.network_name,
-                    token_name: row.token_name,
-                    contract_address: row.contract_address,
-                    holder_address: row.holder_address,
-                    place: row.place,
-                    amount: row.amount,
-                    checked_on: row.checked_on
-                };
-                holders.data.push(holder);
-            };
-            HttpResponse::Ok().json(holders)
+    .fetch(pool);
+
+    let mut next_row = match rows.try_next().await.context("Failed to fetch holders.") {
+        Ok(row) => {
+            let _ = started.send(Ok(()));
+            row
         }
         Err(e) => {
-            HttpResponse::InternalServerError().finish()
+            let _ = started.send(Err(e));
+            return Ok(());
+        }
+    };
+    send(sender, "{\"data\":[".to_string()).await?;
+    let mut sent = 0;
+    let mut last = None;
+    let mut next_cursor = None;
+    let mut error = None;
+    while let Some(row) = next_row {
+        if sent == page.limit {
+            next_cursor = last.as_ref().map(HoldersCursor::encode);
+            break;
         }
+        let holder = HolderRowData {
+            network: row.network_name,
+            token_name: row.token_name,
+            contract_address: row.contract_address,
+            holder_address: row.holder_address,
+            place: row.place,
+            amount: row.amount,
+            raw_amount: row.raw_amount,
+            percent_of_supply: row.percent_of_supply,
+            checked_on: row.checked_on,
+        };
+        let separator = if sent == 0 { "" } else { "," };
+        send(
+            sender,
+            format!("{}{}", separator, serde_json::to_string(&holder)?),
+        )
+        .await?;
+        last = Some(HoldersCursor {
+            checked_on: row.checked_on,
+            snapshot_id: row.snapshot_id,
+            place: row.place,
+            holder_address: row.holder_key,
+        });
+        sent += 1;
+        next_row = match rows.try_next().await.context("Failed to fetch holders.") {
+            Ok(row) => row,
+            Err(e) => {
+                tracing::error!(error.cause_chain = ?e, "Stopped streaming holders partway.");
+                next_cursor = last.as_ref().map(HoldersCursor::encode);
+                error = Some("Failed to fetch the rest of the page. Retry from next_cursor.");
+                break;
+            }
+        };
     }
+    let error = match error {
+        Some(error) => format!(",\"error\":{}", serde_json::to_string(error)?),
+        None => String::new(),
+    };
+    send(
+        sender,
+        format!(
+            "],\"next_cursor\":{}{}}}",
+            serde_json::to_string(&next_cursor)?,
+            error
+        ),
+    )
+    .await
+}
+
+async fn send(sender: &mut mpsc::Sender<HoldersChunk>, chunk: String) -> Result<(), anyhow::Error> {
+    sender
+        .send(Ok(web::Bytes::from(chunk)))
+        .await
+        .context("The client stopped reading holders.")
+}
+
+#[derive(serde::Deserialize)]
+pub struct DiffParameters {
+    network: String,
+    contract_address: String,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    top: Option<i32>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct HoldersDiffResponse {
+    pub network: String,
+    pub contract_address: String,
+    pub from_snapshot_id: i64,
+    pub from_checked_on: DateTime<Utc>,
+    pub to_snapshot_id: i64,
+    pub to_checked_on: DateTime<Utc>,
+    pub data: HolderSnapshotDiff,
+}
+
+#[allow(clippy::async_yields_async)]
+#[tracing::instrument(
+    name = "Diffing holder snapshots.",
+    skip(parameters, pool),
+    fields(
+        network = % parameters.network,
+        contract_address = % parameters.contract_address,
+        from = % parameters.from,
+        to = % parameters.to
+    )
+)]
+pub async fn get_holders_diff(
+    parameters: web::Query<DiffParameters>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, BlockchainAppError> {
+    let parameters = parameters.0;
+    let network =
+        Network::parse(parameters.network).map_err(BlockchainAppError::ValidationError)?;
+    let contract_address = Address::parse(parameters.contract_address, &network)
+        .map_err(|e| BlockchainAppError::ValidationError(e.into()))?;
+    if parameters.from > parameters.to {
+        return Err(BlockchainAppError::ValidationError(
+            "from must not be later than to.".to_string(),
+        ));
+    }
+    if matches!(parameters.top, Some(top) if top < 1) {
+        return Err(BlockchainAppError::ValidationError(
+            "top must be a positive number.".to_string(),
+        ));
+    }
+
+    let from = get_closest_snapshot(&pool, &network, &contract_address, parameters.from, false)
+        .await
+        .context("Failed to find the holder snapshot closest to from.")?;
+    let to = get_closest_snapshot(&pool, &network, &contract_address, parameters.to, true)
+        .await
+        .context("Failed to find the holder snapshot closest to to.")?;
+    let (from, to) = match (from, to) {
+        (Some(from), Some(to)) => (from, to),
+        _ => {
+            return Err(BlockchainAppError::NotFoundError(format!(
+                "No holder snapshots found for contract address {}.",
+                contract_address.as_ref()
+            )))
+        }
+    };
+
+    let previous = get_snapshot_positions(&pool, from.snapshot_id)
+        .await
+        .context("Failed to fetch the earlier holder snapshot.")?;
+    let current = get_snapshot_positions(&pool, to.snapshot_id)
+        .await
+        .context("Failed to fetch the later holder snapshot.")?;
+
+    Ok(HttpResponse::Ok().json(HoldersDiffResponse {
+        network: network.as_ref().to_string(),
+        contract_address: contract_address.as_ref().to_string(),
+        from_snapshot_id: from.snapshot_id,
+        from_checked_on: from.checked_on,
+        to_snapshot_id: to.snapshot_id,
+        to_checked_on: to.checked_on,
+        data: HolderSnapshotDiff::between(previous, current, parameters.top),
+    }))
+}
+
+struct SnapshotRef {
+    snapshot_id: i64,
+    checked_on: DateTime<Utc>,
+}
+
+/// Submissions can share a `checked_on`, so ties go to the earliest snapshot, or to the latest
+/// one with `prefer_later`.
+#[tracing::instrument(
+    name = "Get the holder snapshot closest to a timestamp",
+    skip(pool, network, contract_address)
+)]
+async fn get_closest_snapshot(
+    pool: &PgPool,
+    network: &Network,
+    contract_address: &Address,
+    checked_on: DateTime<Utc>,
+    prefer_later: bool,
+) -> Result<Option<SnapshotRef>, sqlx::Error> {
+    sqlx::query_as!(
+        SnapshotRef,
+        r#"
+        SELECT s.snapshot_id, s.checked_on FROM holder_snapshots s
+        INNER JOIN networks n
+            ON n.network_id = s.network_id AND n.network_name = $1
+        WHERE s.contract_address = $2
+            AND EXISTS (SELECT 1 FROM holder_totals h WHERE h.snapshot_id = s.snapshot_id)
+        ORDER BY ABS(EXTRACT(EPOCH FROM (s.checked_on - $3))) ASC,
+            CASE WHEN $4 THEN s.checked_on END DESC, s.checked_on ASC,
+            CASE WHEN $4 THEN s.snapshot_id END DESC, s.snapshot_id ASC
+        LIMIT 1;
+        "#,
+        network.as_ref(),
+        contract_address.as_ref(),
+        checked_on,
+        prefer_later,
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+#[tracing::instrument(name = "Get the holders of a snapshot", skip(pool))]
+async fn get_snapshot_positions(
+    pool: &PgPool,
+    snapshot_id: i64,
+) -> Result<Vec<HolderPosition>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT a.display_address, h.place, h.amount FROM holder_totals h
+        INNER JOIN addresses a
+            ON a.network_id = h.network_id AND a.address = h.holder_address
+        WHERE h.snapshot_id = $1
+        ORDER BY h.place ASC;
+        "#,
+        snapshot_id,
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|r| HolderPosition {
+            holder_address: r.display_address,
+            place: r.place,
+            amount: r.amount,
+        })
+        .collect())
 }
@@ -0,0 +1,164 @@
+use super::{store_holder_totals, BlockchainAppError};
+use crate::domain::{
+    Address, AmountFormat, HolderRowError, HolderTotals, HoldersCsv, Network, SnapshotSource,
+    TokenMetadata, TokenName,
+};
+use actix_multipart::{Multipart, MultipartError};
+use actix_web::error::PayloadError;
+use actix_web::http::header::CONTENT_TYPE;
+use actix_web::{web, HttpRequest, HttpResponse};
+use futures::TryStreamExt;
+use sqlx::PgPool;
+use std::collections::HashMap;
+
+/// Uploads may be this large. The default payload limit is too small for long holder lists.
+pub const MAX_IMPORT_BYTES: usize = 16 * 1024 * 1024;
+
+#[derive(serde::Deserialize)]
+pub struct ImportParameters {
+    network: Option<String>,
+    token_name: Option<String>,
+    contract_address: Option<String>,
+    source: Option<String>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct ImportResponse {
+    pub snapshot_id: Option<i64>,
+    pub imported: usize,
+    pub errors: Vec<HolderRowError>,
+}
+
+/// The upload's metadata and CSV text, taken from the query string for `text/csv` bodies or from
+/// the form fields and `file` part of a multipart upload.
+struct Upload {
+    parameters: ImportParameters,
+    csv: String,
+}
+
+fn required(value: Option<String>, name: &str) -> Result<String, String> {
+    value.ok_or_else(|| format!("{} is required.", name))
+}
+
+#[allow(clippy::async_yields_async)]
+#[tracing::instrument(name = "Importing holders from a CSV.", skip(request, body, pool))]
+pub async fn import_holders(
+    request: HttpRequest,
+    body: web::Bytes,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, BlockchainAppError> {
+    let upload = read_upload(&request, body)
+        .await
+        .map_err(BlockchainAppError::ValidationError)?;
+    let parameters = upload.parameters;
+    let network = required(parameters.network, "network")
+        .and_then(Network::parse)
+        .map_err(BlockchainAppError::ValidationError)?;
+    let token_name = required(parameters.token_name, "token_name")
+        .and_then(TokenName::parse)
+        .map_err(BlockchainAppError::ValidationError)?;
+    let contract_address = required(parameters.contract_address, "contract_address")
+        .and_then(|c| Address::parse(c, &network).map_err(String::from))
+        .map_err(BlockchainAppError::ValidationError)?;
+    let source = parameters
+        .source
+        .map(SnapshotSource::parse)
+        .transpose()
+        .map_err(BlockchainAppError::ValidationError)?;
+    let csv =
+        HoldersCsv::parse(&upload.csv, &network).map_err(BlockchainAppError::ValidationError)?;
+
+    if csv.holders.is_empty() {
+        return Ok(HttpResponse::BadRequest().json(ImportResponse {
+            snapshot_id: None,
+            imported: 0,
+            errors: csv.errors,
+        }));
+    }
+    let imported = csv.holders.len();
+    let holder_totals = HolderTotals {
+        network,
+        token_name,
+        contract_address,
+        source,
+        checked_on: None,
+        block_number: None,
+        amount_format: AmountFormat::Scaled,
+        metadata: TokenMetadata::default(),
+        total_supply: None,
+        holders: csv.holders,
+    };
+    let snapshot_id = store_holder_totals(&pool, &holder_totals).await?;
+    Ok(HttpResponse::Ok().json(ImportResponse {
+        snapshot_id: Some(snapshot_id),
+        imported,
+        errors: csv.errors,
+    }))
+}
+
+async fn read_upload(request: &HttpRequest, body: web::Bytes) -> Result<Upload, String> {
+    let content_type = request
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|c| c.to_str().ok())
+        .unwrap_or_default();
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase();
+    match mime.as_str() {
+        "text/csv" | "text/plain" => {
+            let parameters = web::Query::<ImportParameters>::from_query(request.query_string())
+                .map_err(|e| e.to_string())?
+                .into_inner();
+            Ok(Upload {
+                parameters,
+                csv: utf8(&body)?,
+            })
+        }
+        "multipart/form-data" => {
+            let mut fields = multipart_fields(request, body).await?;
+            let csv = fields
+                .remove("file")
+                .ok_or("The multipart upload has no file part.")?;
+            Ok(Upload {
+                parameters: ImportParameters {
+                    network: fields.remove("network"),
+                    token_name: fields.remove("token_name"),
+                    contract_address: fields.remove("contract_address"),
+                    source: fields.remove("source"),
+                },
+                csv,
+            })
+        }
+        _ => Err("Upload the CSV as text/csv or multipart/form-data.".to_string()),
+    }
+}
+
+fn utf8(bytes: &[u8]) -> Result<String, String> {
+    String::from_utf8(bytes.to_vec()).map_err(|_| "The upload is not UTF-8 text.".to_string())
+}
+
+/// Reads the named parts of a `multipart/form-data` body. Only text parts are expected.
+async fn multipart_fields(
+    request: &HttpRequest,
+    body: web::Bytes,
+) -> Result<HashMap<String, String>, String> {
+    let malformed = |e: MultipartError| format!("The multipart upload is malformed: {}", e);
+    let mut multipart = Multipart::new(
+        request.headers(),
+        futures::stream::once(async move { Ok::<_, PayloadError>(body) }),
+    );
+    let mut fields = HashMap::new();
+    while let Some(mut field) = multipart.try_next().await.map_err(malformed)? {
+        let name = field.name().to_string();
+        let mut content = web::BytesMut::new();
+        while let Some(chunk) = field.try_next().await.map_err(malformed)? {
+            content.extend_from_slice(&chunk);
+        }
+        fields.insert(name, utf8(&content)?);
+    }
+    Ok(fields)
+}
@@ -1,16 +1,14 @@
-use super::{error_chain_fmt, insert_address, insert_network, BlockchainAppError};
-use crate::domain::{Address, LegitTokenCreator, Network, Notes, ScamType, TokenCreatorQuery};
-use actix_web::ResponseError;
+use super::{
+    error_chain_fmt, insert_address, insert_network, link_token_creator, BlockchainAppError,
+};
+use crate::domain::{Address, LegitTokenCreator, Network, Notes, TokenCreatorQuery};
 use actix_web::{web, HttpResponse};
 use anyhow::Context;
-use chrono::{DateTime, Utc};
-use sqlx::types::BigDecimal;
 use sqlx::{PgPool, Postgres, Transaction};
+use std::collections::BTreeMap;
 use std::convert::{TryFrom, TryInto};
-use tracing_futures::Instrument;
-use uuid::Uuid;
 
-#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
 pub struct FormDataLegitTokenCreator {
     address: String,
     notes: Option<String>,
@@ -22,10 +20,11 @@ impl TryFrom<FormDataLegitTokenCreator> for LegitTokenCreator {
     type Error = String;
 
     fn try_from(value: FormDataLegitTokenCreator) -> Result<Self, Self::Error> {
-        let address = Address::parse(value.address)?;
-        let notes = Notes::parse(value.notes)?;
         let network_of_legit_token = Network::parse(value.network_of_legit_token)?;
-        let legit_contract_address = Address::parse(value.legit_contract_address)?;
+        let address = Address::parse(value.address, &network_of_legit_token)?;
+        let notes = Notes::parse(value.notes)?;
+        let legit_contract_address =
+            Address::parse(value.legit_contract_address, &network_of_legit_token)?;
         Ok(Self {
             address,
             notes,
@@ -65,6 +64,36 @@ pub async fn insert_legit_token_creator(
     Ok(())
 }
 
+#[tracing::instrument(
+    name = "Inserting a legit token.",
+    skip(transaction, legit_token_creator)
+)]
+pub async fn insert_legit_token(
+    transaction: &mut Transaction<'_, Postgres>,
+    legit_token_creator: &LegitTokenCreator,
+) -> Result<(), StoreLegitTokenCreatorError> {
+    sqlx::query!(
+        r#"
+        INSERT INTO legit_tokens (address, notes, creator_network, creator_address)
+        VALUES (
+            $1,
+            $2,
+            (SELECT network_id FROM networks WHERE network_name = $3),
+            $4
+        )
+        ON CONFLICT (creator_network, address, creator_address) DO NOTHING;
+        "#,
+        legit_token_creator.legit_contract_address.as_ref(),
+        legit_token_creator.notes.as_ref(),
+        legit_token_creator.network_of_legit_token.as_ref(),
+        legit_token_creator.address.as_ref(),
+    )
+    .execute(transaction)
+    .await
+    .map_err(StoreLegitTokenCreatorError)?;
+    Ok(())
+}
+
 #[allow(clippy::async_yields_async)]
 #[tracing::instrument(
 name = "Adding a new legit token creator.",
@@ -124,6 +153,19 @@ pub async fn register_legit_token_creator(
             &legit_token_creator.address.as_ref(),
             &legit_token_creator.legit_contract_address.as_ref()
         ))?;
+    insert_legit_token(&mut transaction, &legit_token_creator)
+        .await
+        .context(format!(
+            "Failed to insert legit token {} in the database.",
+            &legit_token_creator.legit_contract_address.as_ref()
+        ))?;
+    link_token_creator(
+        &mut transaction,
+        &legit_token_creator.network_of_legit_token,
+        &legit_token_creator.legit_contract_address,
+        &legit_token_creator.address,
+    )
+    .await?;
 
     transaction
         .commit()
@@ -159,22 +201,22 @@ impl std::fmt::Display for StoreLegitTokenCreatorError {
 #[derive(serde::Deserialize)]
 pub struct LegitTokenCreatorParameters {
     token_creator_address: String,
+    network: Option<String>,
 }
 
 impl TryFrom<LegitTokenCreatorParameters> for TokenCreatorQuery {
     type Error = String;
 
     fn try_from(value: LegitTokenCreatorParameters) -> Result<Self, Self::Error> {
-        let token_creator_address = Address::parse(value.token_creator_address)?;
-        Ok(Self {
-            token_creator_address,
-        })
+        TokenCreatorQuery::parse(value.token_creator_address, value.network)
     }
 }
 
 #[derive(serde::Deserialize, serde::Serialize)]
 pub struct LegitTokenCreatorResponse {
     pub data: Vec<FormDataLegitTokenCreator>,
+    /// The same creations as `data`, keyed by network.
+    pub by_network: BTreeMap<String, Vec<FormDataLegitTokenCreator>>,
 }
 
 #[allow(clippy::async_yields_async)]
@@ -195,33 +237,46 @@ pub async fn get_legit_token_creators(
     };
     match sqlx::query!(
         r#"
-        SELECT l.address, l.notes, n.network_name, l.legit_contract_address FROM legit_token_creators l
+        SELECT COALESCE(a.display_address, l.address) AS "address!", l.notes, n.network_name,
+            COALESCE(c.display_address, l.legit_contract_address) AS "legit_contract_address!"
+        FROM legit_token_creators l
         INNER JOIN networks n
             ON l.network_of_legit_token = n.network_id
-        WHERE l.address = $1
+        LEFT JOIN addresses a
+            ON a.network_id = l.network_of_legit_token AND a.address = l.address
+        LEFT JOIN addresses c
+            ON c.network_id = l.network_of_legit_token AND c.address = l.legit_contract_address
+        WHERE l.address = $1 AND n.network_name = ANY($2)
+        ORDER BY n.network_name, l.legit_contract_address
         ;
         "#,
         token_creator_query.token_creator_address.as_ref(),
+        &token_creator_query.network_names()[..],
     )
-        .fetch_all(pool.get_ref())
-        .await {
+    .fetch_all(pool.get_ref())
+    .await
+    {
         Ok(rows) => {
             let mut legit_token_creators = LegitTokenCreatorResponse {
-                data: vec![]
+                data: vec![],
+                by_network: BTreeMap::new(),
             };
             for row in rows {
                 let legit_token_creator = FormDataLegitTokenCreator {
                     address: row.address,
                     notes: row.notes,
                     network_of_legit_token: row.network_name,
-                    legit_contract_address: row.legit_contract_address
+                    legit_contract_address: row.legit_contract_address,
                 };
+                legit_token_creators
+                    .by_network
+                    .entry(legit_token_creator.network_of_legit_token.clone())
+                    .or_default()
+                    .push(legit_token_creator.clone());
                 legit_token_creators.data.push(legit_token_creator);
-            };
+            }
             HttpResponse::Ok().json(legit_token_creators)
         }
-        Err(e) => {
-            HttpResponse::InternalServerError().finish()
-        }
+        Err(_) => HttpResponse::InternalServerError().finish(),
     }
 }
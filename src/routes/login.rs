@@ -0,0 +1,153 @@
+use crate::authentication::{
+    end_session, session_user, start_session, validate_credentials, AuthError, Credentials,
+    SESSION_COOKIE,
+};
+use crate::routes::error_chain_fmt;
+use crate::startup::ApplicationBaseUrl;
+use actix_web::cookie::{Cookie, SameSite};
+use actix_web::http::header::{ContentType, LOCATION};
+use actix_web::http::StatusCode;
+use actix_web::{web, HttpRequest, HttpResponse, HttpResponseBuilder, ResponseError};
+use anyhow::Context;
+use sqlx::PgPool;
+
+#[derive(serde::Deserialize)]
+pub struct LoginFormData {
+    username: String,
+    password: String,
+}
+
+#[derive(serde::Deserialize)]
+pub struct LoginParameters {
+    error: Option<String>,
+}
+
+#[derive(thiserror::Error)]
+pub enum LoginError {
+    #[error(transparent)]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for LoginError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for LoginError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            LoginError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+fn see_other(location: &str) -> HttpResponseBuilder {
+    let mut response = HttpResponse::SeeOther();
+    response.insert_header((LOCATION, location));
+    response
+}
+
+/// The login form, or who is signed in when the session cookie is still valid.
+#[allow(clippy::async_yields_async)]
+#[tracing::instrument(name = "Showing the login form", skip(request, parameters, pool))]
+pub async fn login_form(
+    request: HttpRequest,
+    parameters: web::Query<LoginParameters>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, LoginError> {
+    if let Some(cookie) = request.cookie(SESSION_COOKIE) {
+        if let Some((_, username)) = session_user(&pool, cookie.value())
+            .await
+            .context("Failed to look the session up.")?
+        {
+            // Usernames are limited to letters, digits, `.`, `_` and `-`, so they are safe in HTML.
+            return Ok(HttpResponse::Ok()
+                .content_type(ContentType::html())
+                .body(format!(
+                    r#"<!DOCTYPE html>
+<html lang="en">
+<head><meta charset="utf-8"><title>Signed in</title></head>
+<body>
+    <p>Signed in as {}. You can publish newsletters.</p>
+    <form action="/logout" method="post"><button type="submit">Log out</button></form>
+</body>
+</html>"#,
+                    username
+                )));
+        }
+    }
+    let error = match parameters.error.as_deref() {
+        Some(_) => "<p><i>Wrong username or password.</i></p>",
+        None => "",
+    };
+    Ok(HttpResponse::Ok()
+        .content_type(ContentType::html())
+        .body(format!(
+            r#"<!DOCTYPE html>
+<html lang="en">
+<head><meta charset="utf-8"><title>Login</title></head>
+<body>
+    {}
+    <form action="/login" method="post">
+        <label>Username <input type="text" name="username"></label>
+        <label>Password <input type="password" name="password"></label>
+        <button type="submit">Login</button>
+    </form>
+</body>
+</html>"#,
+            error
+        )))
+}
+
+#[allow(clippy::async_yields_async)]
+#[tracing::instrument(
+    name = "Logging in",
+    skip(form, pool, base_url),
+    fields(username = %form.username, user_id = tracing::field::Empty)
+)]
+pub async fn login(
+    form: web::Form<LoginFormData>,
+    pool: web::Data<PgPool>,
+    base_url: web::Data<ApplicationBaseUrl>,
+) -> Result<HttpResponse, LoginError> {
+    let form = form.into_inner();
+    let credentials = Credentials {
+        username: form.username,
+        password: form.password,
+    };
+    let user_id = match validate_credentials(credentials, &pool).await {
+        Ok(user_id) => user_id,
+        Err(AuthError::InvalidCredentials(_)) => {
+            return Ok(see_other("/login?error=credentials").finish())
+        }
+        Err(AuthError::UnexpectedError(e)) => return Err(e.into()),
+    };
+    tracing::Span::current().record("user_id", &tracing::field::display(&user_id));
+    let token = start_session(&pool, user_id)
+        .await
+        .context("Failed to start a session.")?;
+    let cookie = Cookie::build(SESSION_COOKIE, token)
+        .path("/")
+        .http_only(true)
+        .same_site(SameSite::Strict)
+        .secure(base_url.0.starts_with("https"))
+        .finish();
+    Ok(see_other("/login").cookie(cookie).finish())
+}
+
+#[allow(clippy::async_yields_async)]
+#[tracing::instrument(name = "Logging out", skip(request, pool))]
+pub async fn logout(
+    request: HttpRequest,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, LoginError> {
+    if let Some(cookie) = request.cookie(SESSION_COOKIE) {
+        end_session(&pool, cookie.value())
+            .await
+            .context("Failed to end the session.")?;
+    }
+    Ok(see_other("/login")
+        .del_cookie(&Cookie::build(SESSION_COOKIE, "").path("/").finish())
+        .finish())
+}
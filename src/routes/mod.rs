@@ -1,28 +1,53 @@
+mod address_report;
+mod api_keys;
+mod creator_tokens;
+mod email_webhooks;
 mod health_check;
 mod holder_description;
+mod holder_description_history;
+mod holder_metrics;
+mod holder_snapshots;
 mod holders;
+mod holders_import;
 mod legit_token_creator;
+mod login;
 mod newsletters;
 mod scam_creators;
 mod scam_tokens;
 mod subscriptions;
 mod subscriptions_confirm;
+mod token_score;
+mod tokens;
+mod unsubscribe;
+mod users;
 
+pub use address_report::*;
+pub use api_keys::*;
+pub use creator_tokens::*;
+pub use email_webhooks::*;
 pub use health_check::*;
 pub use holder_description::*;
+pub use holder_description_history::*;
+pub use holder_metrics::*;
+pub use holder_snapshots::*;
 pub use holders::*;
+pub use holders_import::*;
 pub use legit_token_creator::*;
+pub use login::*;
 pub use newsletters::*;
 pub use scam_creators::*;
 pub use scam_tokens::*;
 pub use subscriptions::*;
 pub use subscriptions_confirm::*;
+pub use token_score::*;
+pub use tokens::*;
+pub use unsubscribe::*;
+pub use users::*;
 
-use crate::domain::{Address, Network, TokenName};
+use crate::domain::{Address, Network};
 use actix_web::http::StatusCode;
-use actix_web::{web, HttpResponse, ResponseError};
-use sqlx::{PgPool, Postgres, Transaction};
-use tracing_futures::Instrument;
+use actix_web::ResponseError;
+use sqlx::{Postgres, Transaction};
 
 #[tracing::instrument(
     name = "Saving new network in the database",
@@ -39,28 +64,7 @@ async fn insert_network(
         network.as_ref()
     )
     .execute(transaction)
-    .await
-    .map_err(|e| e)?;
-    Ok(())
-}
-
-#[tracing::instrument(
-    name = "Saving new token name in the database",
-    skip(token_name, transaction)
-)]
-async fn insert_token_name(
-    transaction: &mut Transaction<'_, Postgres>,
-    token_name: &TokenName,
-) -> Result<(), sqlx::Error> {
-    sqlx::query!(
-        r#"
-        INSERT INTO token_names (token_name) VALUES ($1) ON CONFLICT DO NOTHING;
-        "#,
-        token_name.as_ref()
-    )
-    .execute(transaction)
-    .await
-    .map_err(|e| e)?;
+    .await?;
     Ok(())
 }
 
@@ -73,21 +77,25 @@ async fn insert_address(
     network: &Network,
     address: &Address,
 ) -> Result<(), sqlx::Error> {
+    // An address first seen in a single case picks up the checksummed spelling when it comes in.
     sqlx::query!(
         r#"
-                INSERT INTO addresses (network_id, address)
+                INSERT INTO addresses (network_id, address, display_address)
                 VALUES (
                  (SELECT network_id FROM networks WHERE network_name = $1),
-                 $2
+                 $2,
+                 $3
                 )
-                ON CONFLICT DO NOTHING;
+                ON CONFLICT (network_id, address) DO UPDATE
+                    SET display_address = EXCLUDED.display_address
+                    WHERE addresses.display_address = addresses.address;
                 "#,
         network.as_ref(),
-        address.as_ref()
+        address.as_ref(),
+        address.display()
     )
     .execute(transaction)
-    .await
-    .map_err(|e| e)?;
+    .await?;
     Ok(())
 }
 
@@ -95,6 +103,8 @@ async fn insert_address(
 pub enum BlockchainAppError {
     #[error("{0}")]
     ValidationError(String),
+    #[error("{0}")]
+    NotFoundError(String),
     #[error(transparent)]
     UnexpectedError(#[from] anyhow::Error),
 }
@@ -109,6 +119,7 @@ impl ResponseError for BlockchainAppError {
     fn status_code(&self) -> StatusCode {
         match self {
             BlockchainAppError::ValidationError(_) => StatusCode::BAD_REQUEST,
+            BlockchainAppError::NotFoundError(_) => StatusCode::NOT_FOUND,
             BlockchainAppError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -1,10 +1,17 @@
-use crate::domain::Email;
-use crate::email_client::EmailClient;
+use crate::api_keys::{api_key, check_api_key, AdminApiKey, ApiKeyRejection};
+use crate::authentication::{
+    basic_authentication, session_user, validate_credentials, AuthError, SESSION_COOKIE,
+};
+use crate::domain::ApiScope;
 use crate::routes::error_chain_fmt;
+use actix_web::body::BoxBody;
+use actix_web::http::header::{HeaderValue, WWW_AUTHENTICATE};
 use actix_web::http::StatusCode;
-use actix_web::{web, HttpResponse, ResponseError};
+use actix_web::{web, HttpRequest, HttpResponse, ResponseError};
 use anyhow::Context;
-use sqlx::PgPool;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
 
 #[derive(serde::Deserialize)]
 pub struct BodyData {
@@ -20,6 +27,12 @@ pub struct Content {
 
 #[derive(thiserror::Error)]
 pub enum PublishError {
+    #[error("Authentication failed.")]
+    AuthError(#[source] anyhow::Error),
+    #[error("{0}")]
+    ForbiddenError(String),
+    #[error("{0}")]
+    NotFoundError(String),
     #[error(transparent)]
     UnexpectedError(#[from] anyhow::Error),
 }
@@ -33,66 +46,248 @@ impl std::fmt::Debug for PublishError {
 impl ResponseError for PublishError {
     fn status_code(&self) -> StatusCode {
         match self {
+            PublishError::AuthError(_) => StatusCode::UNAUTHORIZED,
+            PublishError::ForbiddenError(_) => StatusCode::FORBIDDEN,
+            PublishError::NotFoundError(_) => StatusCode::NOT_FOUND,
             PublishError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
+
+    fn error_response(&self) -> HttpResponse<BoxBody> {
+        let mut response = HttpResponse::build(self.status_code());
+        if let PublishError::AuthError(_) = self {
+            response.insert_header((
+                WWW_AUTHENTICATE,
+                HeaderValue::from_static(r#"Basic realm="publish""#),
+            ));
+        }
+        response.body(self.to_string())
+    }
 }
 
+impl From<AuthError> for PublishError {
+    fn from(e: AuthError) -> Self {
+        match e {
+            AuthError::InvalidCredentials(e) => PublishError::AuthError(e),
+            AuthError::UnexpectedError(e) => PublishError::UnexpectedError(e),
+        }
+    }
+}
+
+/// Editors publish with Basic credentials or the session cookie from `/login`. Automated
+/// senders can use an API key with the `publish:newsletter` scope instead.
+#[tracing::instrument(
+    name = "Authorizing a publisher",
+    skip(request, pool),
+    fields(user_id = tracing::field::Empty)
+)]
+async fn authorize_publisher(request: &HttpRequest, pool: &PgPool) -> Result<(), PublishError> {
+    if let Some(key) = api_key(request.headers()) {
+        let admin_api_key = request.app_data::<web::Data<AdminApiKey>>();
+        return check_api_key(
+            pool,
+            admin_api_key.map(|a| a.get_ref()),
+            &key,
+            ApiScope::PublishNewsletter,
+        )
+        .await
+        .context("Failed to check the API key.")?
+        .map_err(|rejection| match rejection {
+            // The caller is known, so asking for other credentials would not help.
+            ApiKeyRejection::MissingScope(_) => PublishError::ForbiddenError(rejection.to_string()),
+            ApiKeyRejection::Unknown => PublishError::AuthError(rejection.into()),
+        })
+        .map(|_| ());
+    }
+    if let Some(credentials) =
+        basic_authentication(request.headers()).map_err(PublishError::AuthError)?
+    {
+        let user_id = validate_credentials(credentials, pool).await?;
+        tracing::Span::current().record("user_id", &tracing::field::display(&user_id));
+        return Ok(());
+    }
+    if let Some(cookie) = request.cookie(SESSION_COOKIE) {
+        if session_user(pool, cookie.value())
+            .await
+            .context("Failed to look the session up.")?
+            .is_some()
+        {
+            return Ok(());
+        }
+    }
+    Err(PublishError::AuthError(anyhow::anyhow!(
+        "No credentials, API key or session were sent."
+    )))
+}
+
+/// Stores the issue and queues one delivery per confirmed subscriber. The issue delivery worker
+/// sends them, so the response does not wait for Postmark.
+#[tracing::instrument(
+    name = "Publishing a newsletter issue",
+    skip(body, pool, request),
+    fields(newsletter_issue_id = tracing::field::Empty)
+)]
 pub async fn publish_newsletter(
+    request: HttpRequest,
     body: web::Json<BodyData>,
     pool: web::Data<PgPool>,
-    email_client: web::Data<EmailClient>,
 ) -> Result<HttpResponse, PublishError> {
-    let subscribers = get_confirmed_subscribers(&pool).await?;
-    for subscriber in subscribers {
-        match subscriber {
-            Ok(subscriber) => {
-                email_client
-                    .send_email(
-                        &subscriber.email,
-                        &body.title,
-                        &body.content.html,
-                        &body.content.text,
-                    )
-                    .await
-                    .with_context(|| {
-                        format!("Failed to send newsletter issue to {}", subscriber.email)
-                    })?;
-            }
-            Err(error) => {
-                tracing::warn!(
-                    error.cause_chain = ?error,
-                    "Skipping a confirmed subscriber. \
-                    Their stored contact details are invalid",
-                );
-            }
-        }
-    }
-    Ok(HttpResponse::Ok().finish())
+    authorize_publisher(&request, &pool).await?;
+    let mut transaction = pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+    let newsletter_issue_id = insert_newsletter_issue(&mut transaction, &body)
+        .await
+        .context("Failed to store newsletter issue details")?;
+    tracing::Span::current().record(
+        "newsletter_issue_id",
+        &tracing::field::display(newsletter_issue_id),
+    );
+    let queued = enqueue_delivery_tasks(&mut transaction, newsletter_issue_id)
+        .await
+        .context("Failed to enqueue delivery tasks")?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the newsletter issue.")?;
+    Ok(HttpResponse::Accepted().json(PublishedIssue {
+        newsletter_issue_id: newsletter_issue_id.to_string(),
+        queued,
+    }))
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct PublishedIssue {
+    pub newsletter_issue_id: String,
+    pub queued: u64,
 }
 
-struct ConfirmedSubscriber {
-    email: Email,
+#[tracing::instrument(skip_all)]
+async fn insert_newsletter_issue(
+    transaction: &mut Transaction<'_, Postgres>,
+    body: &BodyData,
+) -> Result<Uuid, sqlx::Error> {
+    let newsletter_issue_id = Uuid::new_v4();
+    sqlx::query!(
+        r#"
+        INSERT INTO newsletter_issues (
+            newsletter_issue_id, title, text_content, html_content, published_on
+        )
+        VALUES ($1, $2, $3, $4, $5);
+        "#,
+        newsletter_issue_id,
+        body.title,
+        body.content.text,
+        body.content.html,
+        Utc::now(),
+    )
+    .execute(transaction)
+    .await?;
+    Ok(newsletter_issue_id)
 }
 
-#[tracing::instrument(name = "Adding a new subscriber", skip(pool))]
-async fn get_confirmed_subscribers(
-    pool: &PgPool,
-) -> Result<Vec<Result<ConfirmedSubscriber, anyhow::Error>>, anyhow::Error> {
-    let confirmed_subscribers = sqlx::query!(
+/// Queues the issue for every confirmed subscriber and returns how many there are.
+#[tracing::instrument(skip_all)]
+async fn enqueue_delivery_tasks(
+    transaction: &mut Transaction<'_, Postgres>,
+    newsletter_issue_id: Uuid,
+) -> Result<u64, sqlx::Error> {
+    let queued = sqlx::query!(
         r#"
-        SELECT email
+        INSERT INTO issue_delivery_queue (newsletter_issue_id, subscriber_email, execute_after)
+        SELECT $1, email, now()
         FROM subscriptions
-        WHERE status = 'confirmed'
+        WHERE status = 'confirmed';
         "#,
+        newsletter_issue_id,
     )
-    .fetch_all(pool)
+    .execute(transaction)
     .await?
-    .into_iter()
-    .map(|r| match Email::parse(r.email) {
-        Ok(email) => Ok(ConfirmedSubscriber { email }),
-        Err(error) => Err(anyhow::anyhow!(error)),
-    })
-    .collect();
-    Ok(confirmed_subscribers)
+    .rows_affected();
+    Ok(queued)
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct DeliveryData {
+    pub subscriber_email: String,
+    pub status: String,
+    pub n_retries: i16,
+    pub last_error: Option<String>,
+    pub delivered_on: Option<DateTime<Utc>>,
+    pub provider_message_id: Option<String>,
+    /// A bounce or spam complaint Postmark reported about the email.
+    pub event: Option<String>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct IssueDeliveriesResponse {
+    pub newsletter_issue_id: String,
+    pub title: String,
+    pub published_on: DateTime<Utc>,
+    pub pending: usize,
+    pub delivered: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub deliveries: Vec<DeliveryData>,
+}
+
+/// Where each recipient's delivery of an issue stands. Publishers only, since it lists addresses.
+#[allow(clippy::async_yields_async)]
+#[tracing::instrument(name = "Getting newsletter deliveries", skip(request, path, pool))]
+pub async fn get_newsletter_deliveries(
+    request: HttpRequest,
+    path: web::Path<String>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, PublishError> {
+    authorize_publisher(&request, &pool).await?;
+    let newsletter_issue_id = path.into_inner();
+    let newsletter_issue_id = Uuid::parse_str(&newsletter_issue_id).map_err(|_| {
+        PublishError::NotFoundError(format!(
+            "{} is not a newsletter issue id.",
+            newsletter_issue_id
+        ))
+    })?;
+    let issue = sqlx::query!(
+        r#"
+        SELECT title, published_on FROM newsletter_issues WHERE newsletter_issue_id = $1;
+        "#,
+        newsletter_issue_id,
+    )
+    .fetch_optional(pool.get_ref())
+    .await
+    .context("Failed to fetch the newsletter issue.")?
+    .ok_or_else(|| {
+        PublishError::NotFoundError(format!(
+            "There is no newsletter issue {}.",
+            newsletter_issue_id
+        ))
+    })?;
+    let deliveries = sqlx::query_as!(
+        DeliveryData,
+        r#"
+        SELECT q.subscriber_email, q.status, q.n_retries, q.last_error, q.delivered_on,
+            s.provider_message_id AS "provider_message_id?", s.event
+        FROM issue_delivery_queue q
+        LEFT JOIN email_sends s
+            ON s.newsletter_issue_id = q.newsletter_issue_id AND s.recipient = q.subscriber_email
+        WHERE q.newsletter_issue_id = $1
+        ORDER BY q.subscriber_email;
+        "#,
+        newsletter_issue_id,
+    )
+    .fetch_all(pool.get_ref())
+    .await
+    .context("Failed to fetch the deliveries.")?;
+    let count = |status: &str| deliveries.iter().filter(|d| d.status == status).count();
+    Ok(HttpResponse::Ok().json(IssueDeliveriesResponse {
+        newsletter_issue_id: newsletter_issue_id.to_string(),
+        title: issue.title,
+        published_on: issue.published_on,
+        pending: count("pending"),
+        delivered: count("delivered"),
+        failed: count("failed"),
+        skipped: count("skipped"),
+        deliveries,
+    }))
 }
@@ -1,16 +1,14 @@
-use super::{error_chain_fmt, insert_address, insert_network, BlockchainAppError};
-use crate::domain::{Address, Network, Notes, ScamCreator, ScamType, TokenCreatorQuery};
-use actix_web::ResponseError;
+use super::{
+    error_chain_fmt, insert_address, insert_network, link_token_creator, BlockchainAppError,
+};
+use crate::domain::{Address, Network, Notes, ScamCreator, TokenCreatorQuery};
 use actix_web::{web, HttpResponse};
 use anyhow::Context;
-use chrono::{DateTime, Utc};
-use sqlx::types::BigDecimal;
 use sqlx::{PgPool, Postgres, Transaction};
+use std::collections::BTreeMap;
 use std::convert::{TryFrom, TryInto};
-use tracing_futures::Instrument;
-use uuid::Uuid;
 
-#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
 pub struct FormDataScammers {
     address: String,
     notes: Option<String>,
@@ -22,10 +20,11 @@ impl TryFrom<FormDataScammers> for ScamCreator {
     type Error = String;
 
     fn try_from(value: FormDataScammers) -> Result<Self, Self::Error> {
-        let address = Address::parse(value.address)?;
-        let notes = Notes::parse(value.notes)?;
         let network_of_scammed_token = Network::parse(value.network_of_scammed_token)?;
-        let scammed_contract_address = Address::parse(value.scammed_contract_address)?;
+        let address = Address::parse(value.address, &network_of_scammed_token)?;
+        let notes = Notes::parse(value.notes)?;
+        let scammed_contract_address =
+            Address::parse(value.scammed_contract_address, &network_of_scammed_token)?;
         Ok(Self {
             address,
             notes,
@@ -144,6 +143,13 @@ pub async fn register_scammer(
             &scam_creator.address.as_ref(),
             &scam_creator.scammed_contract_address.as_ref()
         ))?;
+    link_token_creator(
+        &mut transaction,
+        &scam_creator.network_of_scammed_token,
+        &scam_creator.scammed_contract_address,
+        &scam_creator.address,
+    )
+    .await?;
 
     transaction
         .commit()
@@ -156,22 +162,22 @@ pub async fn register_scammer(
 #[derive(serde::Deserialize)]
 pub struct ScammerParameters {
     token_creator_address: String,
+    network: Option<String>,
 }
 
 impl TryFrom<ScammerParameters> for TokenCreatorQuery {
     type Error = String;
 
     fn try_from(value: ScammerParameters) -> Result<Self, Self::Error> {
-        let token_creator_address = Address::parse(value.token_creator_address)?;
-        Ok(Self {
-            token_creator_address,
-        })
+        TokenCreatorQuery::parse(value.token_creator_address, value.network)
     }
 }
 
 #[derive(serde::Deserialize, serde::Serialize)]
 pub struct ScamTokenCreatorResponse {
     pub data: Vec<FormDataScammers>,
+    /// The same reports as `data`, keyed by network.
+    pub by_network: BTreeMap<String, Vec<FormDataScammers>>,
 }
 
 #[allow(clippy::async_yields_async)]
@@ -192,33 +198,46 @@ pub async fn get_scammers(
     };
     match sqlx::query!(
         r#"
-        SELECT s.address, s.notes, n.network_name, s.scammed_contract_address FROM scam_token_creators s
+        SELECT COALESCE(a.display_address, s.address) AS "address!", s.notes, n.network_name,
+            COALESCE(c.display_address, s.scammed_contract_address) AS "scammed_contract_address!"
+        FROM scam_token_creators s
         INNER JOIN networks n
             ON s.network_of_scammed_token = n.network_id
-        WHERE s.address = $1
+        LEFT JOIN addresses a
+            ON a.network_id = s.network_of_scammed_token AND a.address = s.address
+        LEFT JOIN addresses c
+            ON c.network_id = s.network_of_scammed_token AND c.address = s.scammed_contract_address
+        WHERE s.address = $1 AND n.network_name = ANY($2)
+        ORDER BY n.network_name, s.scammed_contract_address
         ;
         "#,
         scammer_query.token_creator_address.as_ref(),
+        &scammer_query.network_names()[..],
     )
-        .fetch_all(pool.get_ref())
-        .await {
+    .fetch_all(pool.get_ref())
+    .await
+    {
         Ok(rows) => {
             let mut scammers = ScamTokenCreatorResponse {
-                data: vec![]
+                data: vec![],
+                by_network: BTreeMap::new(),
             };
             for row in rows {
                 let scammer = FormDataScammers {
                     address: row.address,
                     notes: row.notes,
                     network_of_scammed_token: row.network_name,
-                    scammed_contract_address: row.scammed_contract_address
+                    scammed_contract_address: row.scammed_contract_address,
                 };
+                scammers
+                    .by_network
+                    .entry(scammer.network_of_scammed_token.clone())
+                    .or_default()
+                    .push(scammer.clone());
                 scammers.data.push(scammer);
-            };
+            }
             HttpResponse::Ok().json(scammers)
         }
-        Err(e) => {
-            HttpResponse::InternalServerError().finish()
-        }
+        Err(_) => HttpResponse::InternalServerError().finish(),
     }
 }
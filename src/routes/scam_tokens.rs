@@ -1,26 +1,225 @@
-use super::{insert_address, insert_network};
-use crate::domain::{Address, Network, Notes, ScamCreator, ScamType};
-use actix_web::ResponseError;
+use super::{
+    error_chain_fmt, insert_address, insert_network, link_token_creator, BlockchainAppError,
+};
+use crate::domain::{Address, Network, Notes, ScamToken, ScamType};
 use actix_web::{web, HttpResponse};
-use chrono::{DateTime, Utc};
-use sqlx::types::BigDecimal;
+use anyhow::Context;
 use sqlx::{PgPool, Postgres, Transaction};
 use std::convert::{TryFrom, TryInto};
-use tracing_futures::Instrument;
-use uuid::Uuid;
 
 #[derive(serde::Deserialize, serde::Serialize)]
-pub struct FormData {
+pub struct FormDataScamToken {
     address: String,
-    notes: String,
+    notes: Option<String>,
     scam_creator_network: String,
     scam_creator_address: String,
     scam_type: String,
 }
 
+impl TryFrom<FormDataScamToken> for ScamToken {
+    type Error = String;
+
+    fn try_from(value: FormDataScamToken) -> Result<Self, Self::Error> {
+        let scam_creator_network = Network::parse(value.scam_creator_network)?;
+        let address = Address::parse(value.address, &scam_creator_network)?;
+        let notes = Notes::parse(value.notes)?;
+        let scam_creator_address =
+            Address::parse(value.scam_creator_address, &scam_creator_network)?;
+        let scam_type = ScamType::parse(value.scam_type)?;
+        Ok(Self {
+            address,
+            notes,
+            scam_creator_network,
+            scam_creator_address,
+            scam_type,
+        })
+    }
+}
+
+#[tracing::instrument(name = "Inserting a scam token.", skip(transaction, scam_token))]
+pub async fn insert_scam_token(
+    transaction: &mut Transaction<'_, Postgres>,
+    scam_token: &ScamToken,
+) -> Result<(), StoreScamTokenError> {
+    sqlx::query!(
+        r#"
+        INSERT INTO scam_tokens (address, notes, scam_creator_network, scam_creator_address, scam_type)
+        VALUES (
+            $1,
+            $2,
+            (SELECT network_id FROM networks WHERE network_name = $3),
+            $4,
+            ($5::text)::scam_types
+        );
+        "#,
+        scam_token.address.as_ref(),
+        scam_token.notes.as_ref(),
+        scam_token.scam_creator_network.as_ref(),
+        scam_token.scam_creator_address.as_ref(),
+        scam_token.scam_type.as_ref(),
+    )
+    .execute(transaction)
+    .await
+    .map_err(StoreScamTokenError)?;
+    Ok(())
+}
+
+pub struct StoreScamTokenError(sqlx::Error);
+
+impl std::error::Error for StoreScamTokenError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+impl std::fmt::Debug for StoreScamTokenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl std::fmt::Display for StoreScamTokenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "A database failure was encountered while trying to store a scam token."
+        )
+    }
+}
+
+#[allow(clippy::async_yields_async)]
+#[tracing::instrument(
+    name = "Adding a new scam token.",
+    skip(form, pool),
+    fields(
+        address = %form.address,
+        scam_creator_network = %form.scam_creator_network,
+        scam_creator_address = %form.scam_creator_address,
+        scam_type = %form.scam_type
+    )
+)]
 pub async fn register_scam_token(
-    form: web::Form<FormData>,
+    form: web::Form<FormDataScamToken>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, BlockchainAppError> {
+    let scam_token: ScamToken = form
+        .0
+        .try_into()
+        .map_err(BlockchainAppError::ValidationError)?;
+
+    let mut transaction = pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+
+    insert_network(&mut transaction, &scam_token.scam_creator_network)
+        .await
+        .context("Failed to insert network in the database.")?;
+
+    insert_address(
+        &mut transaction,
+        &scam_token.scam_creator_network,
+        &scam_token.scam_creator_address,
+    )
+    .await
+    .context(format!(
+        "Failed to insert scam token creator's address {} in the database.",
+        &scam_token.scam_creator_address.as_ref()
+    ))?;
+
+    insert_address(
+        &mut transaction,
+        &scam_token.scam_creator_network,
+        &scam_token.address,
+    )
+    .await
+    .context(format!(
+        "Failed to insert scam token contract address {} in the database.",
+        &scam_token.address.as_ref()
+    ))?;
+
+    insert_scam_token(&mut transaction, &scam_token)
+        .await
+        .context(format!(
+            "Failed to insert scam token {} created by {} in the database.",
+            &scam_token.address.as_ref(),
+            &scam_token.scam_creator_address.as_ref()
+        ))?;
+    link_token_creator(
+        &mut transaction,
+        &scam_token.scam_creator_network,
+        &scam_token.address,
+        &scam_token.scam_creator_address,
+    )
+    .await?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to store a scam token.")?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[derive(serde::Deserialize)]
+pub struct ScamTokenParameters {
+    contract_address: String,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct ScamTokenResponse {
+    pub data: Vec<FormDataScamToken>,
+}
+
+#[allow(clippy::async_yields_async)]
+#[tracing::instrument(
+    name = "Getting a scam token.",
+    skip(parameters, pool),
+    fields(
+        contract_address = %parameters.contract_address
+    )
+)]
+pub async fn get_scam_tokens(
+    parameters: web::Query<ScamTokenParameters>,
     pool: web::Data<PgPool>,
-) -> HttpResponse {
-    HttpResponse::Ok().finish()
+) -> Result<HttpResponse, BlockchainAppError> {
+    let contract_address = Address::parse_for_any_network(parameters.0.contract_address)
+        .map_err(|e| BlockchainAppError::ValidationError(e.into()))?;
+    let rows = sqlx::query!(
+        r#"
+        SELECT COALESCE(a.display_address, s.address) AS "address!", s.notes, n.network_name,
+            COALESCE(c.display_address, s.scam_creator_address) AS "scam_creator_address!",
+            s.scam_type::text AS "scam_type!"
+        FROM scam_tokens s
+        INNER JOIN networks n
+            ON s.scam_creator_network = n.network_id
+        LEFT JOIN addresses a
+            ON a.network_id = s.scam_creator_network AND a.address = s.address
+        LEFT JOIN addresses c
+            ON c.network_id = s.scam_creator_network AND c.address = s.scam_creator_address
+        WHERE s.address = $1
+        ;
+        "#,
+        contract_address.as_ref(),
+    )
+    .fetch_all(pool.get_ref())
+    .await
+    .context(format!(
+        "Failed to fetch scam token {} from the database.",
+        contract_address.as_ref()
+    ))?;
+
+    let scam_tokens = ScamTokenResponse {
+        data: rows
+            .into_iter()
+            .map(|row| FormDataScamToken {
+                address: row.address,
+                notes: row.notes,
+                scam_creator_network: row.network_name,
+                scam_creator_address: row.scam_creator_address,
+                scam_type: row.scam_type,
+            })
+            .collect(),
+    };
+    Ok(HttpResponse::Ok().json(scam_tokens))
 }
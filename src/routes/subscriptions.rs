@@ -1,6 +1,6 @@
 use super::error_chain_fmt;
 use crate::domain::{Email, NewSubscriber, SubscriberName};
-use crate::email_client::EmailClient;
+use crate::email_client::{store_email_send, EmailClient};
 use crate::startup::ApplicationBaseUrl;
 use actix_web::http::StatusCode;
 use actix_web::{web, HttpResponse, ResponseError};
@@ -81,7 +81,8 @@ pub async fn subscribe(
         .commit()
         .await
         .context("Failed to commit SQL transaction to store a new subscriber.")?;
-    send_confirmation_email(
+    let recipient = new_subscriber.email.clone();
+    let provider_message_id = send_confirmation_email(
         &email_client,
         new_subscriber,
         &base_url.0,
@@ -89,6 +90,11 @@ pub async fn subscribe(
     )
     .await
     .context("Failed to send a confirmation email.")?;
+    if let Some(provider_message_id) = provider_message_id {
+        store_email_send(pool.get_ref(), &provider_message_id, &recipient, None)
+            .await
+            .context("Failed to store the confirmation email's message id.")?;
+    }
     Ok(HttpResponse::Ok().finish())
 }
 
@@ -109,7 +115,7 @@ pub async fn send_confirmation_email(
     new_subscriber: NewSubscriber,
     base_url: &str,
     subscription_token: &str,
-) -> Result<(), reqwest::Error> {
+) -> Result<Option<String>, reqwest::Error> {
     let confirmation_link = format!(
         "{}/subscriptions/confirm?subscription_token={}",
         base_url, subscription_token
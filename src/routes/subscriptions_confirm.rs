@@ -27,7 +27,8 @@ pub async fn confirm(parameters: web::Query<Parameters>, pool: web::Data<PgPool>
 pub async fn confirm_subscriber(pool: &PgPool, subscriber_id: Uuid) -> Result<(), sqlx::Error> {
     sqlx::query!(
         r#"
-            UPDATE subscriptions SET status = 'confirmed' WHERE id = $1
+            UPDATE subscriptions SET status = 'confirmed'
+            WHERE id = $1 AND status = 'pending_confirmation'
         "#,
         subscriber_id,
     )
@@ -0,0 +1,170 @@
+use super::BlockchainAppError;
+use crate::domain::{Address, AddressType, Network};
+use crate::scoring::{ScoredHolder, ScoringSettings, TokenFacts, TokenScore};
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use sqlx::PgPool;
+
+#[derive(serde::Deserialize)]
+pub struct ScorePath {
+    network: String,
+    contract_address: String,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct TokenScoreResponse {
+    pub network: String,
+    pub contract_address: String,
+    #[serde(flatten)]
+    pub data: TokenScore,
+}
+
+#[allow(clippy::async_yields_async)]
+#[tracing::instrument(
+    name = "Scoring a token.",
+    skip(path, pool, scoring),
+    fields(
+        network = % path.network,
+        contract_address = % path.contract_address
+    )
+)]
+pub async fn get_token_score(
+    path: web::Path<ScorePath>,
+    pool: web::Data<PgPool>,
+    scoring: web::Data<ScoringSettings>,
+) -> Result<HttpResponse, BlockchainAppError> {
+    let path = path.into_inner();
+    let network = Network::parse(path.network).map_err(BlockchainAppError::ValidationError)?;
+    let contract_address = Address::parse(path.contract_address, &network)
+        .map_err(|e| BlockchainAppError::ValidationError(e.into()))?;
+
+    let known_scammer_creators = get_known_scammer_creators(&pool, &network, &contract_address)
+        .await
+        .context("Failed to fetch the creators of the token.")?;
+    let holders = get_latest_holders(&pool, &network, &contract_address)
+        .await
+        .context("Failed to fetch the latest holders of the token.")?;
+    let facts = TokenFacts {
+        known_scammer_creators,
+        holders,
+    };
+
+    Ok(HttpResponse::Ok().json(TokenScoreResponse {
+        network: network.as_ref().to_string(),
+        contract_address: contract_address.display().to_string(),
+        data: scoring.score(&facts),
+    }))
+}
+
+/// Creators come from scam and legit creator reports and from descriptions tagging an address as
+/// the token's creator. A creator is a known scammer when any report or description says so.
+#[tracing::instrument(
+    name = "Fetching known scammers that created a token.",
+    skip(pool, network, contract_address)
+)]
+async fn get_known_scammer_creators(
+    pool: &PgPool,
+    network: &Network,
+    contract_address: &Address,
+) -> Result<Vec<String>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        WITH network AS (
+            SELECT network_id FROM networks WHERE network_name = $1
+        ), creators AS (
+            SELECT t.scam_creator_address AS address FROM scam_tokens t
+            WHERE t.scam_creator_network IN (SELECT network_id FROM network) AND t.address = $2
+            UNION
+            SELECT s.address FROM scam_token_creators s
+            WHERE s.network_of_scammed_token IN (SELECT network_id FROM network)
+                AND s.scammed_contract_address = $2
+            UNION
+            SELECT l.address FROM legit_token_creators l
+            WHERE l.network_of_legit_token IN (SELECT network_id FROM network)
+                AND l.legit_contract_address = $2
+            UNION
+            SELECT d.holder_address FROM holder_descriptions d
+            WHERE d.network_id IN (SELECT network_id FROM network)
+                AND d.contract_address = $2
+                AND 'token_creator' = ANY(d.address_types)
+        )
+        SELECT COALESCE(a.display_address, c.address) AS "address!"
+        FROM creators c
+        LEFT JOIN addresses a
+            ON a.network_id IN (SELECT network_id FROM network) AND a.address = c.address
+        WHERE EXISTS (
+                SELECT 1 FROM scam_token_creators s
+                WHERE s.network_of_scammed_token IN (SELECT network_id FROM network)
+                    AND s.address = c.address
+            )
+            OR EXISTS (
+                SELECT 1 FROM scam_tokens t
+                WHERE t.scam_creator_network IN (SELECT network_id FROM network)
+                    AND t.scam_creator_address = c.address
+            )
+            OR EXISTS (
+                SELECT 1 FROM holder_descriptions d
+                WHERE d.network_id IN (SELECT network_id FROM network)
+                    AND d.holder_address = c.address
+                    AND 'scammer' = ANY(d.address_types)
+            )
+        ORDER BY 1;
+        "#,
+        network.as_ref(),
+        contract_address.as_ref(),
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(|r| r.address).collect())
+}
+
+#[tracing::instrument(
+    name = "Fetching the latest holders of a token.",
+    skip(pool, network, contract_address)
+)]
+async fn get_latest_holders(
+    pool: &PgPool,
+    network: &Network,
+    contract_address: &Address,
+) -> Result<Vec<ScoredHolder>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT h.holder_address, h.place, h.amount,
+            ARRAY(
+                SELECT DISTINCT t
+                FROM holder_descriptions d, unnest(d.address_types) t
+                WHERE d.network_id = h.network_id AND d.holder_address = h.holder_address
+            ) AS "address_types!"
+        FROM holder_totals h
+        INNER JOIN networks n
+            ON n.network_id = h.network_id AND n.network_name = $1
+        WHERE h.contract_address = $2
+            AND h.snapshot_id = (
+                SELECT l.snapshot_id FROM holder_snapshots l
+                WHERE l.network_id = h.network_id
+                    AND l.contract_address = h.contract_address
+                    AND EXISTS (SELECT 1 FROM holder_totals e WHERE e.snapshot_id = l.snapshot_id)
+                ORDER BY l.checked_on DESC, l.snapshot_id DESC
+                LIMIT 1
+            )
+        ORDER BY h.place ASC;
+        "#,
+        network.as_ref(),
+        contract_address.as_ref(),
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|r| ScoredHolder {
+            holder_address: r.holder_address,
+            place: r.place,
+            amount: r.amount,
+            address_types: r
+                .address_types
+                .into_iter()
+                .filter_map(|t| AddressType::parse(t).ok())
+                .collect(),
+        })
+        .collect())
+}
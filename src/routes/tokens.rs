@@ -0,0 +1,439 @@
+use super::{error_chain_fmt, insert_address, insert_network, BlockchainAppError};
+use crate::domain::{
+    parse_decimals, Address, Network, NewToken, TokenAmount, TokenMetadata, TokenName, TokenSymbol,
+};
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use sqlx::types::BigDecimal;
+use sqlx::{PgPool, Postgres, Transaction};
+use std::convert::{TryFrom, TryInto};
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct TokenFormData {
+    network: String,
+    contract_address: String,
+    token_name: String,
+    symbol: Option<String>,
+    decimals: Option<i16>,
+    creator_address: Option<String>,
+    launched_on: Option<DateTime<Utc>>,
+}
+
+impl TryFrom<TokenFormData> for NewToken {
+    type Error = String;
+
+    fn try_from(value: TokenFormData) -> Result<Self, Self::Error> {
+        let network = Network::parse(value.network)?;
+        let contract_address = Address::parse(value.contract_address, &network)?;
+        let token_name = TokenName::parse(value.token_name)?;
+        let metadata = TokenMetadata {
+            symbol: value.symbol.map(TokenSymbol::parse).transpose()?,
+            decimals: value.decimals.map(parse_decimals).transpose()?,
+            creator_address: value
+                .creator_address
+                .map(|a| Address::parse(a, &network))
+                .transpose()?,
+            launched_on: value.launched_on,
+        };
+        Ok(Self {
+            network,
+            contract_address,
+            token_name,
+            metadata,
+        })
+    }
+}
+
+#[derive(serde::Deserialize)]
+pub struct TokenParameters {
+    network: Option<String>,
+    token_name: Option<String>,
+    symbol: Option<String>,
+    creator_address: Option<String>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct TokenData {
+    pub network: String,
+    pub contract_address: String,
+    pub token_name: Option<String>,
+    pub symbol: Option<String>,
+    pub decimals: Option<i16>,
+    /// Divided by the token's decimals.
+    pub total_supply: Option<BigDecimal>,
+    pub raw_total_supply: Option<BigDecimal>,
+    pub creator_address: Option<String>,
+    pub launched_on: Option<DateTime<Utc>>,
+    pub updated_on: DateTime<Utc>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct TokensResponse {
+    pub data: Vec<TokenData>,
+}
+
+/// Registers a token or updates what is known about it, and returns the decimals known for it.
+/// The name is replaced, other fields are only replaced when given. Decimals are fixed on chain,
+/// so a submission that disagrees with the recorded ones is rejected.
+#[tracing::instrument(
+    name = "Saving a token in the database",
+    skip(transaction, network, contract_address, token_name, metadata)
+)]
+pub async fn upsert_token(
+    transaction: &mut Transaction<'_, Postgres>,
+    network: &Network,
+    contract_address: &Address,
+    token_name: &TokenName,
+    metadata: &TokenMetadata,
+) -> Result<Option<i16>, BlockchainAppError> {
+    if let Some(creator_address) = &metadata.creator_address {
+        insert_address(transaction, network, creator_address)
+            .await
+            .context(format!(
+                "Failed to insert creator address {} in the database.",
+                creator_address.as_ref()
+            ))?;
+    }
+    let row = sqlx::query!(
+        r#"
+        INSERT INTO tokens (network_id, contract_address, token_name, symbol, decimals,
+            creator_address, launched_on, updated_on)
+        VALUES ((SELECT network_id FROM networks WHERE network_name = $1), $2, $3, $4, $5, $6, $7, now())
+        ON CONFLICT (network_id, contract_address) DO UPDATE
+            SET token_name = EXCLUDED.token_name,
+                symbol = COALESCE(EXCLUDED.symbol, tokens.symbol),
+                decimals = COALESCE(tokens.decimals, EXCLUDED.decimals),
+                creator_address = COALESCE(EXCLUDED.creator_address, tokens.creator_address),
+                launched_on = COALESCE(EXCLUDED.launched_on, tokens.launched_on),
+                updated_on = EXCLUDED.updated_on
+        RETURNING decimals;
+        "#,
+        network.as_ref(),
+        contract_address.as_ref(),
+        token_name.as_ref(),
+        metadata.symbol.as_ref().map(|s| s.as_ref()),
+        metadata.decimals,
+        metadata.creator_address.as_ref().map(|a| a.as_ref()),
+        metadata.launched_on,
+    )
+    .fetch_one(transaction)
+    .await
+    .map_err(StoreTokenRegistryError)
+    .context(format!(
+        "Failed to store token {} in the database.",
+        contract_address.as_ref()
+    ))?;
+    match (metadata.decimals, row.decimals) {
+        (Some(submitted), Some(recorded)) if submitted != recorded => {
+            Err(BlockchainAppError::ValidationError(format!(
+                "Token {} has {} decimals, not {}.",
+                contract_address.as_ref(),
+                recorded,
+                submitted
+            )))
+        }
+        _ => Ok(row.decimals),
+    }
+}
+
+#[tracing::instrument(
+    name = "Saving a token's total supply in the database",
+    skip(transaction, network, contract_address)
+)]
+pub async fn update_total_supply(
+    transaction: &mut Transaction<'_, Postgres>,
+    network: &Network,
+    contract_address: &Address,
+    raw_total_supply: &BigDecimal,
+) -> Result<(), StoreTokenRegistryError> {
+    sqlx::query!(
+        r#"
+        UPDATE tokens
+        SET total_supply = $3
+        WHERE network_id = (SELECT network_id FROM networks WHERE network_name = $1)
+            AND contract_address = $2;
+        "#,
+        network.as_ref(),
+        contract_address.as_ref(),
+        raw_total_supply,
+    )
+    .execute(transaction)
+    .await
+    .map_err(StoreTokenRegistryError)?;
+    Ok(())
+}
+
+/// Records who deployed a token reported as a scam or legit, registering the token when it is
+/// new. The first creator recorded is kept; a report naming another creator is rejected.
+#[tracing::instrument(
+    name = "Linking a token to its creator in the database",
+    skip(transaction, network, contract_address, creator_address)
+)]
+pub async fn link_token_creator(
+    transaction: &mut Transaction<'_, Postgres>,
+    network: &Network,
+    contract_address: &Address,
+    creator_address: &Address,
+) -> Result<(), BlockchainAppError> {
+    let row = sqlx::query!(
+        r#"
+        INSERT INTO tokens (network_id, contract_address, creator_address, updated_on)
+        VALUES ((SELECT network_id FROM networks WHERE network_name = $1), $2, $3, now())
+        ON CONFLICT (network_id, contract_address) DO UPDATE
+            SET creator_address = COALESCE(tokens.creator_address, EXCLUDED.creator_address),
+                updated_on = EXCLUDED.updated_on
+        RETURNING creator_address;
+        "#,
+        network.as_ref(),
+        contract_address.as_ref(),
+        creator_address.as_ref(),
+    )
+    .fetch_one(transaction)
+    .await
+    .map_err(StoreTokenRegistryError)
+    .context(format!(
+        "Failed to link token {} to its creator in the database.",
+        contract_address.as_ref()
+    ))?;
+    match row.creator_address {
+        Some(recorded) if recorded != creator_address.as_ref() => {
+            Err(BlockchainAppError::ValidationError(format!(
+                "Token {} was created by {}, not {}.",
+                contract_address.as_ref(),
+                recorded,
+                creator_address.as_ref()
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+pub struct StoreTokenRegistryError(sqlx::Error);
+
+impl std::error::Error for StoreTokenRegistryError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+impl std::fmt::Debug for StoreTokenRegistryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl std::fmt::Display for StoreTokenRegistryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "A database failure was encountered while trying to store a token."
+        )
+    }
+}
+
+#[allow(clippy::async_yields_async)]
+#[tracing::instrument(
+    name = "Registering a token.",
+    skip(form, pool),
+    fields(
+        network = % form.network,
+        contract_address = % form.contract_address,
+        token_name = % form.token_name
+    )
+)]
+pub async fn register_token(
+    form: web::Json<TokenFormData>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, BlockchainAppError> {
+    let token: NewToken = form
+        .0
+        .try_into()
+        .map_err(BlockchainAppError::ValidationError)?;
+
+    let mut transaction = pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+    insert_network(&mut transaction, &token.network)
+        .await
+        .context("Failed to insert network in the database.")?;
+    insert_address(&mut transaction, &token.network, &token.contract_address)
+        .await
+        .context(format!(
+            "Failed to insert contract address {} in the database.",
+            token.contract_address.as_ref()
+        ))?;
+    upsert_token(
+        &mut transaction,
+        &token.network,
+        &token.contract_address,
+        &token.token_name,
+        &token.metadata,
+    )
+    .await?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to store a token.")?;
+
+    let data = find_tokens(
+        &pool,
+        Some(&token.network),
+        Some(&token.contract_address),
+        None,
+        None,
+        None,
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(TokensResponse { data }))
+}
+
+#[allow(clippy::async_yields_async)]
+#[tracing::instrument(name = "Listing tokens.", skip(parameters, pool))]
+pub async fn get_tokens(
+    parameters: web::Query<TokenParameters>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, BlockchainAppError> {
+    let parameters = parameters.into_inner();
+    let network = parameters
+        .network
+        .map(Network::parse)
+        .transpose()
+        .map_err(BlockchainAppError::ValidationError)?;
+    let creator_address = match (parameters.creator_address, &network) {
+        (Some(creator_address), Some(network)) => Some(
+            Address::parse(creator_address, network)
+                .map_err(|e| BlockchainAppError::ValidationError(e.into()))?,
+        ),
+        (Some(_), None) => {
+            return Err(BlockchainAppError::ValidationError(
+                "network is required to look tokens up by creator_address.".to_string(),
+            ))
+        }
+        (None, _) => None,
+    };
+    let token_name = parameters.token_name.map(|n| n.trim().to_string());
+    let symbol = parameters.symbol.map(|s| s.trim().to_string());
+    if network.is_none() && token_name.is_none() && symbol.is_none() {
+        return Err(BlockchainAppError::ValidationError(
+            "At least one of network, token_name, symbol or creator_address is required."
+                .to_string(),
+        ));
+    }
+
+    let data = find_tokens(
+        &pool,
+        network.as_ref(),
+        None,
+        token_name.as_deref(),
+        symbol.as_deref(),
+        creator_address.as_ref(),
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(TokensResponse { data }))
+}
+
+#[allow(clippy::async_yields_async)]
+#[tracing::instrument(name = "Fetching a token.", skip(path, pool))]
+pub async fn get_token(
+    path: web::Path<(String, String)>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, BlockchainAppError> {
+    let (network, contract_address) = path.into_inner();
+    let network = Network::parse(network).map_err(BlockchainAppError::ValidationError)?;
+    let contract_address = Address::parse(contract_address, &network)
+        .map_err(|e| BlockchainAppError::ValidationError(e.into()))?;
+
+    let token = find_tokens(
+        &pool,
+        Some(&network),
+        Some(&contract_address),
+        None,
+        None,
+        None,
+    )
+    .await?
+    .pop()
+    .ok_or_else(|| {
+        BlockchainAppError::NotFoundError(format!(
+            "There is no token {} on {}.",
+            contract_address.as_ref(),
+            network.as_ref()
+        ))
+    })?;
+    Ok(HttpResponse::Ok().json(token))
+}
+
+/// Tokens matching every filter given. Names and symbols match regardless of case.
+#[tracing::instrument(
+    name = "Finding tokens",
+    skip(pool, network, contract_address, creator_address)
+)]
+pub async fn find_tokens(
+    pool: &PgPool,
+    network: Option<&Network>,
+    contract_address: Option<&Address>,
+    token_name: Option<&str>,
+    symbol: Option<&str>,
+    creator_address: Option<&Address>,
+) -> Result<Vec<TokenData>, BlockchainAppError> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT n.network_name, c.display_address AS contract_address, t.token_name, t.symbol,
+            t.decimals, t.total_supply, cr.display_address AS "creator_address?", t.launched_on,
+            t.updated_on
+        FROM tokens t
+        INNER JOIN networks n
+            ON n.network_id = t.network_id
+        INNER JOIN addresses c
+            ON c.network_id = t.network_id AND c.address = t.contract_address
+        LEFT JOIN addresses cr
+            ON cr.network_id = t.network_id AND cr.address = t.creator_address
+        WHERE ($1::text IS NULL OR n.network_name = $1)
+            AND ($2::text IS NULL OR t.contract_address = $2)
+            AND ($3::text IS NULL OR lower(t.token_name) = lower($3))
+            AND ($4::text IS NULL OR lower(t.symbol) = lower($4))
+            AND ($5::text IS NULL OR t.creator_address = $5)
+        ORDER BY n.network_name, lower(t.token_name), t.contract_address;
+        "#,
+        network.map(|n| n.as_ref()),
+        contract_address.map(|a| a.as_ref()),
+        token_name,
+        symbol,
+        creator_address.map(|a| a.as_ref()),
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch tokens.")?;
+
+    let mut tokens = vec![];
+    for r in rows {
+        let total_supply = match (&r.total_supply, r.decimals) {
+            (Some(raw), Some(decimals)) => Some(
+                TokenAmount::try_from(raw.clone())
+                    .and_then(|raw| TokenAmount::from_raw(raw, decimals))
+                    .map_err(anyhow::Error::msg)
+                    .context(format!(
+                        "The total supply of {} is not a valid amount.",
+                        r.contract_address
+                    ))?
+                    .into(),
+            ),
+            _ => None,
+        };
+        tokens.push(TokenData {
+            network: r.network_name,
+            contract_address: r.contract_address,
+            token_name: r.token_name,
+            symbol: r.symbol,
+            decimals: r.decimals,
+            total_supply,
+            raw_total_supply: r.total_supply,
+            creator_address: r.creator_address,
+            launched_on: r.launched_on,
+            updated_on: r.updated_on,
+        });
+    }
+    Ok(tokens)
+}
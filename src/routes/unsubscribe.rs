@@ -0,0 +1,101 @@
+use super::BlockchainAppError;
+use crate::unsubscribe::UnsubscribeLinks;
+use actix_web::http::header::ContentType;
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(serde::Deserialize)]
+pub struct UnsubscribeParameters {
+    token: String,
+}
+
+fn html_page(title: &str, body: &str) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type(ContentType::html())
+        .body(format!(
+            r#"<!DOCTYPE html>
+<html lang="en">
+<head><meta charset="utf-8"><title>{}</title></head>
+<body>
+    {}
+</body>
+</html>"#,
+            title, body
+        ))
+}
+
+fn verify(
+    parameters: &UnsubscribeParameters,
+    unsubscribe_links: &UnsubscribeLinks,
+) -> Result<Uuid, BlockchainAppError> {
+    unsubscribe_links
+        .verify(&parameters.token)
+        .map_err(|e| BlockchainAppError::ValidationError(e.to_string()))
+}
+
+/// The page an unsubscribe link opens. It asks before unsubscribing, so that link scanners and
+/// prefetching do not unsubscribe anyone.
+#[allow(clippy::async_yields_async)]
+#[tracing::instrument(
+    name = "Showing the unsubscribe page",
+    skip(parameters, unsubscribe_links)
+)]
+pub async fn unsubscribe_form(
+    parameters: web::Query<UnsubscribeParameters>,
+    unsubscribe_links: web::Data<UnsubscribeLinks>,
+) -> Result<HttpResponse, BlockchainAppError> {
+    verify(&parameters, &unsubscribe_links)?;
+    // The token is a UUID, a dot and URL-safe base64, so it is safe in HTML.
+    Ok(html_page(
+        "Unsubscribe",
+        &format!(
+            r#"<p>Stop getting the newsletter?</p>
+    <form action="/subscriptions/unsubscribe?token={}" method="post">
+        <button type="submit">Unsubscribe</button>
+    </form>"#,
+            parameters.token
+        ),
+    ))
+}
+
+/// Unsubscribes the token's subscriber. It takes the token from the query string, so it also
+/// serves one-click `List-Unsubscribe-Post` requests from mail clients.
+#[allow(clippy::async_yields_async)]
+#[tracing::instrument(
+    name = "Unsubscribing a subscriber",
+    skip(parameters, pool, unsubscribe_links),
+    fields(subscriber_id = tracing::field::Empty)
+)]
+pub async fn unsubscribe(
+    parameters: web::Query<UnsubscribeParameters>,
+    pool: web::Data<PgPool>,
+    unsubscribe_links: web::Data<UnsubscribeLinks>,
+) -> Result<HttpResponse, BlockchainAppError> {
+    let subscriber_id = verify(&parameters, &unsubscribe_links)?;
+    tracing::Span::current().record("subscriber_id", &tracing::field::display(&subscriber_id));
+    // The row is kept, so the address is never mailed again. Bounced and complained subscribers
+    // keep their status; they are not mailed either.
+    let subscriber = sqlx::query!(
+        r#"
+        UPDATE subscriptions
+        SET status = CASE WHEN status IN ('bounced', 'complained') THEN status ELSE 'unsubscribed' END
+        WHERE id = $1
+        RETURNING id;
+        "#,
+        subscriber_id,
+    )
+    .fetch_optional(pool.get_ref())
+    .await
+    .context("Failed to unsubscribe the subscriber.")?;
+    match subscriber {
+        Some(_) => Ok(html_page(
+            "Unsubscribed",
+            "<p>You have been unsubscribed and will not get the newsletter again.</p>",
+        )),
+        None => Err(BlockchainAppError::NotFoundError(
+            "The subscription no longer exists.".to_string(),
+        )),
+    }
+}
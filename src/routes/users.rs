@@ -0,0 +1,84 @@
+use super::BlockchainAppError;
+use crate::authentication::compute_password_hash;
+use crate::domain::{NewPassword, Username};
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use chrono::Utc;
+use sqlx::PgPool;
+use std::convert::{TryFrom, TryInto};
+use uuid::Uuid;
+
+#[derive(serde::Deserialize)]
+pub struct UserFormData {
+    username: String,
+    password: String,
+}
+
+pub struct NewUser {
+    username: Username,
+    password: NewPassword,
+}
+
+impl TryFrom<UserFormData> for NewUser {
+    type Error = String;
+
+    fn try_from(value: UserFormData) -> Result<Self, Self::Error> {
+        let username = Username::parse(value.username)?;
+        let password = NewPassword::parse(value.password)?;
+        Ok(Self { username, password })
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct UserData {
+    pub user_id: String,
+    pub username: String,
+}
+
+/// Adds an editor who can log in to publish newsletters.
+#[allow(clippy::async_yields_async)]
+#[tracing::instrument(
+    name = "Adding a user.",
+    skip(form, pool),
+    fields(username = %form.username)
+)]
+pub async fn add_user(
+    form: web::Json<UserFormData>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, BlockchainAppError> {
+    let new_user: NewUser = form
+        .0
+        .try_into()
+        .map_err(BlockchainAppError::ValidationError)?;
+    let password = new_user.password.as_ref().to_string();
+    let password_hash =
+        actix_web::rt::task::spawn_blocking(move || compute_password_hash(&password))
+            .await
+            .context("Failed to spawn the password hashing.")??;
+    let user_id = Uuid::new_v4();
+    let inserted = sqlx::query!(
+        r#"
+        INSERT INTO users (user_id, username, password_hash, created_on)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (username) DO NOTHING
+        RETURNING user_id;
+        "#,
+        user_id,
+        new_user.username.as_ref(),
+        password_hash,
+        Utc::now(),
+    )
+    .fetch_optional(pool.get_ref())
+    .await
+    .context("Failed to store the user in the database.")?;
+    if inserted.is_none() {
+        return Err(BlockchainAppError::ValidationError(format!(
+            "The username {} is taken.",
+            new_user.username.as_ref()
+        )));
+    }
+    Ok(HttpResponse::Ok().json(UserData {
+        user_id: user_id.to_string(),
+        username: new_user.username.as_ref().to_string(),
+    }))
+}
@@ -0,0 +1,309 @@
+use crate::domain::{AddressType, ConcentrationMetrics};
+use serde::{Deserialize, Deserializer};
+use sqlx::types::BigDecimal;
+
+#[derive(serde::Deserialize, Clone)]
+pub struct ScoringSettings {
+    pub rules: Vec<ScoringRule>,
+}
+
+/// A rule adds its `weight` to the score when it is triggered. Weights can be negative for
+/// rules that make a token look safer.
+#[derive(serde::Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum ScoringRule {
+    CreatorIsKnownScammer {
+        weight: f64,
+    },
+    TopHolderShareAbove {
+        threshold: f64,
+        #[serde(default, deserialize_with = "deserialize_address_types")]
+        excluded_address_types: Vec<AddressType>,
+        weight: f64,
+    },
+    NoLiquidityLockerInTopHolders {
+        top: i32,
+        weight: f64,
+    },
+    TaggedHoldersAtLeast {
+        #[serde(deserialize_with = "deserialize_address_types")]
+        address_types: Vec<AddressType>,
+        count: usize,
+        top: Option<i32>,
+        weight: f64,
+    },
+}
+
+fn deserialize_address_types<'de, D>(deserializer: D) -> Result<Vec<AddressType>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Vec::<String>::deserialize(deserializer)?
+        .into_iter()
+        .map(|s| AddressType::parse(s).map_err(serde::de::Error::custom))
+        .collect()
+}
+
+pub struct ScoredHolder {
+    pub holder_address: String,
+    pub place: i32,
+    pub amount: BigDecimal,
+    pub address_types: Vec<AddressType>,
+}
+
+/// What is known about a token when it gets scored. `holders` is its latest holder snapshot.
+pub struct TokenFacts {
+    pub known_scammer_creators: Vec<String>,
+    pub holders: Vec<ScoredHolder>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, PartialEq)]
+pub struct RuleResult {
+    pub rule: String,
+    pub triggered: bool,
+    pub weight: f64,
+    pub points: f64,
+    pub detail: String,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug)]
+pub struct TokenScore {
+    pub score: f64,
+    pub max_score: f64,
+    pub rules: Vec<RuleResult>,
+}
+
+impl ScoringRule {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ScoringRule::CreatorIsKnownScammer { .. } => "creator_is_known_scammer",
+            ScoringRule::TopHolderShareAbove { .. } => "top_holder_share_above",
+            ScoringRule::NoLiquidityLockerInTopHolders { .. } => {
+                "no_liquidity_locker_in_top_holders"
+            }
+            ScoringRule::TaggedHoldersAtLeast { .. } => "tagged_holders_at_least",
+        }
+    }
+
+    pub fn weight(&self) -> f64 {
+        match self {
+            ScoringRule::CreatorIsKnownScammer { weight }
+            | ScoringRule::TopHolderShareAbove { weight, .. }
+            | ScoringRule::NoLiquidityLockerInTopHolders { weight, .. }
+            | ScoringRule::TaggedHoldersAtLeast { weight, .. } => *weight,
+        }
+    }
+
+    pub fn evaluate(&self, facts: &TokenFacts) -> RuleResult {
+        let (triggered, detail) = match self {
+            ScoringRule::CreatorIsKnownScammer { .. } => {
+                if facts.known_scammer_creators.is_empty() {
+                    (false, "No creator of this token is a known scammer.".into())
+                } else {
+                    (
+                        true,
+                        format!(
+                            "Created by known scammer {}.",
+                            facts.known_scammer_creators.join(", ")
+                        ),
+                    )
+                }
+            }
+            ScoringRule::TopHolderShareAbove {
+                threshold,
+                excluded_address_types,
+                ..
+            } => {
+                let amounts = facts
+                    .holders
+                    .iter()
+                    .filter(|h| !tagged_with_any(h, excluded_address_types))
+                    .map(|h| h.amount.clone())
+                    .collect();
+                match ConcentrationMetrics::from_amounts(amounts) {
+                    Some(metrics) => (
+                        metrics.top_1_share > *threshold,
+                        format!(
+                            "The top holder has {:.4} of the listed amount.",
+                            metrics.top_1_share
+                        ),
+                    ),
+                    None => (false, "No holders to measure.".into()),
+                }
+            }
+            ScoringRule::NoLiquidityLockerInTopHolders { top, .. } => {
+                let has_locker = facts.holders.iter().any(|h| {
+                    h.place <= *top && h.address_types.contains(&AddressType::LiquidityLocker)
+                });
+                if has_locker {
+                    (false, format!("A liquidity locker is in the top {}.", top))
+                } else {
+                    (true, format!("No liquidity locker in the top {}.", top))
+                }
+            }
+            ScoringRule::TaggedHoldersAtLeast {
+                address_types,
+                count,
+                top,
+                ..
+            } => {
+                let tagged = facts
+                    .holders
+                    .iter()
+                    .filter(|h| top.map_or(true, |top| h.place <= top))
+                    .filter(|h| tagged_with_any(h, address_types))
+                    .count();
+                let types = address_types
+                    .iter()
+                    .map(|t| t.as_ref())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                (
+                    tagged >= *count,
+                    format!("{} holders are tagged as {}.", tagged, types),
+                )
+            }
+        };
+        RuleResult {
+            rule: self.name().to_string(),
+            triggered,
+            weight: self.weight(),
+            points: if triggered { self.weight() } else { 0.0 },
+            detail,
+        }
+    }
+}
+
+fn tagged_with_any(holder: &ScoredHolder, address_types: &[AddressType]) -> bool {
+    holder
+        .address_types
+        .iter()
+        .any(|t| address_types.contains(t))
+}
+
+impl ScoringSettings {
+    /// `max_score` is what a token would get if every rule with a positive weight triggered.
+    pub fn score(&self, facts: &TokenFacts) -> TokenScore {
+        let rules: Vec<RuleResult> = self.rules.iter().map(|r| r.evaluate(facts)).collect();
+        TokenScore {
+            score: rules.iter().map(|r| r.points).sum(),
+            max_score: self.rules.iter().map(|r| r.weight().max(0.0)).sum(),
+            rules,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ScoredHolder, ScoringRule, ScoringSettings, TokenFacts};
+    use crate::domain::AddressType;
+    use sqlx::types::BigDecimal;
+
+    fn holder(place: i32, amount: i64, address_types: Vec<AddressType>) -> ScoredHolder {
+        ScoredHolder {
+            holder_address: format!("holder{}", place),
+            place,
+            amount: BigDecimal::from(amount),
+            address_types,
+        }
+    }
+
+    fn settings() -> ScoringSettings {
+        ScoringSettings {
+            rules: vec![
+                ScoringRule::CreatorIsKnownScammer { weight: 50.0 },
+                ScoringRule::TopHolderShareAbove {
+                    threshold: 0.5,
+                    excluded_address_types: vec![AddressType::DeadAddress],
+                    weight: 20.0,
+                },
+                ScoringRule::NoLiquidityLockerInTopHolders {
+                    top: 2,
+                    weight: 15.0,
+                },
+                ScoringRule::TaggedHoldersAtLeast {
+                    address_types: vec![AddressType::Dumper, AddressType::PanicSeller],
+                    count: 2,
+                    top: None,
+                    weight: 10.0,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn a_token_that_triggers_every_rule_gets_the_max_score() {
+        let facts = TokenFacts {
+            known_scammer_creators: vec!["0xscammer".to_string()],
+            holders: vec![
+                holder(1, 90, vec![AddressType::Dumper]),
+                holder(2, 5, vec![AddressType::PanicSeller]),
+                holder(3, 5, vec![AddressType::LiquidityLocker]),
+            ],
+        };
+        let score = settings().score(&facts);
+        assert_eq!(score.score, 95.0);
+        assert_eq!(score.max_score, 95.0);
+        assert!(score.rules.iter().all(|r| r.triggered));
+    }
+
+    #[test]
+    fn a_healthy_token_scores_zero() {
+        let facts = TokenFacts {
+            known_scammer_creators: vec![],
+            holders: vec![
+                holder(1, 1000, vec![AddressType::DeadAddress]),
+                holder(2, 30, vec![AddressType::LiquidityLocker]),
+                holder(3, 30, vec![AddressType::Dumper]),
+                holder(4, 40, vec![]),
+            ],
+        };
+        let score = settings().score(&facts);
+        assert_eq!(score.score, 0.0);
+        assert!(score.rules.iter().all(|r| !r.triggered));
+    }
+
+    #[test]
+    fn rules_are_read_from_yaml() {
+        let yaml = r#"
+rules:
+  - rule: creator_is_known_scammer
+    weight: 50
+  - rule: tagged_holders_at_least
+    address_types: [dumper, panic_seller]
+    count: 3
+    top: 50
+    weight: 10
+"#;
+        let mut settings = config::Config::default();
+        settings
+            .merge(config::File::from_str(yaml, config::FileFormat::Yaml))
+            .unwrap();
+        let settings: ScoringSettings = settings.try_into().unwrap();
+        assert_eq!(
+            settings.rules[1],
+            ScoringRule::TaggedHoldersAtLeast {
+                address_types: vec![AddressType::Dumper, AddressType::PanicSeller],
+                count: 3,
+                top: Some(50),
+                weight: 10.0,
+            }
+        );
+    }
+
+    #[test]
+    fn unknown_address_types_are_rejected() {
+        let yaml = r#"
+rules:
+  - rule: tagged_holders_at_least
+    address_types: [moon_boy]
+    count: 3
+    weight: 10
+"#;
+        let mut settings = config::Config::default();
+        settings
+            .merge(config::File::from_str(yaml, config::FileFormat::Yaml))
+            .unwrap();
+        assert!(settings.try_into::<ScoringSettings>().is_err());
+    }
+}
@@ -1,13 +1,24 @@
+use crate::api_keys::{AdminApiKey, RequireScope};
 use crate::configuration::DatabaseSettings;
 use crate::configuration::Settings;
+use crate::domain::ApiScope;
 use crate::email_client::EmailClient;
+use crate::idempotency::Idempotency;
 use crate::routes::{
-    add_holder_descriptions, add_holders, confirm, get_holder, get_holder_descriptions,
-    get_legit_token_creators, get_scammers, health_check, publish_newsletter,
-    register_legit_token_creator, register_scam_token, register_scammer, subscribe,
+    add_holder_descriptions, add_holders, add_user, confirm, delete_holder_description,
+    get_address_report, get_api_keys, get_creator_tokens, get_holder,
+    get_holder_description_history, get_holder_descriptions, get_holder_metrics,
+    get_holder_snapshot, get_holder_snapshots, get_holders_diff, get_legit_token_creators,
+    get_newsletter_deliveries, get_scam_tokens, get_scammers, get_token, get_token_score,
+    get_tokens, health_check, import_holders, login, login_form, logout, mint_api_key,
+    publish_newsletter, receive_email_webhook, register_legit_token_creator, register_scam_token,
+    register_scammer, register_token, revoke_api_key, subscribe, unsubscribe, unsubscribe_form,
+    update_holder_description, MAX_IMPORT_BYTES,
 };
+use crate::scoring::ScoringSettings;
+use crate::unsubscribe::UnsubscribeLinks;
 use actix_web::dev::Server;
-use actix_web::{web, App, HttpServer};
+use actix_web::{guard, web, App, HttpServer};
 use sqlx::postgres::PgPoolOptions;
 use sqlx::PgPool;
 use std::net::TcpListener;
@@ -20,29 +31,26 @@ pub struct Application {
 
 impl Application {
     pub async fn build(configuration: Settings) -> Result<Self, std::io::Error> {
+        configuration
+            .application
+            .check_admin_api_key()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
         let connection_pool = get_connection_pool(&configuration.database);
-        let sender_email = configuration
-            .email_client
-            .sender()
-            .expect("Invalid sender email address.");
-        let timeout = configuration.email_client.timeout();
-        let email_client = EmailClient::new(
-            configuration.email_client.base_url,
-            sender_email,
-            configuration.email_client.authorization_token,
-            timeout,
-        );
+        let email_client = configuration.email_client.client();
         let address = format!(
             "{}:{}",
             configuration.application.host, configuration.application.port
         );
-        let listener = TcpListener::bind(&address)?;
+        let listener = TcpListener::bind(address)?;
         let port = listener.local_addr().unwrap().port();
         let server = run(
             listener,
             connection_pool,
             email_client,
             configuration.application.base_url,
+            configuration.application.admin_api_key,
+            configuration.application.hmac_secret,
+            configuration.scoring,
         )?;
         // We "save" the bound port in one of `Application`'s fields
         Ok(Self { port, server })
@@ -69,27 +77,88 @@ pub fn run(
     db_pool: PgPool,
     email_client: EmailClient,
     base_url: String,
+    admin_api_key: String,
+    hmac_secret: String,
+    scoring: ScoringSettings,
 ) -> Result<Server, std::io::Error> {
     let db_pool = web::Data::new(db_pool);
     let email_client = web::Data::new(email_client);
+    let unsubscribe_links = web::Data::new(UnsubscribeLinks::new(base_url.clone(), hmac_secret));
     let base_url = web::Data::new(ApplicationBaseUrl(base_url));
+    let admin_api_key = web::Data::new(AdminApiKey(admin_api_key));
+    let scoring = web::Data::new(scoring);
     let server = HttpServer::new(move || {
         App::new()
             .wrap(TracingLogger::default())
             .route("/health_check", web::get().to(health_check))
-            .route("/holders", web::post().to(add_holders))
+            .service(
+                web::resource("/admin/api_keys")
+                    .wrap(RequireScope(ApiScope::Admin))
+                    .route(web::get().to(get_api_keys))
+                    .route(web::post().to(mint_api_key)),
+            )
+            .service(
+                web::resource("/admin/users")
+                    .wrap(RequireScope(ApiScope::Admin))
+                    .route(web::post().to(add_user)),
+            )
+            .route("/login", web::get().to(login_form))
+            .route("/login", web::post().to(login))
+            .route("/logout", web::post().to(logout))
+            .service(
+                web::resource("/admin/api_keys/{api_key_id}")
+                    .wrap(RequireScope(ApiScope::Admin))
+                    .route(web::delete().to(revoke_api_key)),
+            )
+            .route(
+                "/addresses/{network}/{address}/report",
+                web::get().to(get_address_report),
+            )
+            .service(
+                web::resource("/holders")
+                    .wrap(Idempotency::default())
+                    .wrap(RequireScope(ApiScope::IngestHolders))
+                    .route(web::post().to(add_holders)),
+            )
+            .service(
+                web::resource("/holders/import")
+                    .app_data(web::PayloadConfig::new(MAX_IMPORT_BYTES))
+                    .wrap(Idempotency::with_body_limit(MAX_IMPORT_BYTES))
+                    .wrap(RequireScope(ApiScope::IngestHolders))
+                    .route(web::post().to(import_holders)),
+            )
             .route("/holders/list", web::get().to(get_holder))
+            .route("/holders/diff", web::get().to(get_holders_diff))
+            .route("/holders/metrics", web::get().to(get_holder_metrics))
+            .route("/holders/snapshots", web::get().to(get_holder_snapshots))
             .route(
-                "/holder_descriptions",
-                web::post().to(add_holder_descriptions),
+                "/holders/snapshots/{snapshot_id}",
+                web::get().to(get_holder_snapshot),
+            )
+            .service(
+                web::resource("/holder_descriptions")
+                    .wrap(RequireScope(ApiScope::WriteLabels))
+                    .route(web::post().to(add_holder_descriptions)),
             )
             .route(
                 "/holder_descriptions/list",
                 web::post().to(get_holder_descriptions),
             )
             .route(
-                "/legit/creators",
-                web::post().to(register_legit_token_creator),
+                "/holder_descriptions/history",
+                web::get().to(get_holder_description_history),
+            )
+            .service(
+                web::resource("/holder_descriptions/{network}/{holder_address}/{contract_address}")
+                    .wrap(RequireScope(ApiScope::WriteLabels))
+                    .route(web::put().to(update_holder_description))
+                    .route(web::delete().to(delete_holder_description)),
+            )
+            .service(
+                web::resource("/legit/creators")
+                    .wrap(Idempotency::default())
+                    .wrap(RequireScope(ApiScope::WriteLabels))
+                    .route(web::post().to(register_legit_token_creator)),
             )
             .route(
                 "/legit/creators/list",
@@ -97,13 +166,60 @@ pub fn run(
             )
             .route("/subscriptions", web::post().to(subscribe))
             .route("/subscriptions/confirm", web::get().to(confirm))
-            .route("/scam/creators", web::post().to(register_scammer))
+            .route(
+                "/subscriptions/unsubscribe",
+                web::get().to(unsubscribe_form),
+            )
+            .route("/subscriptions/unsubscribe", web::post().to(unsubscribe))
+            .service(
+                web::resource("/scam/creators")
+                    .wrap(Idempotency::default())
+                    .wrap(RequireScope(ApiScope::WriteLabels))
+                    .route(web::post().to(register_scammer)),
+            )
             .route("/scam/creators/list", web::get().to(get_scammers))
-            .route("/scam/tokens", web::post().to(register_scam_token))
+            .service(
+                web::resource("/scam/tokens")
+                    .wrap(RequireScope(ApiScope::WriteLabels))
+                    .route(web::post().to(register_scam_token)),
+            )
+            .route("/scam/tokens/list", web::get().to(get_scam_tokens))
+            .route(
+                "/creators/{network}/{address}/tokens",
+                web::get().to(get_creator_tokens),
+            )
             .route("/newsletters", web::post().to(publish_newsletter))
+            .route(
+                "/newsletters/{newsletter_issue_id}",
+                web::get().to(get_newsletter_deliveries),
+            )
+            .service(
+                web::resource("/webhooks/email")
+                    .wrap(RequireScope(ApiScope::EmailWebhooks))
+                    .route(web::post().to(receive_email_webhook)),
+            )
+            .route("/tokens", web::get().to(get_tokens))
+            .service(
+                // Reading tokens stays open, so only the POST route needs a key.
+                web::resource("/tokens")
+                    .guard(guard::Post())
+                    .wrap(RequireScope(ApiScope::IngestHolders))
+                    .route(web::post().to(register_token)),
+            )
+            .route(
+                "/tokens/{network}/{contract_address}",
+                web::get().to(get_token),
+            )
+            .route(
+                "/tokens/{network}/{contract_address}/score",
+                web::get().to(get_token_score),
+            )
             .app_data(db_pool.clone())
             .app_data(email_client.clone())
+            .app_data(unsubscribe_links.clone())
             .app_data(base_url.clone())
+            .app_data(admin_api_key.clone())
+            .app_data(scoring.clone())
     })
     .listen(listener)?
     .run();
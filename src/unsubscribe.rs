@@ -0,0 +1,96 @@
+//! Unsubscribe links. A token is the subscriber id and an HMAC-SHA256 of it, so the link works
+//! without being stored and cannot be forged for another subscriber.
+use hmac::{Hmac, Mac, NewMac};
+use sha2::Sha256;
+use uuid::Uuid;
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub struct UnsubscribeLinks {
+    base_url: String,
+    hmac_secret: String,
+}
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+#[error("The unsubscribe link is invalid.")]
+pub struct InvalidUnsubscribeToken;
+
+impl UnsubscribeLinks {
+    pub fn new(base_url: String, hmac_secret: String) -> Self {
+        Self {
+            base_url,
+            hmac_secret,
+        }
+    }
+
+    fn mac(&self, subscriber_id: Uuid) -> HmacSha256 {
+        let mut mac = HmacSha256::new_varkey(self.hmac_secret.as_bytes())
+            .expect("HMAC accepts keys of any length.");
+        mac.update(subscriber_id.as_bytes());
+        mac
+    }
+
+    pub fn token(&self, subscriber_id: Uuid) -> String {
+        let signature = self.mac(subscriber_id).finalize().into_bytes();
+        format!(
+            "{}.{}",
+            subscriber_id,
+            base64::encode_config(signature, base64::URL_SAFE_NO_PAD)
+        )
+    }
+
+    pub fn link(&self, subscriber_id: Uuid) -> String {
+        format!(
+            "{}/subscriptions/unsubscribe?token={}",
+            self.base_url,
+            self.token(subscriber_id)
+        )
+    }
+
+    /// The subscriber a token was signed for.
+    pub fn verify(&self, token: &str) -> Result<Uuid, InvalidUnsubscribeToken> {
+        let (subscriber_id, signature) = token.split_once('.').ok_or(InvalidUnsubscribeToken)?;
+        let subscriber_id = Uuid::parse_str(subscriber_id).map_err(|_| InvalidUnsubscribeToken)?;
+        let signature = base64::decode_config(signature, base64::URL_SAFE_NO_PAD)
+            .map_err(|_| InvalidUnsubscribeToken)?;
+        // `verify` compares in constant time.
+        self.mac(subscriber_id)
+            .verify(&signature)
+            .map_err(|_| InvalidUnsubscribeToken)?;
+        Ok(subscriber_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InvalidUnsubscribeToken, UnsubscribeLinks};
+    use uuid::Uuid;
+
+    fn links(secret: &str) -> UnsubscribeLinks {
+        UnsubscribeLinks::new("http://127.0.0.1".to_string(), secret.to_string())
+    }
+
+    #[test]
+    fn a_token_verifies_to_its_subscriber() {
+        let subscriber_id = Uuid::new_v4();
+        let links = links("secret");
+        assert_eq!(links.verify(&links.token(subscriber_id)), Ok(subscriber_id));
+        assert!(links
+            .link(subscriber_id)
+            .starts_with("http://127.0.0.1/subscriptions/unsubscribe?token="));
+    }
+
+    #[test]
+    fn forged_tokens_are_rejected() {
+        let links = links("secret");
+        let token = links.token(Uuid::new_v4());
+        let signature = token.split_once('.').unwrap().1;
+        let forged = format!("{}.{}", Uuid::new_v4(), signature);
+        assert_eq!(links.verify(&forged), Err(InvalidUnsubscribeToken));
+        assert_eq!(
+            self::links("another secret").verify(&token),
+            Err(InvalidUnsubscribeToken)
+        );
+        assert_eq!(links.verify("garbage"), Err(InvalidUnsubscribeToken));
+    }
+}
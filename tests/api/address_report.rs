@@ -0,0 +1,93 @@
+use crate::helpers::spawn_app;
+
+const NETWORK: &str = "eth";
+const ADDRESS: &str = "0x18ce832a86C207eeC301437f3dE05Aa11fd79fc1";
+const SCAMMED_TOKEN_ADDRESS: &str = "0xB91f05B798f8A010A1BDdbFf75dC3D106dC84B50";
+const HELD_TOKEN_ADDRESS: &str = "0x044727e50ff30db57fad06ff4f5846eab5ea52a2";
+
+#[actix_rt::test]
+async fn address_report_combines_everything_known_about_an_address() {
+    let app = spawn_app().await;
+    let scammer = format!(
+        "address={}&notes=rugged&network_of_scammed_token={}&scammed_contract_address={}",
+        ADDRESS, NETWORK, SCAMMED_TOKEN_ADDRESS
+    );
+    assert_eq!(200, app.post_scam_creators(scammer).await.status().as_u16());
+    let description = serde_json::json!({
+        "network_name": NETWORK,
+        "holder_descriptions": [
+            {"holder_address": ADDRESS, "contract_address": HELD_TOKEN_ADDRESS, "notes": "sells on every pump", "address_types": ["whale", "dumper"]}
+        ]
+    });
+    assert_eq!(
+        200,
+        app.post_holder_descriptions(&description)
+            .await
+            .status()
+            .as_u16()
+    );
+    for (place, amount) in &[(3, "1,000"), (1, "5,000")] {
+        let holders = serde_json::json!({
+            "network": NETWORK,
+            "token_name": "some coin",
+            "contract_address": HELD_TOKEN_ADDRESS,
+            "holders": [{"holder_address": ADDRESS, "place": place, "amount": amount}]
+        });
+        assert_eq!(200, app.post_holders(&holders).await.status().as_u16());
+    }
+
+    let response = app
+        .get_address_report(NETWORK, &ADDRESS.to_lowercase())
+        .await;
+    assert_eq!(200, response.status().as_u16());
+
+    let report: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(report["address"], ADDRESS.to_lowercase());
+    assert_eq!(report["verdict"], "LoseConditionMet");
+    assert_eq!(report["scam_creations"].as_array().unwrap().len(), 1);
+    assert_eq!(
+        report["scam_creations"][0]["contract_address"],
+        SCAMMED_TOKEN_ADDRESS
+    );
+    assert!(report["legit_creations"].as_array().unwrap().is_empty());
+    assert_eq!(
+        report["descriptions"][0]["address_types"],
+        serde_json::json!(["whale", "dumper"])
+    );
+    let holdings = report["holdings"].as_array().unwrap();
+    assert_eq!(holdings.len(), 1);
+    assert_eq!(holdings[0]["contract_address"], HELD_TOKEN_ADDRESS);
+    assert_eq!(holdings[0]["place"], 1);
+}
+
+#[actix_rt::test]
+async fn address_report_is_inconclusive_for_an_unknown_address() {
+    let app = spawn_app().await;
+
+    let response = app.get_address_report(NETWORK, ADDRESS).await;
+    assert_eq!(200, response.status().as_u16());
+
+    let report: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(report["verdict"], "InconclusiveCondition");
+    assert!(report["holdings"].as_array().unwrap().is_empty());
+}
+
+#[actix_rt::test]
+async fn address_report_returns_a_400_for_an_invalid_address() {
+    let app = spawn_app().await;
+
+    for (network, address) in &[
+        ("eth", "not an address"),
+        ("somesuperchain", ADDRESS),
+        ("sol", ADDRESS),
+    ] {
+        let response = app.get_address_report(network, address).await;
+        assert_eq!(
+            400,
+            response.status().as_u16(),
+            "The API did not fail with 400 Bad Request for {} on {}.",
+            address,
+            network
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use crate::helpers::{spawn_app, TestApp};
+use serde_json::Value;
+
+const SCAMMER: &str = "address=0x18ce832a86C207eeC301437f3dE05Aa11fd79fc1&notes=rugged&network_of_scammed_token=eth&scammed_contract_address=0xB91f05B798f8A010A1BDdbFf75dC3D106dC84B50";
+
+async fn mint(app: &TestApp, scopes: &[&str]) -> Value {
+    let response = app
+        .post_api_keys(
+            &serde_json::json!({"name": "crawler", "scopes": scopes}),
+            &app.admin_api_key,
+        )
+        .await;
+    assert_eq!(200, response.status().as_u16());
+    response.json().await.unwrap()
+}
+
+async fn post_scammer_with_key(app: &TestApp, api_key: Option<&str>) -> reqwest::Response {
+    let mut request = reqwest::Client::new()
+        .post(format!("{}/scam/creators", &app.address))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(SCAMMER);
+    if let Some(api_key) = api_key {
+        request = request.header("X-Api-Key", api_key);
+    }
+    request.send().await.expect("Failed to execute request.")
+}
+
+#[actix_rt::test]
+async fn write_routes_reject_requests_without_a_valid_key() {
+    let app = spawn_app().await;
+    assert_eq!(
+        401,
+        post_scammer_with_key(&app, None).await.status().as_u16()
+    );
+    assert_eq!(
+        401,
+        post_scammer_with_key(&app, Some("not-a-key"))
+            .await
+            .status()
+            .as_u16()
+    );
+    let newsletter = reqwest::Client::new()
+        .post(format!("{}/newsletters", &app.address))
+        .json(&serde_json::json!({"title": "t", "content": {"text": "t", "html": "t"}}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(401, newsletter.status().as_u16());
+
+    // Reads stay open.
+    let response = app
+        .get_scam_creators("token_creator_address=0x18ce832a86C207eeC301437f3dE05Aa11fd79fc1")
+        .await;
+    assert_eq!(200, response.status().as_u16());
+}
+
+#[actix_rt::test]
+async fn a_key_can_only_use_its_scopes() {
+    let app = spawn_app().await;
+    let crawler = mint(&app, &["ingest:holders"]).await;
+    let labeller = mint(&app, &["write:labels"]).await;
+
+    let response = post_scammer_with_key(&app, Some(crawler["api_key"].as_str().unwrap())).await;
+    assert_eq!(403, response.status().as_u16());
+    let response = post_scammer_with_key(&app, Some(labeller["api_key"].as_str().unwrap())).await;
+    assert_eq!(200, response.status().as_u16());
+}
+
+#[actix_rt::test]
+async fn a_revoked_key_stops_working() {
+    let app = spawn_app().await;
+    let labeller = mint(&app, &["write:labels"]).await;
+    let api_key_id = labeller["api_key_id"].as_str().unwrap();
+
+    assert_eq!(200, app.revoke_api_key(api_key_id).await.status().as_u16());
+    let response = post_scammer_with_key(&app, Some(labeller["api_key"].as_str().unwrap())).await;
+    assert_eq!(401, response.status().as_u16());
+    assert_eq!(404, app.revoke_api_key(api_key_id).await.status().as_u16());
+
+    let keys: Value = app.get_api_keys().await.json().await.unwrap();
+    let revoked = keys["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|k| k["api_key_id"] == api_key_id)
+        .unwrap();
+    assert!(!revoked["revoked_on"].is_null());
+    assert!(revoked.get("api_key").is_none());
+}
+
+#[actix_rt::test]
+async fn only_the_admin_key_can_mint_keys() {
+    let app = spawn_app().await;
+    let body = serde_json::json!({"name": "crawler", "scopes": ["ingest:holders"]});
+    assert_eq!(
+        403,
+        app.post_api_keys(&body, &app.api_key)
+            .await
+            .status()
+            .as_u16()
+    );
+    assert_eq!(
+        401,
+        app.post_api_keys(&body, "guess").await.status().as_u16()
+    );
+
+    for (scopes, description) in [
+        (serde_json::json!([]), "no scopes"),
+        (serde_json::json!(["admin"]), "the admin scope"),
+        (serde_json::json!(["write"]), "an unknown scope"),
+        (
+            serde_json::json!(["read"]),
+            "the read scope, since reads are public",
+        ),
+    ] {
+        let response = app
+            .post_api_keys(
+                &serde_json::json!({"name": "crawler", "scopes": scopes}),
+                &app.admin_api_key,
+            )
+            .await;
+        assert_eq!(
+            400,
+            response.status().as_u16(),
+            "The API did not reject a key with {}.",
+            description
+        );
+    }
+}
@@ -0,0 +1,120 @@
+use crate::helpers::spawn_app;
+use serde_json::Value;
+
+const CREATOR: &str = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+const RUG_PULL: &str = "0x8076c74c5e3f5852037f31ff0093eeb8c8add8d3";
+const HONEYPOT: &str = "0xb91f05b798f8a010a1bddbff75dc3d106dc84b50";
+const UNREPORTED: &str = "0xfb6916095ca1df60bb79ce92ce3ea74c37c5d359";
+const LEGIT: &str = "0xdbf03b407c01e7cd3cbea99509d93f8dddc8c6fb";
+
+#[actix_rt::test]
+async fn every_token_of_a_creator_is_listed_with_its_status() {
+    let app = spawn_app().await;
+    let scammer = format!(
+        "address={}&notes=rugged&network_of_scammed_token=bsc&scammed_contract_address={}",
+        CREATOR, RUG_PULL
+    );
+    assert_eq!(200, app.post_scam_creators(scammer).await.status().as_u16());
+    let scam_token = format!(
+        "address={}&notes=cannot+sell&scam_creator_network=bsc&scam_creator_address={}&scam_type=honeypot",
+        HONEYPOT, CREATOR
+    );
+    assert_eq!(
+        200,
+        app.post_scam_tokens(scam_token).await.status().as_u16()
+    );
+    let legit = format!(
+        "address={}&notes=audited&network_of_legit_token=bsc&legit_contract_address={}",
+        CREATOR, LEGIT
+    );
+    assert_eq!(
+        200,
+        app.post_legit_token_creators(legit).await.status().as_u16()
+    );
+    let token = serde_json::json!({
+        "network": "bsc",
+        "contract_address": UNREPORTED,
+        "token_name": "Santa Coin",
+        "symbol": "SANTA",
+        "creator_address": CREATOR,
+        "launched_on": "2021-12-01T12:00:00Z"
+    });
+    assert_eq!(200, app.post_token(&token).await.status().as_u16());
+
+    let response = app.get_creator_tokens("bsc", CREATOR).await;
+    assert_eq!(200, response.status().as_u16());
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["creator_address"], CREATOR);
+    let tokens = body["tokens"].as_array().unwrap();
+    assert_eq!(tokens.len(), 4);
+    // Tokens with a known launch come first.
+    assert_eq!(tokens[0]["contract_address"], UNREPORTED);
+    assert_eq!(tokens[0]["token_name"], "Santa Coin");
+    assert_eq!(tokens[0]["status"], "unreported");
+    let status_of = |contract_address: &str| {
+        tokens
+            .iter()
+            .find(|t| t["contract_address"] == contract_address)
+            .map(|t| (t["status"].clone(), t["scam_types"].clone()))
+            .unwrap()
+    };
+    assert_eq!(
+        status_of(RUG_PULL),
+        (serde_json::json!("scam"), serde_json::json!([]))
+    );
+    assert_eq!(
+        status_of(HONEYPOT),
+        (serde_json::json!("scam"), serde_json::json!(["honeypot"]))
+    );
+    assert_eq!(
+        status_of(LEGIT),
+        (serde_json::json!("legit"), serde_json::json!([]))
+    );
+}
+
+#[actix_rt::test]
+async fn a_creator_without_tokens_gets_an_empty_list() {
+    let app = spawn_app().await;
+    let response = app.get_creator_tokens("eth", CREATOR).await;
+    assert_eq!(200, response.status().as_u16());
+    let body: Value = response.json().await.unwrap();
+    assert!(body["tokens"].as_array().unwrap().is_empty());
+}
+
+#[actix_rt::test]
+async fn an_invalid_creator_address_is_rejected() {
+    let app = spawn_app().await;
+    let response = app.get_creator_tokens("bsc", "notanaddress").await;
+    assert_eq!(400, response.status().as_u16());
+}
+
+#[actix_rt::test]
+async fn a_report_naming_another_creator_keeps_the_first_one() {
+    let app = spawn_app().await;
+    let other_creator = "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359";
+    let first = format!(
+        "address={}&notes=rugged&network_of_scammed_token=bsc&scammed_contract_address={}",
+        CREATOR, RUG_PULL
+    );
+    assert_eq!(200, app.post_scam_creators(first).await.status().as_u16());
+    let second = format!(
+        "address={}&notes=rugged&network_of_scammed_token=bsc&scammed_contract_address={}",
+        other_creator, RUG_PULL
+    );
+    assert_eq!(400, app.post_scam_creators(second).await.status().as_u16());
+
+    let body: Value = app
+        .get_creator_tokens("bsc", CREATOR)
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["tokens"].as_array().unwrap().len(), 1);
+    let body: Value = app
+        .get_creator_tokens("bsc", other_creator)
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert!(body["tokens"].as_array().unwrap().is_empty());
+}
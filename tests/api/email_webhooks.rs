@@ -0,0 +1,187 @@
+use crate::helpers::{create_confirmed_subscriber, spawn_app, TestApp};
+use serde_json::Value;
+use wiremock::matchers::{any, path};
+use wiremock::{Mock, ResponseTemplate};
+
+const SUBSCRIBER: &str = "ursula_le_guin@gmail.com";
+
+fn bounce(bounce_type: &str, message_id: &str) -> Value {
+    serde_json::json!({
+        "RecordType": "Bounce",
+        "ID": 4323372036854775807u64,
+        "Type": bounce_type,
+        "TypeCode": 1,
+        "MessageID": message_id,
+        "Description": "The server was unable to deliver your message (ex: unknown user, mailbox not found).",
+        "Email": SUBSCRIBER,
+        "BouncedAt": "2022-02-21T16:33:54.9070259Z",
+        "Inactive": bounce_type == "HardBounce"
+    })
+}
+
+fn spam_complaint(message_id: &str) -> Value {
+    serde_json::json!({
+        "RecordType": "SpamComplaint",
+        "ID": 42,
+        "Type": "SpamComplaint",
+        "TypeCode": 512,
+        "MessageID": message_id,
+        "Email": SUBSCRIBER,
+        "BouncedAt": "2022-02-21T16:33:54.9070259Z",
+        "Inactive": true
+    })
+}
+
+async fn subscriber_status(app: &TestApp) -> String {
+    sqlx::query!(
+        "SELECT status FROM subscriptions WHERE email = $1",
+        SUBSCRIBER
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap()
+    .status
+}
+
+async fn publish(app: &TestApp) -> Value {
+    app.post_newsletters(serde_json::json!({
+        "title": "Newsletter title",
+        "content": {"text": "plain text", "html": "<p>HTML</p>"}
+    }))
+    .await
+    .json()
+    .await
+    .unwrap()
+}
+
+#[actix_rt::test]
+async fn a_hard_bounce_stops_newsletters_to_the_subscriber() {
+    // Arrange
+    let app = spawn_app().await;
+    create_confirmed_subscriber(&app).await;
+    // Act
+    let response = app
+        .post_email_webhook(&bounce("HardBounce", "unknown-message"))
+        .await;
+    // Assert
+    assert_eq!(200, response.status().as_u16());
+    let outcome: Value = response.json().await.unwrap();
+    assert_eq!("hard_bounce", outcome["event"]);
+    assert_eq!("bounced", outcome["subscriber_status"]);
+    assert_eq!("bounced", subscriber_status(&app).await);
+    assert_eq!(0, publish(&app).await["queued"]);
+}
+
+#[actix_rt::test]
+async fn a_soft_bounce_keeps_the_subscription() {
+    let app = spawn_app().await;
+    create_confirmed_subscriber(&app).await;
+    let outcome: Value = app
+        .post_email_webhook(&bounce("SoftBounce", "unknown-message"))
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!("soft_bounce", outcome["event"]);
+    assert!(outcome["subscriber_status"].is_null());
+    assert_eq!("confirmed", subscriber_status(&app).await);
+}
+
+#[actix_rt::test]
+async fn a_spam_complaint_is_not_replaced_by_a_later_bounce() {
+    let app = spawn_app().await;
+    create_confirmed_subscriber(&app).await;
+    app.post_email_webhook(&spam_complaint("unknown-message"))
+        .await;
+    assert_eq!("complained", subscriber_status(&app).await);
+    app.post_email_webhook(&bounce("HardBounce", "unknown-message"))
+        .await;
+    assert_eq!("complained", subscriber_status(&app).await);
+}
+
+#[actix_rt::test]
+async fn events_are_recorded_on_the_send_they_are_about() {
+    // Arrange
+    let app = spawn_app().await;
+    create_confirmed_subscriber(&app).await;
+    Mock::given(path("/email"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "To": SUBSCRIBER,
+            "MessageID": "0a129aee-e1cd-480d-b08d-4f48548ff48d",
+            "ErrorCode": 0,
+            "Message": "OK"
+        })))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+    let issue = publish(&app).await;
+    let newsletter_issue_id = issue["newsletter_issue_id"].as_str().unwrap();
+    app.dispatch_all_pending_emails().await;
+    // Act
+    app.post_email_webhook(&spam_complaint("0a129aee-e1cd-480d-b08d-4f48548ff48d"))
+        .await;
+    // Assert
+    let deliveries: Value = app
+        .get_newsletter_deliveries(newsletter_issue_id)
+        .await
+        .json()
+        .await
+        .unwrap();
+    let delivery = &deliveries["deliveries"][0];
+    assert_eq!("delivered", delivery["status"]);
+    assert_eq!(
+        "0a129aee-e1cd-480d-b08d-4f48548ff48d",
+        delivery["provider_message_id"]
+    );
+    assert_eq!("spam_complaint", delivery["event"]);
+}
+
+#[actix_rt::test]
+async fn queued_deliveries_are_skipped_after_a_bounce() {
+    // Arrange
+    let app = spawn_app().await;
+    create_confirmed_subscriber(&app).await;
+    Mock::given(any())
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&app.email_server)
+        .await;
+    let issue = publish(&app).await;
+    assert_eq!(1, issue["queued"]);
+    // Act
+    app.post_email_webhook(&bounce("HardBounce", "unknown-message"))
+        .await;
+    app.dispatch_all_pending_emails().await;
+    // Assert
+    let deliveries: Value = app
+        .get_newsletter_deliveries(issue["newsletter_issue_id"].as_str().unwrap())
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(1, deliveries["skipped"]);
+    assert_eq!("skipped", deliveries["deliveries"][0]["status"]);
+}
+
+#[actix_rt::test]
+async fn webhooks_need_the_webhook_scope_and_ignore_other_records() {
+    let app = spawn_app().await;
+    let response = reqwest::Client::new()
+        .post(format!("{}/webhooks/email", &app.address))
+        .json(&bounce("HardBounce", "unknown-message"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(401, response.status().as_u16());
+
+    let response = app
+        .post_email_webhook(&serde_json::json!({
+            "RecordType": "Delivery",
+            "MessageID": "unknown-message",
+            "Recipient": SUBSCRIBER
+        }))
+        .await;
+    assert_eq!(200, response.status().as_u16());
+    let outcome: Value = response.json().await.unwrap();
+    assert!(outcome["event"].is_null());
+}
@@ -1,14 +1,15 @@
 use once_cell::sync::Lazy;
 use serde_json::Value;
 use sqlx::{Connection, Executor, PgConnection, PgPool};
-use std::net::TcpListener;
 use uuid::Uuid;
 use whale_watcher_server::configuration::{get_configuration, DatabaseSettings};
 use whale_watcher_server::email_client::EmailClient;
+use whale_watcher_server::issue_delivery_worker::{try_execute_task, ExecutionOutcome};
 use whale_watcher_server::startup::{get_connection_pool, Application};
 use whale_watcher_server::telemetry::{get_subscriber, init_subscriber};
-use wiremock::matchers::query_param;
-use wiremock::MockServer;
+use whale_watcher_server::unsubscribe::UnsubscribeLinks;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
 
 static TRACING: Lazy<()> = Lazy::new(|| {
     let default_filter_level = "info".to_string();
@@ -27,11 +28,24 @@ pub struct ConfirmationLinks {
     pub plain_text: reqwest::Url,
 }
 
+pub struct TestUser {
+    pub username: String,
+    pub password: String,
+}
+
 pub struct TestApp {
     pub address: String,
     pub db_pool: PgPool,
     pub email_server: MockServer,
     pub port: u16,
+    /// A key with every scope a key can be granted.
+    pub api_key: String,
+    pub api_key_id: String,
+    pub admin_api_key: String,
+    /// An editor who may publish newsletters.
+    pub test_user: TestUser,
+    pub email_client: EmailClient,
+    pub unsubscribe_links: UnsubscribeLinks,
 }
 
 impl TestApp {
@@ -49,6 +63,119 @@ impl TestApp {
             .post(&format!("{}/holders", &self.address))
             .header("Content-Type", "application/json")
             .json(body)
+            .header("X-Api-Key", &self.api_key)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+    pub async fn post_token(&self, body: &Value) -> reqwest::Response {
+        reqwest::Client::new()
+            .post(&format!("{}/tokens", &self.address))
+            .json(body)
+            .header("X-Api-Key", &self.api_key)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+    pub async fn get_tokens(&self, query_params: &str) -> reqwest::Response {
+        reqwest::Client::new()
+            .get(&format!("{}/tokens?{}", &self.address, query_params))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+    pub async fn get_token(&self, network: &str, contract_address: &str) -> reqwest::Response {
+        reqwest::Client::new()
+            .get(&format!(
+                "{}/tokens/{}/{}",
+                &self.address, network, contract_address
+            ))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+    pub async fn get_creator_tokens(&self, network: &str, address: &str) -> reqwest::Response {
+        reqwest::Client::new()
+            .get(&format!(
+                "{}/creators/{}/{}/tokens",
+                &self.address, network, address
+            ))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+    pub async fn post_holders_with_idempotency_key(
+        &self,
+        body: &Value,
+        idempotency_key: &str,
+    ) -> reqwest::Response {
+        reqwest::Client::new()
+            .post(&format!("{}/holders", &self.address))
+            .header("Idempotency-Key", idempotency_key)
+            .json(body)
+            .header("X-Api-Key", &self.api_key)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+    pub async fn import_holders(
+        &self,
+        query_params: &str,
+        content_type: &str,
+        body: String,
+    ) -> reqwest::Response {
+        reqwest::Client::new()
+            .post(&format!(
+                "{}/holders/import?{}",
+                &self.address, query_params
+            ))
+            .header("Content-Type", content_type)
+            .body(body)
+            .header("X-Api-Key", &self.api_key)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+    pub async fn get_holders(&self, query_params: &str) -> reqwest::Response {
+        reqwest::Client::new()
+            .get(&format!("{}/holders/list?{}", &self.address, query_params))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+    pub async fn get_holder_snapshots(&self, query_params: &str) -> reqwest::Response {
+        reqwest::Client::new()
+            .get(&format!(
+                "{}/holders/snapshots?{}",
+                &self.address, query_params
+            ))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+    pub async fn get_holder_snapshot(&self, snapshot_id: i64) -> reqwest::Response {
+        reqwest::Client::new()
+            .get(&format!(
+                "{}/holders/snapshots/{}",
+                &self.address, snapshot_id
+            ))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+    pub async fn get_holders_diff(&self, query_params: &str) -> reqwest::Response {
+        reqwest::Client::new()
+            .get(&format!("{}/holders/diff?{}", &self.address, query_params))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+    pub async fn get_holder_metrics(&self, query_params: &str) -> reqwest::Response {
+        reqwest::Client::new()
+            .get(&format!(
+                "{}/holders/metrics?{}",
+                &self.address, query_params
+            ))
             .send()
             .await
             .expect("Failed to execute request.")
@@ -58,6 +185,7 @@ impl TestApp {
             .post(&format!("{}/holder_descriptions", &self.address))
             .header("Content-Type", "application/application/json")
             .json(body)
+            .header("X-Api-Key", &self.api_key)
             .send()
             .await
             .expect("Failed to execute request.")
@@ -71,11 +199,56 @@ impl TestApp {
             .await
             .expect("Failed to execute request.")
     }
+    pub async fn put_holder_description(
+        &self,
+        network: &str,
+        holder_address: &str,
+        contract_address: &str,
+        body: &Value,
+    ) -> reqwest::Response {
+        reqwest::Client::new()
+            .put(&format!(
+                "{}/holder_descriptions/{}/{}/{}",
+                &self.address, network, holder_address, contract_address
+            ))
+            .json(body)
+            .header("X-Api-Key", &self.api_key)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+    pub async fn delete_holder_description(
+        &self,
+        network: &str,
+        holder_address: &str,
+        contract_address: &str,
+    ) -> reqwest::Response {
+        reqwest::Client::new()
+            .delete(&format!(
+                "{}/holder_descriptions/{}/{}/{}",
+                &self.address, network, holder_address, contract_address
+            ))
+            .header("X-Api-Key", &self.api_key)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+    pub async fn get_holder_description_history(&self, query: &str) -> reqwest::Response {
+        reqwest::Client::new()
+            .get(&format!(
+                "{}/holder_descriptions/history?{}",
+                &self.address, query
+            ))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
     pub async fn post_scam_creators(&self, body: String) -> reqwest::Response {
         reqwest::Client::new()
             .post(&format!("{}/scam/creators", &self.address))
             .header("Content-Type", "application/x-www-form-urlencoded")
             .body(body)
+            .header("X-Api-Key", &self.api_key)
             .send()
             .await
             .expect("Failed to execute request.")
@@ -90,11 +263,32 @@ impl TestApp {
             .await
             .expect("Failed to execute request.")
     }
+    pub async fn post_scam_tokens(&self, body: String) -> reqwest::Response {
+        reqwest::Client::new()
+            .post(&format!("{}/scam/tokens", &self.address))
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .body(body)
+            .header("X-Api-Key", &self.api_key)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+    pub async fn get_scam_tokens(&self, query_params: &str) -> reqwest::Response {
+        reqwest::Client::new()
+            .get(&format!(
+                "{}/scam/tokens/list?{}",
+                &self.address, query_params
+            ))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
     pub async fn post_legit_token_creators(&self, body: String) -> reqwest::Response {
         reqwest::Client::new()
             .post(&format!("{}/legit/creators", &self.address))
             .header("Content-Type", "application/x-www-form-urlencoded")
             .body(body)
+            .header("X-Api-Key", &self.api_key)
             .send()
             .await
             .expect("Failed to execute request.")
@@ -109,6 +303,30 @@ impl TestApp {
             .await
             .expect("Failed to execute request.")
     }
+    pub async fn get_address_report(&self, network: &str, address: &str) -> reqwest::Response {
+        reqwest::Client::new()
+            .get(&format!(
+                "{}/addresses/{}/{}/report",
+                &self.address, network, address
+            ))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+    pub async fn get_token_score(
+        &self,
+        network: &str,
+        contract_address: &str,
+    ) -> reqwest::Response {
+        reqwest::Client::new()
+            .get(&format!(
+                "{}/tokens/{}/{}/score",
+                &self.address, network, contract_address
+            ))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
     pub fn get_confirmation_links(&self, email_request: &wiremock::Request) -> ConfirmationLinks {
         let body: serde_json::Value = serde_json::from_slice(&email_request.body).unwrap();
         // Helper function that extracts the link from one of the request fields.
@@ -126,18 +344,140 @@ impl TestApp {
         };
 
         // use the helper function to get email body of both html and text types:
-        let html = get_link(&body["HtmlBody"].as_str().unwrap());
-        let plain_text = get_link(&body["TextBody"].as_str().unwrap());
+        let html = get_link(body["HtmlBody"].as_str().unwrap());
+        let plain_text = get_link(body["TextBody"].as_str().unwrap());
         ConfirmationLinks { html, plain_text }
     }
+    pub async fn post_api_keys(&self, body: &Value, admin_api_key: &str) -> reqwest::Response {
+        reqwest::Client::new()
+            .post(&format!("{}/admin/api_keys", &self.address))
+            .header("X-Api-Key", admin_api_key)
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+    pub async fn get_api_keys(&self) -> reqwest::Response {
+        reqwest::Client::new()
+            .get(&format!("{}/admin/api_keys", &self.address))
+            .header("X-Api-Key", &self.admin_api_key)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+    pub async fn revoke_api_key(&self, api_key_id: &str) -> reqwest::Response {
+        reqwest::Client::new()
+            .delete(&format!("{}/admin/api_keys/{}", &self.address, api_key_id))
+            .header("X-Api-Key", &self.admin_api_key)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
     pub async fn post_newsletters(&self, body: serde_json::Value) -> reqwest::Response {
         reqwest::Client::new()
             .post(&format!("{}/newsletters", &self.address))
+            .basic_auth(&self.test_user.username, Some(&self.test_user.password))
             .json(&body)
             .send()
             .await
             .expect("Failed to execute request.")
     }
+    pub async fn get_newsletter_deliveries(&self, newsletter_issue_id: &str) -> reqwest::Response {
+        reqwest::Client::new()
+            .get(&format!(
+                "{}/newsletters/{}",
+                &self.address, newsletter_issue_id
+            ))
+            .basic_auth(&self.test_user.username, Some(&self.test_user.password))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+    pub async fn post_email_webhook(&self, body: &Value) -> reqwest::Response {
+        reqwest::Client::new()
+            .post(&format!("{}/webhooks/email", &self.address))
+            .header("X-Api-Key", &self.api_key)
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+    pub async fn post_users(&self, body: &Value) -> reqwest::Response {
+        reqwest::Client::new()
+            .post(&format!("{}/admin/users", &self.address))
+            .header("X-Api-Key", &self.admin_api_key)
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+    pub async fn post_login(&self, username: &str, password: &str) -> reqwest::Response {
+        self.client_without_redirects()
+            .post(&format!("{}/login", &self.address))
+            .form(&[("username", username), ("password", password)])
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+    /// Does the issue delivery worker's job: sends every delivery that is due.
+    pub async fn dispatch_all_pending_emails(&self) {
+        loop {
+            if let ExecutionOutcome::EmptyQueue =
+                try_execute_task(&self.db_pool, &self.email_client, &self.unsubscribe_links)
+                    .await
+                    .unwrap()
+            {
+                break;
+            }
+        }
+    }
+    /// Keeps redirects so tests can check where they point and the cookies they set.
+    pub fn client_without_redirects(&self) -> reqwest::Client {
+        reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .unwrap()
+    }
+}
+
+/// Use the public API of the application under test to create /// an unconfirmed subscriber.
+pub async fn create_unconfirmed_subscriber(app: &TestApp) -> ConfirmationLinks {
+    let body = "name=le%20guin&email=ursula_le_guin%40gmail.com";
+
+    let _mock_guard = Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .named("Create unconfirmed subscriber")
+        .expect(1)
+        .mount_as_scoped(&app.email_server)
+        .await;
+
+    app.post_subscriptions(body.into())
+        .await
+        .error_for_status()
+        .unwrap();
+
+    // We now inspect the requests received by the mock Postmark server // to retrieve the confirmation link and return it
+    let email_request = &app
+        .email_server
+        .received_requests()
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+
+    app.get_confirmation_links(&email_request)
+}
+
+pub async fn create_confirmed_subscriber(app: &TestApp) {
+    // We can then reuse the same helper and just add
+    // an extra step to actually call the confirmation link!
+    let confirmation_link = create_unconfirmed_subscriber(app).await;
+    reqwest::get(confirmation_link.html)
+        .await
+        .unwrap()
+        .error_for_status()
+        .unwrap();
 }
 
 // our integration test
@@ -161,12 +501,52 @@ pub async fn spawn_app() -> TestApp {
     let application_port = application.port();
     let address = format!("http://127.0.0.1:{}", application.port());
     let _ = tokio::spawn(application.run_until_stopped());
-    TestApp {
+    let mut test_app = TestApp {
         address,
         db_pool: get_connection_pool(&configuration.database),
         email_server,
         port: application_port,
-    }
+        api_key: String::new(),
+        api_key_id: String::new(),
+        admin_api_key: configuration.application.admin_api_key,
+        email_client: configuration.email_client.client(),
+        unsubscribe_links: UnsubscribeLinks::new(
+            configuration.application.base_url.clone(),
+            configuration.application.hmac_secret.clone(),
+        ),
+        test_user: TestUser {
+            username: "editor".to_string(),
+            password: Uuid::new_v4().to_string(),
+        },
+    };
+    test_app
+        .post_users(&serde_json::json!({
+            "username": test_app.test_user.username,
+            "password": test_app.test_user.password
+        }))
+        .await
+        .error_for_status()
+        .expect("Failed to add the test user.");
+    let minted: Value = test_app
+        .post_api_keys(
+            &serde_json::json!({
+                "name": "test suite",
+                "scopes": [
+                    "ingest:holders",
+                    "write:labels",
+                    "publish:newsletter",
+                    "webhooks:email"
+                ]
+            }),
+            &test_app.admin_api_key,
+        )
+        .await
+        .json()
+        .await
+        .expect("Failed to mint an API key.");
+    test_app.api_key = minted["api_key"].as_str().unwrap().to_string();
+    test_app.api_key_id = minted["api_key_id"].as_str().unwrap().to_string();
+    test_app
 }
 
 async fn configure_database(config: &DatabaseSettings) -> PgPool {
@@ -0,0 +1,212 @@
+use crate::helpers::spawn_app;
+
+const HOLDER_ADDRESS: &str = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+const CONTRACT_ADDRESS: &str = "0x8076c74c5e3f5852037f31ff0093eeb8c8add8d3";
+
+#[derive(serde::Deserialize, Debug, PartialEq)]
+pub struct DescriptionState {
+    notes: Option<String>,
+    address_types: Vec<String>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct HolderDescriptionChange {
+    contract_address: String,
+    previous: Option<DescriptionState>,
+    new: Option<DescriptionState>,
+    changed_by: Option<String>,
+    changed_by_api_key_id: Option<String>,
+    on_behalf_of: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct HolderDescriptionHistoryResponse {
+    holder_address: String,
+    data: Vec<HolderDescriptionChange>,
+}
+
+async fn fetch_history(app: &crate::helpers::TestApp) -> HolderDescriptionHistoryResponse {
+    let response = app
+        .get_holder_description_history(&format!("network=bsc&holder_address={}", HOLDER_ADDRESS))
+        .await;
+    assert_eq!(200, response.status().as_u16());
+    response
+        .json::<HolderDescriptionHistoryResponse>()
+        .await
+        .expect("Failed to parse the history.")
+}
+
+fn state(notes: &str, address_types: &[&str]) -> Option<DescriptionState> {
+    Some(DescriptionState {
+        notes: Some(notes.to_string()),
+        address_types: address_types.iter().map(|t| t.to_string()).collect(),
+    })
+}
+
+#[actix_rt::test]
+async fn every_change_to_a_description_is_recorded_in_order() {
+    let app = spawn_app().await;
+    let body = serde_json::json!({
+        "network_name": "bsc",
+        "holder_descriptions": [
+            {"holder_address": HOLDER_ADDRESS, "contract_address": CONTRACT_ADDRESS, "notes": "sold early", "address_types": ["whale"]}
+        ]
+    });
+    assert_eq!(
+        200,
+        app.post_holder_descriptions(&body).await.status().as_u16()
+    );
+    let update = serde_json::json!({"address_types": ["suspicious_holder"], "notes": "linked to the deployer"});
+    let response = reqwest::Client::new()
+        .put(&format!(
+            "{}/holder_descriptions/bsc/{}/{}",
+            &app.address, HOLDER_ADDRESS, CONTRACT_ADDRESS
+        ))
+        .header("X-Changed-By", "moderator_jo")
+        .header("X-Api-Key", &app.api_key)
+        .json(&update)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(200, response.status().as_u16());
+    assert_eq!(
+        200,
+        app.delete_holder_description("bsc", HOLDER_ADDRESS, CONTRACT_ADDRESS)
+            .await
+            .status()
+            .as_u16()
+    );
+
+    let history = fetch_history(&app).await;
+    assert_eq!(history.holder_address, HOLDER_ADDRESS);
+    assert_eq!(history.data.len(), 3);
+    assert_eq!(history.data[0].contract_address, CONTRACT_ADDRESS);
+    assert_eq!(history.data[0].previous, None);
+    assert_eq!(history.data[0].new, state("sold early", &["whale"]));
+    assert_eq!(history.data[0].changed_by.as_deref(), Some("test suite"));
+    assert_eq!(
+        history.data[0].changed_by_api_key_id.as_deref(),
+        Some(app.api_key_id.as_str())
+    );
+    assert_eq!(history.data[0].on_behalf_of, None);
+    assert_eq!(history.data[1].previous, state("sold early", &["whale"]));
+    assert_eq!(
+        history.data[1].new,
+        state("linked to the deployer", &["suspicious_holder"])
+    );
+    assert_eq!(history.data[1].changed_by.as_deref(), Some("test suite"));
+    assert_eq!(
+        history.data[1].on_behalf_of.as_deref(),
+        Some("moderator_jo")
+    );
+    assert_eq!(
+        history.data[2].previous,
+        state("linked to the deployer", &["suspicious_holder"])
+    );
+    assert_eq!(history.data[2].new, None);
+}
+
+#[actix_rt::test]
+async fn describing_a_holder_the_same_way_again_records_nothing() {
+    let app = spawn_app().await;
+    let body = serde_json::json!({
+        "network_name": "bsc",
+        "holder_descriptions": [
+            {"holder_address": HOLDER_ADDRESS, "contract_address": CONTRACT_ADDRESS, "notes": "sold early", "address_types": ["whale"]}
+        ]
+    });
+    for _ in 0..2 {
+        assert_eq!(
+            200,
+            app.post_holder_descriptions(&body).await.status().as_u16()
+        );
+    }
+
+    let history = fetch_history(&app).await;
+    assert_eq!(history.data.len(), 1);
+}
+
+#[actix_rt::test]
+async fn get_holder_description_history_returns_a_400_for_invalid_parameters() {
+    let app = spawn_app().await;
+    let test_cases = vec![
+        (
+            format!("network=somesuperchain&holder_address={}", HOLDER_ADDRESS),
+            "an unknown network",
+        ),
+        (
+            "network=bsc&holder_address=someholderaddress".to_string(),
+            "an invalid holder address",
+        ),
+        (
+            format!(
+                "network=bsc&holder_address={}&contract_address=somecontract",
+                HOLDER_ADDRESS
+            ),
+            "an invalid contract address",
+        ),
+        ("network=bsc".to_string(), "a missing holder address"),
+    ];
+
+    for (query, description) in test_cases {
+        let response = app.get_holder_description_history(&query).await;
+        assert_eq!(
+            400,
+            response.status().as_u16(),
+            "The API did not fail with 400 Bad Request for {}.",
+            description
+        );
+    }
+}
+
+#[actix_rt::test]
+async fn changes_made_with_the_admin_key_are_recorded_as_admin() {
+    let app = spawn_app().await;
+    let body = serde_json::json!({
+        "network_name": "bsc",
+        "holder_descriptions": [
+            {"holder_address": HOLDER_ADDRESS, "contract_address": CONTRACT_ADDRESS, "notes": "sold early", "address_types": ["whale"]}
+        ]
+    });
+    let response = reqwest::Client::new()
+        .post(&format!("{}/holder_descriptions", &app.address))
+        .header("X-Api-Key", &app.admin_api_key)
+        .header("X-Changed-By", "test suite")
+        .json(&body)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(200, response.status().as_u16());
+
+    let history = fetch_history(&app).await;
+    assert_eq!(history.data[0].changed_by.as_deref(), Some("admin"));
+    assert_eq!(history.data[0].changed_by_api_key_id, None);
+    assert_eq!(history.data[0].on_behalf_of.as_deref(), Some("test suite"));
+}
+
+#[actix_rt::test]
+async fn concurrent_first_descriptions_record_one_creation() {
+    let app = spawn_app().await;
+    let describe = |notes: &str| {
+        serde_json::json!({
+            "network_name": "bsc",
+            "holder_descriptions": [
+                {"holder_address": HOLDER_ADDRESS, "contract_address": CONTRACT_ADDRESS, "notes": notes, "address_types": ["whale"]}
+            ]
+        })
+    };
+    let (first_body, second_body) = (describe("sold early"), describe("bought the dip"));
+    let (first, second) = tokio::join!(
+        app.post_holder_descriptions(&first_body),
+        app.post_holder_descriptions(&second_body)
+    );
+    assert_eq!(200, first.status().as_u16());
+    assert_eq!(200, second.status().as_u16());
+
+    let history = fetch_history(&app).await;
+    assert_eq!(history.data.len(), 2);
+    assert_eq!(
+        history.data.iter().filter(|c| c.previous.is_none()).count(),
+        1
+    );
+}
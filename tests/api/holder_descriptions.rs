@@ -20,9 +20,9 @@ async fn add_holder_descriptions_returns_a_200_for_validform_data() {
     let body = r#"{
         "network_name": "bsc",
         "holder_descriptions": [
-            {"holder_address": "someholderaddress1", "contract_address": "somecontractaddress1", "notes": "holder1 notes", "address_types": ["whale", "longterm_holder"]},
-            {"holder_address": "someholderaddress2", "contract_address": "somecontractaddress1", "notes": "holder2 notes", "address_types": ["longterm_holder", "token_creator"]},
-            {"holder_address": "someholderaddress3", "contract_address": "somecontractaddress1", "notes": "holder3 notes", "address_types": ["scammer", "paperhand", "dumper"]}
+            {"holder_address": "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed", "contract_address": "0x8076c74c5e3f5852037f31ff0093eeb8c8add8d3", "notes": "holder1 notes", "address_types": ["whale", "longterm_holder"]},
+            {"holder_address": "0xfb6916095ca1df60bb79ce92ce3ea74c37c5d359", "contract_address": "0x8076c74c5e3f5852037f31ff0093eeb8c8add8d3", "notes": "holder2 notes", "address_types": ["longterm_holder", "token_creator"]},
+            {"holder_address": "0xdbf03b407c01e7cd3cbea99509d93f8dddc8c6fb", "contract_address": "0x8076c74c5e3f5852037f31ff0093eeb8c8add8d3", "notes": "holder3 notes", "address_types": ["scammer", "paperhand", "dumper"]}
         ]
     }"#;
     let v: Value = serde_json::from_str(body).unwrap();
@@ -31,7 +31,7 @@ async fn add_holder_descriptions_returns_a_200_for_validform_data() {
     assert_eq!(200, response.status().as_u16());
 
     let fetch_body = r#"{
-        "holder_addresses": ["someholderaddress1"]
+        "holder_addresses": ["0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"]
     }"#;
     let fetch_v: Value = serde_json::from_str(fetch_body).unwrap();
     let response_get = app.get_holder_descriptions(&fetch_v).await;
@@ -39,10 +39,16 @@ async fn add_holder_descriptions_returns_a_200_for_validform_data() {
 
     let response_parsed = response_get.json::<HolderDescriptionsResponse>().await;
     let parsed = response_parsed.unwrap();
-    assert_eq!(parsed.data[0].contract_address, "somecontractaddress1");
+    assert_eq!(
+        parsed.data[0].contract_address,
+        "0x8076c74c5e3f5852037f31ff0093eeb8c8add8d3"
+    );
     assert_eq!(parsed.data[0].notes, "holder1 notes");
     assert_eq!(parsed.data[0].network_name, "bsc");
-    assert_eq!(parsed.data[0].holder_address, "someholderaddress1");
+    assert_eq!(
+        parsed.data[0].holder_address,
+        "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"
+    );
     assert_eq!(parsed.data[0].address_types[0], "whale");
 }
 
@@ -51,27 +57,27 @@ async fn add_holder_descriptions_returns_a_400_when_data_is_missing() {
     let app = spawn_app().await;
     let no_network_name = r#"{
         "holder_descriptions": [
-            {"holder_address": "someholderaddress1", "contract_address": "somecontractaddress1", "notes": "holder1 notes", "address_types": ["whale", "longterm_holder"]},
-            {"holder_address": "someholderaddress2", "contract_address": "somecontractaddress1", "notes": "holder2 notes", "address_types": ["longterm_holder", "token_creator"]},
-            {"holder_address": "someholderaddress3", "contract_address": "somecontractaddress1", "notes": "holder3 notes", "address_types": ["scammer", "paperhand", "dumper"]}
+            {"holder_address": "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed", "contract_address": "0x8076c74c5e3f5852037f31ff0093eeb8c8add8d3", "notes": "holder1 notes", "address_types": ["whale", "longterm_holder"]},
+            {"holder_address": "0xfb6916095ca1df60bb79ce92ce3ea74c37c5d359", "contract_address": "0x8076c74c5e3f5852037f31ff0093eeb8c8add8d3", "notes": "holder2 notes", "address_types": ["longterm_holder", "token_creator"]},
+            {"holder_address": "0xdbf03b407c01e7cd3cbea99509d93f8dddc8c6fb", "contract_address": "0x8076c74c5e3f5852037f31ff0093eeb8c8add8d3", "notes": "holder3 notes", "address_types": ["scammer", "paperhand", "dumper"]}
         ]
     }"#;
     let no_holder_address = r#"{
         "network_name": "bsc",
         "holder_descriptions": [
-            {"contract_address": "somecontractaddress1", "notes": "holder1 notes", "address_types": ["whale", "longterm_holder"]}
+            {"contract_address": "0x8076c74c5e3f5852037f31ff0093eeb8c8add8d3", "notes": "holder1 notes", "address_types": ["whale", "longterm_holder"]}
         ]
     }"#;
     let no_contract_address = r#"{
         "network_name": "bsc",
         "holder_descriptions": [
-            {"holder_address": "someholderaddress3", "notes": "holder3 notes", "address_types": ["scammer", "paperhand", "dumper"]}
+            {"holder_address": "0xdbf03b407c01e7cd3cbea99509d93f8dddc8c6fb", "notes": "holder3 notes", "address_types": ["scammer", "paperhand", "dumper"]}
         ]
     }"#;
     let no_address_types = r#"{
         "network_name": "bsc",
         "holder_descriptions": [
-            {"holder_address": "someholderaddress3", "contract_address": "somecontractaddress1", "notes": "holder3 notes"}
+            {"holder_address": "0xdbf03b407c01e7cd3cbea99509d93f8dddc8c6fb", "contract_address": "0x8076c74c5e3f5852037f31ff0093eeb8c8add8d3", "notes": "holder3 notes"}
         ]
     }"#;
     let test_cases = vec![
@@ -100,9 +106,9 @@ async fn insert_holder_description_fails_if_there_is_a_fatal_database_error() {
     let body = r#"{
         "network_name": "bsc",
         "holder_descriptions": [
-            {"holder_address": "someholderaddress1", "contract_address": "somecontractaddress1", "notes": "holder1 notes", "address_types": ["whale", "longterm_holder"]},
-            {"holder_address": "someholderaddress2", "contract_address": "somecontractaddress1", "notes": "holder2 notes", "address_types": ["longterm_holder", "token_creator"]},
-            {"holder_address": "someholderaddress3", "contract_address": "somecontractaddress1", "notes": "holder3 notes", "address_types": ["scammer", "paperhand", "dumper"]}
+            {"holder_address": "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed", "contract_address": "0x8076c74c5e3f5852037f31ff0093eeb8c8add8d3", "notes": "holder1 notes", "address_types": ["whale", "longterm_holder"]},
+            {"holder_address": "0xfb6916095ca1df60bb79ce92ce3ea74c37c5d359", "contract_address": "0x8076c74c5e3f5852037f31ff0093eeb8c8add8d3", "notes": "holder2 notes", "address_types": ["longterm_holder", "token_creator"]},
+            {"holder_address": "0xdbf03b407c01e7cd3cbea99509d93f8dddc8c6fb", "contract_address": "0x8076c74c5e3f5852037f31ff0093eeb8c8add8d3", "notes": "holder3 notes", "address_types": ["scammer", "paperhand", "dumper"]}
         ]
     }"#;
     let v: Value = serde_json::from_str(body).unwrap();
@@ -113,3 +119,212 @@ async fn insert_holder_description_fails_if_there_is_a_fatal_database_error() {
     let response_post = app.post_holder_descriptions(&v).await;
     assert_eq!(response_post.status().as_u16(), 500);
 }
+
+const HOLDER_ADDRESS: &str = "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed";
+const CONTRACT_ADDRESS: &str = "0x8076c74c5e3f5852037f31ff0093eeb8c8add8d3";
+
+async fn fetch_descriptions(app: &crate::helpers::TestApp) -> Vec<HolderRowData> {
+    let fetch_body = serde_json::json!({ "holder_addresses": [HOLDER_ADDRESS] });
+    app.get_holder_descriptions(&fetch_body)
+        .await
+        .json::<HolderDescriptionsResponse>()
+        .await
+        .unwrap()
+        .data
+}
+
+#[actix_rt::test]
+async fn describing_a_holder_again_merges_its_address_types() {
+    let app = spawn_app().await;
+    for (address_types, notes) in &[
+        (vec!["whale", "longterm_holder"], "first notes"),
+        (vec!["longterm_holder", "dumper"], "second notes"),
+    ] {
+        let body = serde_json::json!({
+            "network_name": "bsc",
+            "holder_descriptions": [
+                {"holder_address": HOLDER_ADDRESS, "contract_address": CONTRACT_ADDRESS, "notes": notes, "address_types": address_types}
+            ]
+        });
+        assert_eq!(
+            200,
+            app.post_holder_descriptions(&body).await.status().as_u16()
+        );
+    }
+
+    let descriptions = fetch_descriptions(&app).await;
+    assert_eq!(descriptions.len(), 1);
+    assert_eq!(
+        descriptions[0].address_types,
+        vec!["whale", "longterm_holder", "dumper"]
+    );
+    assert_eq!(descriptions[0].notes, "second notes");
+}
+
+#[actix_rt::test]
+async fn put_holder_description_replaces_a_wrong_tag() {
+    let app = spawn_app().await;
+    let body = serde_json::json!({
+        "network_name": "bsc",
+        "holder_descriptions": [
+            {"holder_address": HOLDER_ADDRESS, "contract_address": CONTRACT_ADDRESS, "notes": "rugged", "address_types": ["scammer", "whale"]}
+        ]
+    });
+    assert_eq!(
+        200,
+        app.post_holder_descriptions(&body).await.status().as_u16()
+    );
+
+    let update = serde_json::json!({"address_types": ["whale", "legit"], "notes": "mistaken for the deployer"});
+    let response = app
+        .put_holder_description("bsc", HOLDER_ADDRESS, CONTRACT_ADDRESS, &update)
+        .await;
+    assert_eq!(200, response.status().as_u16());
+
+    let descriptions = fetch_descriptions(&app).await;
+    assert_eq!(descriptions.len(), 1);
+    assert_eq!(descriptions[0].address_types, vec!["whale", "legit"]);
+    assert_eq!(descriptions[0].notes, "mistaken for the deployer");
+}
+
+#[actix_rt::test]
+async fn put_holder_description_returns_a_400_for_invalid_data() {
+    let app = spawn_app().await;
+    let test_cases = vec![
+        (
+            "bsc",
+            HOLDER_ADDRESS,
+            serde_json::json!({"address_types": ["moon_boy"]}),
+            "an unknown address type",
+        ),
+        (
+            "bsc",
+            "someholderaddress",
+            serde_json::json!({"address_types": ["whale"]}),
+            "an invalid holder address",
+        ),
+        (
+            "somesuperchain",
+            HOLDER_ADDRESS,
+            serde_json::json!({"address_types": ["whale"]}),
+            "an unknown network",
+        ),
+    ];
+
+    for (network, holder_address, body, description) in test_cases {
+        let response = app
+            .put_holder_description(network, holder_address, CONTRACT_ADDRESS, &body)
+            .await;
+        assert_eq!(
+            400,
+            response.status().as_u16(),
+            "The API did not fail with 400 Bad Request for {}.",
+            description
+        );
+    }
+}
+
+#[actix_rt::test]
+async fn delete_holder_description_removes_it() {
+    let app = spawn_app().await;
+    let update = serde_json::json!({"address_types": ["scammer"]});
+    assert_eq!(
+        200,
+        app.put_holder_description("bsc", HOLDER_ADDRESS, CONTRACT_ADDRESS, &update)
+            .await
+            .status()
+            .as_u16()
+    );
+
+    let response = app
+        .delete_holder_description("bsc", HOLDER_ADDRESS, CONTRACT_ADDRESS)
+        .await;
+    assert_eq!(200, response.status().as_u16());
+    assert!(fetch_descriptions(&app).await.is_empty());
+
+    let response = app
+        .delete_holder_description("bsc", HOLDER_ADDRESS, CONTRACT_ADDRESS)
+        .await;
+    assert_eq!(404, response.status().as_u16());
+}
+
+#[actix_rt::test]
+async fn get_holder_descriptions_filters_by_contract_and_address_types() {
+    let app = spawn_app().await;
+    let other_contract = "0xfb6916095ca1df60bb79ce92ce3ea74c37c5d359";
+    let body = serde_json::json!({
+        "network_name": "bsc",
+        "holder_descriptions": [
+            {"holder_address": HOLDER_ADDRESS, "contract_address": CONTRACT_ADDRESS, "address_types": ["whale"]},
+            {"holder_address": "0xdbf03b407c01e7cd3cbea99509d93f8dddc8c6fb", "contract_address": CONTRACT_ADDRESS, "address_types": ["dumper", "paperhand"]},
+            {"holder_address": "0xd1220a0cf47c7b9be7a2e6ba89f429762e7b9adb", "contract_address": CONTRACT_ADDRESS, "address_types": ["longterm_holder"]},
+            {"holder_address": HOLDER_ADDRESS, "contract_address": other_contract, "address_types": ["whale"]}
+        ]
+    });
+    assert_eq!(
+        200,
+        app.post_holder_descriptions(&body).await.status().as_u16()
+    );
+
+    let filter = serde_json::json!({
+        "network_name": "bsc",
+        "contract_address": CONTRACT_ADDRESS,
+        "address_types": ["whale", "dumper"]
+    });
+    let response = app.get_holder_descriptions(&filter).await;
+    assert_eq!(200, response.status().as_u16());
+    let data = response
+        .json::<HolderDescriptionsResponse>()
+        .await
+        .unwrap()
+        .data;
+    let holders: Vec<&str> = data.iter().map(|d| d.holder_address.as_str()).collect();
+    assert_eq!(
+        holders,
+        vec![HOLDER_ADDRESS, "0xdbf03b407c01e7cd3cbea99509d93f8dddc8c6fb"]
+    );
+    assert!(data.iter().all(|d| d.contract_address == CONTRACT_ADDRESS));
+
+    let filter = serde_json::json!({ "network_name": "eth", "address_types": ["whale"] });
+    let response = app.get_holder_descriptions(&filter).await;
+    assert!(response
+        .json::<HolderDescriptionsResponse>()
+        .await
+        .unwrap()
+        .data
+        .is_empty());
+}
+
+#[actix_rt::test]
+async fn get_holder_descriptions_returns_a_400_for_invalid_filters() {
+    let app = spawn_app().await;
+    let test_cases = vec![
+        (serde_json::json!({}), "no filter"),
+        (
+            serde_json::json!({"network_name": "somesuperchain"}),
+            "an unknown network",
+        ),
+        (
+            serde_json::json!({"contract_address": "somecontract"}),
+            "an invalid contract address",
+        ),
+        (
+            serde_json::json!({"network_name": "sol", "contract_address": CONTRACT_ADDRESS}),
+            "a contract address from another network",
+        ),
+        (
+            serde_json::json!({"address_types": ["moon_boy"]}),
+            "an unknown address type",
+        ),
+    ];
+
+    for (body, description) in test_cases {
+        let response = app.get_holder_descriptions(&body).await;
+        assert_eq!(
+            400,
+            response.status().as_u16(),
+            "The API did not fail with 400 Bad Request for {}.",
+            description
+        );
+    }
+}
@@ -1,18 +1,26 @@
 use crate::helpers::spawn_app;
 use bigdecimal::ToPrimitive;
-use serde_json::{from_str, Value};
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use serde_json::Value;
 use sqlx::types::BigDecimal;
+use std::str::FromStr;
+
+const CONTRACT_ADDRESS: &str = "0x8076c74c5e3f5852037f31ff0093eeb8c8add8d3";
+const WHALE: &str = "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed";
+const DUMPER: &str = "0xfb6916095ca1df60bb79ce92ce3ea74c37c5d359";
+const NEWCOMER: &str = "0xdbf03b407c01e7cd3cbea99509d93f8dddc8c6fb";
+const SHRIMP: &str = "0xd1220a0cf47c7b9be7a2e6ba89f429762e7b9adb";
+const BURN_ADDRESS: &str = "0x000000000000000000000000000000000000dead";
 
 #[actix_rt::test]
 async fn holders_returns_a_200_for_validform_data() {
     let app = spawn_app().await;
-    let body = r#"{
+    let v = serde_json::json!({
         "network": "bsc",
         "token_name": "some coin",
-        "contract_address": "some contract address",
-        "holders": [{"holder_address": "someholderaddress", "place": 10, "amount": "10,000,000,000,000.100001"}]
-    }"#;
-    let v: Value = serde_json::from_str(body).unwrap();
+        "contract_address": CONTRACT_ADDRESS,
+        "holders": [{"holder_address": WHALE, "place": 10, "amount": "10,000,000,000,000.100001"}]
+    });
     let response = app.post_holders(&v).await;
 
     assert_eq!(200, response.status().as_u16());
@@ -22,7 +30,7 @@ async fn holders_returns_a_200_for_validform_data() {
         .await
         .expect("Failed to fetch saved subscription.");
 
-    assert_eq!(saved.holder_address, "someholderaddress");
+    assert_eq!(saved.holder_address, WHALE);
 
     let saved_amount = sqlx::query!("SELECT amount FROM holder_totals",)
         .fetch_one(&app.db_pool)
@@ -55,11 +63,25 @@ async fn holders_returns_a_400_when_data_is_missing() {
         "token_name": "some coin",
         "contract_address": "some contract address"
     }"#;
+    let bad_checksum = r#"{
+        "network": "bsc",
+        "token_name": "some coin",
+        "contract_address": "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD",
+        "holders": [{"holder_address": "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed", "place": 10, "amount": "10.10"}]
+    }"#;
+    let not_an_address = r#"{
+        "network": "bsc",
+        "token_name": "some coin",
+        "contract_address": "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed",
+        "holders": [{"holder_address": "someholderaddress", "place": 10, "amount": "10.10"}]
+    }"#;
     let test_cases = vec![
         (no_contract_address, "no contract address"),
         (no_token_name, "no token name"),
         (no_network, "no network"),
         (no_holders, "no holders"),
+        (bad_checksum, "a contract address with a bad checksum"),
+        (not_an_address, "a holder address that is not an address"),
     ];
 
     for (invalid_body, error_message) in test_cases {
@@ -78,13 +100,12 @@ async fn holders_returns_a_400_when_data_is_missing() {
 #[actix_rt::test]
 async fn add_holder_fails_if_there_is_a_fatal_database_error() {
     let app = spawn_app().await;
-    let body = r#"{
+    let v = serde_json::json!({
         "network": "bsc",
         "token_name": "some coin",
-        "contract_address": "some contract address",
-        "holders": [{"holder_address": "some holder address", "place": 10, "amount": "10.10"}]
-    }"#;
-    let v: Value = serde_json::from_str(body).unwrap();
+        "contract_address": CONTRACT_ADDRESS,
+        "holders": [{"holder_address": WHALE, "place": 10, "amount": "10.10"}]
+    });
     sqlx::query!("ALTER TABLE holder_totals DROP COLUMN holder_address",)
         .execute(&app.db_pool)
         .await
@@ -92,3 +113,694 @@ async fn add_holder_fails_if_there_is_a_fatal_database_error() {
     let response_post = app.post_holders(&v).await;
     assert_eq!(response_post.status().as_u16(), 500);
 }
+
+#[actix_rt::test]
+async fn holders_list_returns_a_500_when_the_query_fails() {
+    let app = spawn_app().await;
+    post_two_snapshots(&app).await;
+    sqlx::query!("ALTER TABLE holder_totals DROP COLUMN place",)
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+
+    let response = app
+        .get_holders(&format!(
+            "network=bsc&contract_address={}",
+            CONTRACT_ADDRESS
+        ))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 500);
+}
+
+fn diff_query(from: DateTime<Utc>, to: DateTime<Utc>) -> String {
+    format!(
+        "network=bsc&contract_address={}&from={}&to={}",
+        CONTRACT_ADDRESS,
+        from.to_rfc3339_opts(SecondsFormat::Micros, true),
+        to.to_rfc3339_opts(SecondsFormat::Micros, true)
+    )
+}
+
+#[actix_rt::test]
+async fn holders_diff_reports_changes_between_two_snapshots() {
+    let app = spawn_app().await;
+    let first = serde_json::json!({
+        "network": "bsc",
+        "token_name": "some coin",
+        "contract_address": CONTRACT_ADDRESS,
+        "holders": [
+            {"holder_address": WHALE, "place": 1, "amount": "1,000"},
+            {"holder_address": DUMPER, "place": 2, "amount": "500"}
+        ]
+    });
+    let second = serde_json::json!({
+        "network": "bsc",
+        "token_name": "some coin",
+        "contract_address": CONTRACT_ADDRESS,
+        "holders": [
+            {"holder_address": WHALE, "place": 1, "amount": "1,500"},
+            {"holder_address": NEWCOMER, "place": 2, "amount": "300"}
+        ]
+    });
+    let before_first = Utc::now();
+    assert_eq!(200, app.post_holders(&first).await.status().as_u16());
+    assert_eq!(200, app.post_holders(&second).await.status().as_u16());
+
+    let response = app
+        .get_holders_diff(&diff_query(before_first, Utc::now()))
+        .await;
+    assert_eq!(200, response.status().as_u16());
+
+    let parsed: Value = response.json().await.unwrap();
+    let data = &parsed["data"];
+    assert_eq!(data["entered"], serde_json::json!([NEWCOMER]));
+    assert_eq!(data["left"], serde_json::json!([DUMPER]));
+    let whale = data["changes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|c| c["holder_address"] == WHALE)
+        .unwrap();
+    assert_eq!(whale["place_change"], 0);
+    assert_eq!(
+        BigDecimal::from_str(whale["amount_change"].as_str().unwrap()).unwrap(),
+        BigDecimal::from(500)
+    );
+}
+
+#[actix_rt::test]
+async fn holders_diff_returns_a_404_when_there_are_no_snapshots() {
+    let app = spawn_app().await;
+    let response = app
+        .get_holders_diff(&diff_query(Utc::now(), Utc::now()))
+        .await;
+    assert_eq!(404, response.status().as_u16());
+}
+
+#[actix_rt::test]
+async fn holders_diff_returns_a_400_when_from_is_after_to() {
+    let app = spawn_app().await;
+    let response = app
+        .get_holders_diff(&diff_query(Utc::now(), Utc::now() - Duration::days(1)))
+        .await;
+    assert_eq!(400, response.status().as_u16());
+}
+
+async fn post_two_snapshots(app: &crate::helpers::TestApp) -> DateTime<Utc> {
+    let first = serde_json::json!({
+        "network": "bsc",
+        "token_name": "some coin",
+        "contract_address": CONTRACT_ADDRESS,
+        "holders": [
+            {"holder_address": WHALE, "place": 1, "amount": "1,000"},
+            {"holder_address": DUMPER, "place": 2, "amount": "500"},
+            {"holder_address": SHRIMP, "place": 3, "amount": "10"}
+        ]
+    });
+    let second = serde_json::json!({
+        "network": "bsc",
+        "token_name": "some coin",
+        "contract_address": CONTRACT_ADDRESS,
+        "holders": [
+            {"holder_address": WHALE, "place": 1, "amount": "1,500"},
+            {"holder_address": NEWCOMER, "place": 2, "amount": "300"}
+        ]
+    });
+    assert_eq!(200, app.post_holders(&first).await.status().as_u16());
+    let between = Utc::now();
+    assert_eq!(200, app.post_holders(&second).await.status().as_u16());
+    between
+}
+
+fn holder_places(page: &Value) -> Vec<(String, i64)> {
+    page["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|h| {
+            (
+                h["holder_address"].as_str().unwrap().to_string(),
+                h["place"].as_i64().unwrap(),
+            )
+        })
+        .collect()
+}
+
+#[actix_rt::test]
+async fn holders_can_be_paged_through_with_a_cursor() {
+    let app = spawn_app().await;
+    post_two_snapshots(&app).await;
+
+    let mut query = format!("network=bsc&contract_address={}&limit=2", CONTRACT_ADDRESS);
+    let mut holders = vec![];
+    let mut pages = 0;
+    loop {
+        let response = app.get_holders(&query).await;
+        assert_eq!(200, response.status().as_u16());
+        let page: Value = response.json().await.unwrap();
+        holders.extend(holder_places(&page));
+        pages += 1;
+        match page["next_cursor"].as_str() {
+            Some(cursor) => {
+                query = format!(
+                    "network=bsc&contract_address={}&limit=2&cursor={}",
+                    CONTRACT_ADDRESS, cursor
+                )
+            }
+            None => break,
+        }
+    }
+
+    assert_eq!(pages, 3);
+    assert_eq!(
+        holders,
+        vec![
+            (WHALE.to_string(), 1),
+            (DUMPER.to_string(), 2),
+            (SHRIMP.to_string(), 3),
+            (WHALE.to_string(), 1),
+            (NEWCOMER.to_string(), 2)
+        ]
+    );
+}
+
+#[actix_rt::test]
+async fn holders_can_be_limited_to_a_time_range_or_the_latest_snapshot() {
+    let app = spawn_app().await;
+    let between = post_two_snapshots(&app).await;
+    let between = between.to_rfc3339_opts(SecondsFormat::Micros, true);
+
+    let query = format!(
+        "network=bsc&contract_address={}&until={}",
+        CONTRACT_ADDRESS, between
+    );
+    let page: Value = app.get_holders(&query).await.json().await.unwrap();
+    assert_eq!(holder_places(&page).len(), 3);
+    assert_eq!(page["next_cursor"], Value::Null);
+
+    let query = format!(
+        "network=bsc&contract_address={}&since={}",
+        CONTRACT_ADDRESS, between
+    );
+    let page: Value = app.get_holders(&query).await.json().await.unwrap();
+    assert_eq!(holder_places(&page).len(), 2);
+
+    let query = format!(
+        "network=bsc&contract_address={}&latest_only=true",
+        CONTRACT_ADDRESS
+    );
+    let page: Value = app.get_holders(&query).await.json().await.unwrap();
+    assert_eq!(
+        holder_places(&page),
+        vec![(WHALE.to_string(), 1), (NEWCOMER.to_string(), 2)]
+    );
+
+    let query = format!(
+        "network=bsc&contract_address={}&latest_only=true&until={}",
+        CONTRACT_ADDRESS, between
+    );
+    let page: Value = app.get_holders(&query).await.json().await.unwrap();
+    assert_eq!(holder_places(&page).len(), 3);
+}
+
+#[actix_rt::test]
+async fn submissions_sharing_a_checked_on_stay_separate_snapshots() {
+    let app = spawn_app().await;
+    let checked_on = "2022-01-20T08:00:00Z";
+    let first = serde_json::json!({
+        "network": "bsc",
+        "token_name": "some coin",
+        "contract_address": CONTRACT_ADDRESS,
+        "checked_on": checked_on,
+        "holders": [
+            {"holder_address": WHALE, "place": 1, "amount": "1,000"},
+            {"holder_address": DUMPER, "place": 2, "amount": "500"},
+            {"holder_address": SHRIMP, "place": 3, "amount": "10"}
+        ]
+    });
+    let second = serde_json::json!({
+        "network": "bsc",
+        "token_name": "some coin",
+        "contract_address": CONTRACT_ADDRESS,
+        "checked_on": checked_on,
+        "holders": [
+            {"holder_address": WHALE, "place": 1, "amount": "1,500"},
+            {"holder_address": NEWCOMER, "place": 2, "amount": "300"}
+        ]
+    });
+    let mut snapshot_ids = vec![];
+    for body in [first, second] {
+        let response = app.post_holders(&body).await;
+        assert_eq!(200, response.status().as_u16());
+        let saved: Value = response.json().await.unwrap();
+        snapshot_ids.push(saved["snapshot_id"].as_i64().unwrap());
+    }
+
+    let diff: Value = app
+        .get_holders_diff(&format!(
+            "network=bsc&contract_address={}&from={}&to={}",
+            CONTRACT_ADDRESS, checked_on, checked_on
+        ))
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(diff["from_snapshot_id"], snapshot_ids[0]);
+    assert_eq!(diff["to_snapshot_id"], snapshot_ids[1]);
+    assert_eq!(diff["data"]["entered"], serde_json::json!([NEWCOMER]));
+    assert_eq!(diff["data"]["left"], serde_json::json!([DUMPER, SHRIMP]));
+
+    let latest: Value = app
+        .get_holders(&format!(
+            "network=bsc&contract_address={}&latest_only=true",
+            CONTRACT_ADDRESS
+        ))
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(
+        holder_places(&latest),
+        vec![(WHALE.to_string(), 1), (NEWCOMER.to_string(), 2)]
+    );
+
+    let mut query = format!("network=bsc&contract_address={}&limit=1", CONTRACT_ADDRESS);
+    let mut holders = vec![];
+    loop {
+        let page: Value = app.get_holders(&query).await.json().await.unwrap();
+        holders.extend(holder_places(&page));
+        match page["next_cursor"].as_str() {
+            Some(cursor) => {
+                query = format!(
+                    "network=bsc&contract_address={}&limit=1&cursor={}",
+                    CONTRACT_ADDRESS, cursor
+                )
+            }
+            None => break,
+        }
+    }
+    assert_eq!(holders.len(), 5);
+
+    let metrics: Value = app
+        .get_holder_metrics(&format!(
+            "network=bsc&contract_address={}",
+            CONTRACT_ADDRESS
+        ))
+        .await
+        .json()
+        .await
+        .unwrap();
+    let metrics_snapshot_ids: Vec<i64> = metrics["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| m["snapshot_id"].as_i64().unwrap())
+        .collect();
+    assert_eq!(metrics_snapshot_ids, snapshot_ids);
+}
+
+#[actix_rt::test]
+async fn holders_list_returns_a_400_for_invalid_parameters() {
+    let app = spawn_app().await;
+    let now = Utc::now();
+    let test_cases = vec![
+        (
+            format!("network=bsc&contract_address={}&limit=0", CONTRACT_ADDRESS),
+            "a limit of zero",
+        ),
+        (
+            format!(
+                "network=bsc&contract_address={}&limit=100000",
+                CONTRACT_ADDRESS
+            ),
+            "a limit that is too large",
+        ),
+        (
+            format!(
+                "network=bsc&contract_address={}&cursor=garbage",
+                CONTRACT_ADDRESS
+            ),
+            "an invalid cursor",
+        ),
+        (
+            format!(
+                "network=bsc&contract_address={}&since={}&until={}",
+                CONTRACT_ADDRESS,
+                now.to_rfc3339_opts(SecondsFormat::Micros, true),
+                (now - Duration::days(1)).to_rfc3339_opts(SecondsFormat::Micros, true)
+            ),
+            "since after until",
+        ),
+        (
+            "network=bsc&contract_address=somecontract".to_string(),
+            "an invalid contract address",
+        ),
+    ];
+
+    for (query, description) in test_cases {
+        let response = app.get_holders(&query).await;
+        assert_eq!(
+            400,
+            response.status().as_u16(),
+            "The API did not fail with 400 Bad Request for {}.",
+            description
+        );
+    }
+}
+
+#[actix_rt::test]
+async fn each_submission_is_a_snapshot_that_can_be_fetched_in_full() {
+    let app = spawn_app().await;
+    let first = serde_json::json!({
+        "network": "bsc",
+        "token_name": "some coin",
+        "contract_address": CONTRACT_ADDRESS,
+        "source": "bscscan",
+        "holders": [
+            {"holder_address": DUMPER, "place": 2, "amount": "500"},
+            {"holder_address": WHALE, "place": 1, "amount": "1,000"}
+        ]
+    });
+    let second = serde_json::json!({
+        "network": "bsc",
+        "token_name": "some coin",
+        "contract_address": CONTRACT_ADDRESS,
+        "holders": [{"holder_address": WHALE, "place": 1, "amount": "1,500"}]
+    });
+    let response = app.post_holders(&first).await;
+    assert_eq!(200, response.status().as_u16());
+    let first_id = response.json::<Value>().await.unwrap()["snapshot_id"]
+        .as_i64()
+        .unwrap();
+    assert_eq!(200, app.post_holders(&second).await.status().as_u16());
+
+    let response = app
+        .get_holder_snapshots(&format!(
+            "network=bsc&contract_address={}",
+            CONTRACT_ADDRESS
+        ))
+        .await;
+    assert_eq!(200, response.status().as_u16());
+    let snapshots: Value = response.json().await.unwrap();
+    let snapshots = snapshots["data"].as_array().unwrap();
+    assert_eq!(snapshots.len(), 2);
+    assert_eq!(snapshots[0]["snapshot_id"], first_id);
+    assert_eq!(snapshots[0]["source"], "bscscan");
+    assert_eq!(snapshots[0]["holder_count"], 2);
+    assert_eq!(snapshots[1]["source"], Value::Null);
+    assert_eq!(snapshots[1]["holder_count"], 1);
+
+    let response = app.get_holder_snapshot(first_id).await;
+    assert_eq!(200, response.status().as_u16());
+    let snapshot: Value = response.json().await.unwrap();
+    assert_eq!(snapshot["contract_address"], CONTRACT_ADDRESS);
+    assert_eq!(snapshot["checked_on"], snapshots[0]["checked_on"]);
+    let holders: Vec<&str> = snapshot["holders"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|h| h["holder_address"].as_str().unwrap())
+        .collect();
+    assert_eq!(holders, vec![WHALE, DUMPER]);
+}
+
+#[actix_rt::test]
+async fn get_holder_snapshot_returns_a_404_for_an_unknown_snapshot() {
+    let app = spawn_app().await;
+    let response = app.get_holder_snapshot(42).await;
+    assert_eq!(404, response.status().as_u16());
+}
+
+#[actix_rt::test]
+async fn backfilled_holders_keep_their_checked_on_and_block_number() {
+    let app = spawn_app().await;
+    let checked_on = "2021-11-02T08:30:00Z";
+    let body = serde_json::json!({
+        "network": "bsc",
+        "token_name": "some coin",
+        "contract_address": CONTRACT_ADDRESS,
+        "checked_on": checked_on,
+        "block_number": 12_345_678,
+        "holders": [{"holder_address": WHALE, "place": 1, "amount": "1,000"}]
+    });
+    let response = app.post_holders(&body).await;
+    assert_eq!(200, response.status().as_u16());
+    let snapshot_id = response.json::<Value>().await.unwrap()["snapshot_id"]
+        .as_i64()
+        .unwrap();
+
+    let snapshot: Value = app
+        .get_holder_snapshot(snapshot_id)
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(snapshot["block_number"], 12_345_678);
+    assert_eq!(
+        DateTime::parse_from_rfc3339(snapshot["checked_on"].as_str().unwrap()).unwrap(),
+        DateTime::parse_from_rfc3339(checked_on).unwrap()
+    );
+    let saved = sqlx::query!("SELECT checked_on FROM holder_totals")
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to fetch saved holders.");
+    assert_eq!(
+        saved.checked_on,
+        DateTime::parse_from_rfc3339(checked_on).unwrap()
+    );
+}
+
+#[actix_rt::test]
+async fn holders_from_the_future_are_rejected() {
+    let app = spawn_app().await;
+    let tomorrow = (Utc::now() + Duration::days(1)).to_rfc3339_opts(SecondsFormat::Secs, true);
+    let test_cases = vec![
+        (
+            serde_json::json!({"checked_on": tomorrow}),
+            "a checked_on in the future",
+        ),
+        (
+            serde_json::json!({"block_number": -1}),
+            "a negative block number",
+        ),
+    ];
+
+    for (extra, description) in test_cases {
+        let mut body = serde_json::json!({
+            "network": "bsc",
+            "token_name": "some coin",
+            "contract_address": CONTRACT_ADDRESS,
+            "holders": [{"holder_address": WHALE, "place": 1, "amount": "1,000"}]
+        });
+        body.as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().unwrap().clone());
+        let response = app.post_holders(&body).await;
+        assert_eq!(
+            400,
+            response.status().as_u16(),
+            "The API did not fail with 400 Bad Request for {}.",
+            description
+        );
+    }
+}
+
+#[actix_rt::test]
+async fn malformed_amounts_are_rejected_naming_the_holder() {
+    let app = spawn_app().await;
+
+    for amount in ["12.3.4", "1e", "-5"] {
+        let body = serde_json::json!({
+            "network": "bsc",
+            "token_name": "some coin",
+            "contract_address": CONTRACT_ADDRESS,
+            "holders": [
+                {"holder_address": WHALE, "place": 1, "amount": "1.000.000,5"},
+                {"holder_address": DUMPER, "place": 2, "amount": amount}
+            ]
+        });
+        let response = app.post_holders(&body).await;
+
+        assert_eq!(
+            400,
+            response.status().as_u16(),
+            "The API did not fail with 400 Bad Request for {}.",
+            amount
+        );
+        let error = response.text().await.unwrap();
+        assert!(
+            error.contains(DUMPER),
+            "{} does not name the holder.",
+            error
+        );
+    }
+    let saved = sqlx::query!(r#"SELECT COUNT(*) AS "count!" FROM holder_totals"#)
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to count holders.");
+    assert_eq!(saved.count, 0);
+}
+
+fn decimal(value: &Value) -> BigDecimal {
+    BigDecimal::from_str(value.as_str().unwrap()).unwrap()
+}
+
+#[actix_rt::test]
+async fn raw_amounts_are_scaled_and_reported_against_the_total_supply() {
+    let app = spawn_app().await;
+    let raw = serde_json::json!({
+        "network": "bsc",
+        "token_name": "some coin",
+        "contract_address": CONTRACT_ADDRESS,
+        "amount_format": "raw",
+        "symbol": "SOME",
+        "decimals": 18,
+        "total_supply": "10000000000000000000000",
+        "holders": [
+            {"holder_address": WHALE, "place": 1, "amount": "1500000000000000000000"},
+            {"holder_address": DUMPER, "place": 2, "amount": "1"}
+        ]
+    });
+    assert_eq!(200, app.post_holders(&raw).await.status().as_u16());
+    // Later submissions may be scaled and leave the decimals out.
+    let scaled = serde_json::json!({
+        "network": "bsc",
+        "token_name": "some coin",
+        "contract_address": CONTRACT_ADDRESS,
+        "holders": [{"holder_address": WHALE, "place": 1, "amount": "2,500.25"}]
+    });
+    assert_eq!(200, app.post_holders(&scaled).await.status().as_u16());
+
+    let page: Value = app
+        .get_holders(&format!(
+            "network=bsc&contract_address={}",
+            CONTRACT_ADDRESS
+        ))
+        .await
+        .json()
+        .await
+        .unwrap();
+
+    let rows = page["data"].as_array().unwrap();
+    assert_eq!(rows.len(), 3);
+    assert_eq!(decimal(&rows[0]["amount"]), BigDecimal::from(1500));
+    assert_eq!(decimal(&rows[0]["percent_of_supply"]), BigDecimal::from(15));
+    assert_eq!(
+        decimal(&rows[1]["amount"]),
+        BigDecimal::from_str("0.000000000000000001").unwrap()
+    );
+    assert_eq!(decimal(&rows[1]["raw_amount"]), BigDecimal::from(1));
+    assert_eq!(
+        decimal(&rows[2]["raw_amount"]),
+        BigDecimal::from_str("2500250000000000000000").unwrap()
+    );
+    assert_eq!(
+        decimal(&rows[2]["percent_of_supply"]),
+        BigDecimal::from_str("25.0025").unwrap()
+    );
+}
+
+#[actix_rt::test]
+async fn amounts_that_do_not_fit_the_decimals_are_rejected() {
+    let app = spawn_app().await;
+    let test_cases = vec![
+        (
+            serde_json::json!({"amount_format": "raw", "amount": "1000"}),
+            "raw amounts without decimals",
+        ),
+        (
+            serde_json::json!({"amount_format": "raw", "decimals": 18, "amount": "1000.5"}),
+            "a raw amount with a fraction",
+        ),
+        (
+            serde_json::json!({"decimals": 2, "amount": "0.001"}),
+            "a scaled amount with more than the token's decimals",
+        ),
+        (
+            serde_json::json!({"total_supply": "1000", "amount": "10"}),
+            "a total supply without decimals",
+        ),
+        (
+            serde_json::json!({"decimals": 256, "amount": "10"}),
+            "too many decimals",
+        ),
+        (
+            serde_json::json!({"amount_format": "wei", "amount": "10"}),
+            "an unknown amount format",
+        ),
+    ];
+
+    for (fields, description) in test_cases {
+        let mut body = serde_json::json!({
+            "network": "bsc",
+            "token_name": "some coin",
+            "contract_address": CONTRACT_ADDRESS,
+            "holders": [{"holder_address": WHALE, "place": 1, "amount": fields["amount"]}]
+        });
+        for (key, value) in fields.as_object().unwrap() {
+            if key != "amount" {
+                body[key] = value.clone();
+            }
+        }
+        let response = app.post_holders(&body).await;
+        assert_eq!(
+            400,
+            response.status().as_u16(),
+            "The API did not fail with 400 Bad Request for {}.",
+            description
+        );
+    }
+    let saved = sqlx::query!(r#"SELECT COUNT(*) AS "count!" FROM holder_totals"#)
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to count holders.");
+    assert_eq!(saved.count, 0);
+}
+
+#[actix_rt::test]
+async fn holder_metrics_can_leave_out_non_circulating_holders() {
+    let app = spawn_app().await;
+    let holders = serde_json::json!({
+        "network": "bsc",
+        "token_name": "some coin",
+        "contract_address": CONTRACT_ADDRESS,
+        "holders": [
+            {"holder_address": BURN_ADDRESS, "place": 1, "amount": "500"},
+            {"holder_address": WHALE, "place": 2, "amount": "300"},
+            {"holder_address": SHRIMP, "place": 3, "amount": "100"},
+            {"holder_address": DUMPER, "place": 4, "amount": "100"}
+        ]
+    });
+    let descriptions = serde_json::json!({
+        "network_name": "bsc",
+        "holder_descriptions": [
+            {"holder_address": BURN_ADDRESS, "contract_address": CONTRACT_ADDRESS, "address_types": ["dead_address"]}
+        ]
+    });
+    assert_eq!(200, app.post_holders(&holders).await.status().as_u16());
+    assert_eq!(
+        200,
+        app.post_holder_descriptions(&descriptions)
+            .await
+            .status()
+            .as_u16()
+    );
+
+    let query = format!("network=bsc&contract_address={}", CONTRACT_ADDRESS);
+    let response = app.get_holder_metrics(&query).await;
+    assert_eq!(200, response.status().as_u16());
+    let parsed: Value = response.json().await.unwrap();
+    let metrics = &parsed["data"][0]["metrics"];
+    assert_eq!(metrics["holder_count"], 4);
+    assert_eq!(metrics["top_1_share"], 0.5);
+
+    let response = app
+        .get_holder_metrics(&format!("{}&exclude_non_circulating=true", query))
+        .await;
+    assert_eq!(200, response.status().as_u16());
+    let parsed: Value = response.json().await.unwrap();
+    let metrics = &parsed["data"][0]["metrics"];
+    assert_eq!(metrics["holder_count"], 3);
+    assert_eq!(metrics["top_1_share"], 0.6);
+}
@@ -0,0 +1,188 @@
+use crate::helpers::spawn_app;
+use serde_json::Value;
+
+const CONTRACT_ADDRESS: &str = "0x8076c74c5e3f5852037f31ff0093eeb8c8add8d3";
+const WHALE: &str = "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed";
+const DUMPER: &str = "0xfb6916095ca1df60bb79ce92ce3ea74c37c5d359";
+
+fn explorer_export() -> String {
+    format!(
+        "\"Rank\",\"Address\",\"Quantity\",\"Percentage\"\r\n\
+        \"1\",\"{}\",\"1,000,000\",\"50.00%\"\r\n\
+        \"2\",\"someholderaddress\",\"500\",\"0.02%\"\r\n\
+        \"3\",\"{}\",\"250.5\",\"12.50%\"\r\n",
+        WHALE, DUMPER
+    )
+}
+
+fn import_query() -> String {
+    format!(
+        "network=bsc&token_name=some%20coin&contract_address={}&source=bscscan",
+        CONTRACT_ADDRESS
+    )
+}
+
+async fn imported_snapshot(app: &crate::helpers::TestApp, response: reqwest::Response) -> Value {
+    assert_eq!(200, response.status().as_u16());
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["imported"], 2);
+    assert_eq!(
+        body["errors"],
+        serde_json::json!([{"row": 3, "error": "someholderaddress is not a valid bsc address: expected 0x followed by 40 hexadecimal characters."}])
+    );
+    app.get_holder_snapshot(body["snapshot_id"].as_i64().unwrap())
+        .await
+        .json()
+        .await
+        .unwrap()
+}
+
+fn holders_of(snapshot: &Value) -> Vec<(String, i64)> {
+    snapshot["holders"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|h| {
+            (
+                h["holder_address"].as_str().unwrap().to_string(),
+                h["place"].as_i64().unwrap(),
+            )
+        })
+        .collect()
+}
+
+#[actix_rt::test]
+async fn a_csv_upload_imports_the_valid_rows_and_reports_the_rest() {
+    let app = spawn_app().await;
+
+    let response = app
+        .import_holders(&import_query(), "text/csv", explorer_export())
+        .await;
+    let snapshot = imported_snapshot(&app, response).await;
+
+    assert_eq!(snapshot["source"], "bscscan");
+    assert_eq!(
+        holders_of(&snapshot),
+        vec![(WHALE.to_string(), 1), (DUMPER.to_string(), 3)]
+    );
+}
+
+#[actix_rt::test]
+async fn a_holder_listed_twice_is_reported_as_a_row_error() {
+    let app = spawn_app().await;
+    let csv = format!(
+        "Rank,Address,Quantity\n1,{},1000\n2,{},500\n3,{},10\n",
+        WHALE, DUMPER, WHALE
+    );
+
+    let response = app.import_holders(&import_query(), "text/csv", csv).await;
+
+    assert_eq!(200, response.status().as_u16());
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["imported"], 2);
+    assert_eq!(
+        body["errors"],
+        serde_json::json!([{"row": 4, "error": format!("{} is listed more than once.", WHALE)}])
+    );
+}
+
+#[actix_rt::test]
+async fn a_multipart_upload_is_imported() {
+    let app = spawn_app().await;
+    let boundary = "holders-boundary";
+    let mut body = String::new();
+    for (name, value) in [
+        ("network", "bsc"),
+        ("token_name", "some coin"),
+        ("contract_address", CONTRACT_ADDRESS),
+    ] {
+        body.push_str(&format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+            boundary, name, value
+        ));
+    }
+    body.push_str(&format!(
+        "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"holders.csv\"\r\nContent-Type: text/csv\r\n\r\n{}\r\n--{}--\r\n",
+        boundary,
+        explorer_export(),
+        boundary
+    ));
+
+    let response = app
+        .import_holders(
+            "",
+            &format!("multipart/form-data; boundary={}", boundary),
+            body,
+        )
+        .await;
+    let snapshot = imported_snapshot(&app, response).await;
+
+    assert_eq!(snapshot["source"], Value::Null);
+    assert_eq!(holders_of(&snapshot).len(), 2);
+}
+
+#[actix_rt::test]
+async fn an_upload_without_valid_rows_is_rejected_with_its_errors() {
+    let app = spawn_app().await;
+    let csv = "Rank,Address,Quantity\n1,someholderaddress,10\n".to_string();
+
+    let response = app.import_holders(&import_query(), "text/csv", csv).await;
+
+    assert_eq!(400, response.status().as_u16());
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["imported"], 0);
+    assert_eq!(body["errors"][0]["row"], 2);
+    let saved = sqlx::query!(r#"SELECT COUNT(*) AS "count!" FROM holder_snapshots"#)
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to count snapshots.");
+    assert_eq!(saved.count, 0);
+}
+
+#[actix_rt::test]
+async fn import_holders_returns_a_400_for_invalid_uploads() {
+    let app = spawn_app().await;
+    let test_cases = vec![
+        (
+            format!("token_name=coin&contract_address={}", CONTRACT_ADDRESS),
+            "text/csv",
+            explorer_export(),
+            "a missing network",
+        ),
+        (
+            import_query(),
+            "text/csv",
+            "Rank,Percentage\n1,50%\n".to_string(),
+            "a CSV without an address column",
+        ),
+        (
+            import_query(),
+            "application/json",
+            explorer_export(),
+            "an unsupported content type",
+        ),
+        (
+            String::new(),
+            "multipart/form-data; boundary=b",
+            "--b\r\nContent-Disposition: form-data; name=\"network\"\r\n\r\nbsc\r\n--b--\r\n"
+                .to_string(),
+            "a multipart upload without a file",
+        ),
+        (
+            import_query(),
+            "multipart/form-data",
+            "--b\r\nContent-Disposition: form-data; name=\"file\"\r\n\r\n".to_string(),
+            "a multipart upload without a boundary",
+        ),
+    ];
+
+    for (query, content_type, body, description) in test_cases {
+        let response = app.import_holders(&query, content_type, body).await;
+        assert_eq!(
+            400,
+            response.status().as_u16(),
+            "The API did not fail with 400 Bad Request for {}.",
+            description
+        );
+    }
+}
@@ -0,0 +1,172 @@
+use crate::helpers::spawn_app;
+use serde_json::Value;
+
+const CONTRACT_ADDRESS: &str = "0x8076c74c5e3f5852037f31ff0093eeb8c8add8d3";
+const WHALE: &str = "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed";
+
+fn holders_body(amount: &str) -> Value {
+    serde_json::json!({
+        "network": "bsc",
+        "token_name": "some coin",
+        "contract_address": CONTRACT_ADDRESS,
+        "holders": [{"holder_address": WHALE, "place": 1, "amount": amount}]
+    })
+}
+
+async fn saved_holder_rows(app: &crate::helpers::TestApp) -> i64 {
+    sqlx::query!(r#"SELECT COUNT(*) AS "count!" FROM holder_totals"#)
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to count saved holders.")
+        .count
+}
+
+#[actix_rt::test]
+async fn a_retried_request_replays_the_saved_response() {
+    let app = spawn_app().await;
+    let body = holders_body("1,000");
+
+    let first = app
+        .post_holders_with_idempotency_key(&body, "crawl-42")
+        .await;
+    assert_eq!(200, first.status().as_u16());
+    let first: Value = first.json().await.unwrap();
+    let second = app
+        .post_holders_with_idempotency_key(&body, "crawl-42")
+        .await;
+    assert_eq!(200, second.status().as_u16());
+    let second: Value = second.json().await.unwrap();
+
+    assert_eq!(first, second);
+    assert_eq!(saved_holder_rows(&app).await, 1);
+}
+
+#[actix_rt::test]
+async fn requests_without_a_key_or_with_different_keys_all_run() {
+    let app = spawn_app().await;
+    let body = holders_body("1,000");
+
+    assert_eq!(200, app.post_holders(&body).await.status().as_u16());
+    assert_eq!(200, app.post_holders(&body).await.status().as_u16());
+    for key in ["crawl-1", "crawl-2"] {
+        let response = app.post_holders_with_idempotency_key(&body, key).await;
+        assert_eq!(200, response.status().as_u16());
+    }
+
+    assert_eq!(saved_holder_rows(&app).await, 4);
+}
+
+#[actix_rt::test]
+async fn api_keys_do_not_share_idempotency_keys() {
+    let app = spawn_app().await;
+    let minted: Value = app
+        .post_api_keys(
+            &serde_json::json!({"name": "another crawler", "scopes": ["ingest:holders"]}),
+            &app.admin_api_key,
+        )
+        .await
+        .json()
+        .await
+        .unwrap();
+    let other_api_key = minted["api_key"].as_str().unwrap();
+
+    let first = app
+        .post_holders_with_idempotency_key(&holders_body("1,000"), "crawl-42")
+        .await;
+    assert_eq!(200, first.status().as_u16());
+    let second = reqwest::Client::new()
+        .post(&format!("{}/holders", &app.address))
+        .header("Idempotency-Key", "crawl-42")
+        .header("X-Api-Key", other_api_key)
+        .json(&holders_body("2,000"))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(200, second.status().as_u16());
+
+    assert_eq!(saved_holder_rows(&app).await, 2);
+}
+
+#[actix_rt::test]
+async fn concurrent_requests_with_the_same_key_run_once() {
+    let app = spawn_app().await;
+    let body = holders_body("1,000");
+
+    let (first, second) = tokio::join!(
+        app.post_holders_with_idempotency_key(&body, "crawl-42"),
+        app.post_holders_with_idempotency_key(&body, "crawl-42")
+    );
+    let statuses = [first.status().as_u16(), second.status().as_u16()];
+
+    assert!(statuses.contains(&200));
+    assert!(statuses.iter().all(|s| *s == 200 || *s == 409));
+    assert_eq!(saved_holder_rows(&app).await, 1);
+}
+
+#[actix_rt::test]
+async fn a_key_reused_for_a_different_request_is_rejected() {
+    let app = spawn_app().await;
+
+    let response = app
+        .post_holders_with_idempotency_key(&holders_body("1,000"), "crawl-42")
+        .await;
+    assert_eq!(200, response.status().as_u16());
+    let response = app
+        .post_holders_with_idempotency_key(&holders_body("2,000"), "crawl-42")
+        .await;
+
+    assert_eq!(422, response.status().as_u16());
+    assert_eq!(saved_holder_rows(&app).await, 1);
+}
+
+#[actix_rt::test]
+async fn an_invalid_request_is_replayed_too() {
+    let app = spawn_app().await;
+    let body = serde_json::json!({"network": "bsc"});
+
+    for _ in 0..2 {
+        let response = app
+            .post_holders_with_idempotency_key(&body, "crawl-42")
+            .await;
+        assert_eq!(400, response.status().as_u16());
+    }
+}
+
+#[actix_rt::test]
+async fn scammer_reports_honour_the_key() {
+    let app = spawn_app().await;
+    let body = "address=0x18ce832a86C207eeC301437f3dE05Aa11fd79fc1&notes=honeypot&network_of_scammed_token=eth&scammed_contract_address=0xB91f05B798f8A010A1BDdbFf75dC3D106dC84B50";
+
+    for _ in 0..2 {
+        let response = reqwest::Client::new()
+            .post(&format!("{}/scam/creators", &app.address))
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .header("Idempotency-Key", "report-7")
+            .header("X-Api-Key", &app.api_key)
+            .body(body)
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(200, response.status().as_u16());
+    }
+
+    let saved = sqlx::query!(r#"SELECT COUNT(*) AS "count!" FROM scam_token_creators"#)
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to count saved scammers.");
+    assert_eq!(saved.count, 1);
+}
+
+#[actix_rt::test]
+async fn an_oversized_body_is_rejected_before_it_is_buffered() {
+    let app = spawn_app().await;
+    let mut body = holders_body("1,000");
+    body["token_name"] = Value::String("x".repeat(3 * 1024 * 1024));
+
+    let response = app
+        .post_holders_with_idempotency_key(&body, "crawl-42")
+        .await;
+
+    assert_eq!(413, response.status().as_u16());
+    assert_eq!(saved_holder_rows(&app).await, 0);
+}
@@ -147,3 +147,58 @@ async fn register_legit_token_creator_fails_if_there_is_a_fatal_database_error()
     let response_post = app.post_legit_token_creators(body.into()).await;
     assert_eq!(response_post.status().as_u16(), 500);
 }
+
+#[actix_rt::test]
+async fn legit_token_creators_can_be_filtered_by_network() {
+    let app = spawn_app().await;
+    for network in ["eth", "ftm"] {
+        let body = format!(
+            "address={}&notes={}&network_of_legit_token={}&legit_contract_address={}",
+            ADDRESS, NOTES, network, LEGIT_TOKEN_CONTRACT_ADDRESS
+        );
+        assert_eq!(
+            200,
+            app.post_legit_token_creators(body).await.status().as_u16()
+        );
+    }
+
+    let response_get = app
+        .get_legit_token_creators(&format!("token_creator_address={}", ADDRESS))
+        .await;
+    let parsed = response_get
+        .json::<LegitTokenCreatorResponse>()
+        .await
+        .unwrap();
+    assert_eq!(parsed.data.len(), 2);
+
+    let response_get = app
+        .get_legit_token_creators(&format!("token_creator_address={}&network=fantom", ADDRESS))
+        .await;
+    let parsed = response_get
+        .json::<LegitTokenCreatorResponse>()
+        .await
+        .unwrap();
+    assert_eq!(parsed.data.len(), 1);
+    assert_eq!(parsed.data[0].network_of_legit_token, "ftm");
+}
+
+#[actix_rt::test]
+async fn repeated_legit_token_creator_reports_store_the_token_once() {
+    // Arrange
+    let app = spawn_app().await;
+    let body = format!(
+        "address={}&notes={}&network_of_legit_token={}&legit_contract_address={}",
+        ADDRESS, NOTES, NETWORK_OF_LEGIT_TOKEN, LEGIT_TOKEN_CONTRACT_ADDRESS
+    );
+    // Act
+    for _ in 0..2 {
+        let response = app.post_legit_token_creators(body.clone()).await;
+        assert_eq!(200, response.status().as_u16());
+    }
+    // Assert
+    let saved = sqlx::query!(r#"SELECT COUNT(*) AS "count!" FROM legit_tokens"#)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(1, saved.count);
+}
@@ -0,0 +1,87 @@
+use crate::helpers::spawn_app;
+
+fn session_cookie(response: &reqwest::Response) -> Option<String> {
+    response
+        .headers()
+        .get_all("Set-Cookie")
+        .iter()
+        .filter_map(|h| h.to_str().ok())
+        .find(|h| h.starts_with("session="))
+        .map(|h| h.to_string())
+}
+
+#[actix_rt::test]
+async fn wrong_credentials_redirect_back_to_the_form_with_an_error() {
+    // Arrange
+    let app = spawn_app().await;
+    // Act
+    let response = app
+        .post_login(&app.test_user.username, "wrong password")
+        .await;
+    // Assert
+    assert_eq!(303, response.status().as_u16());
+    assert_eq!("/login?error=credentials", response.headers()["Location"]);
+    assert!(session_cookie(&response).is_none());
+    let form = reqwest::get(format!("{}/login?error=credentials", &app.address))
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert!(form.contains("Wrong username or password."));
+}
+
+#[actix_rt::test]
+async fn a_session_can_publish_until_logout() {
+    // Arrange
+    let app = spawn_app().await;
+    let client = app.client_without_redirects();
+    let body = serde_json::json!({
+        "title": "Newsletter title",
+        "content": {"text": "plain text", "html": "<p>HTML</p>"}
+    });
+    // Act - log in
+    let response = app
+        .post_login(&app.test_user.username, &app.test_user.password)
+        .await;
+    assert_eq!(303, response.status().as_u16());
+    let set_cookie = session_cookie(&response).expect("No session cookie was set.");
+    assert!(set_cookie.contains("HttpOnly"));
+    let cookie = set_cookie.split(';').next().unwrap().to_string();
+    // Assert - the session is recognised and may publish
+    let form = client
+        .get(format!("{}/login", &app.address))
+        .header("Cookie", &cookie)
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert!(form.contains(&format!("Signed in as {}", app.test_user.username)));
+    let response = client
+        .post(format!("{}/newsletters", &app.address))
+        .header("Cookie", &cookie)
+        .json(&body)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(202, response.status().as_u16());
+    // Act - log out
+    let response = client
+        .post(format!("{}/logout", &app.address))
+        .header("Cookie", &cookie)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(303, response.status().as_u16());
+    // Assert - the old cookie no longer works
+    let response = client
+        .post(format!("{}/newsletters", &app.address))
+        .header("Cookie", &cookie)
+        .json(&body)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(401, response.status().as_u16());
+}
@@ -1,9 +1,22 @@
+mod address_report;
+mod api_keys;
+mod creator_tokens;
+mod email_webhooks;
 mod health_check;
 mod helpers;
+mod holder_description_history;
 mod holder_descriptions;
 mod holders;
+mod holders_import;
+mod idempotency;
 mod legit;
+mod login;
 mod newsletters;
+mod scam_tokens;
 mod scams;
 mod subscriptions;
 mod subscriptions_confirm;
+mod token_score;
+mod tokens;
+mod unsubscribe;
+mod users;
@@ -1,4 +1,7 @@
-use crate::helpers::{spawn_app, ConfirmationLinks, TestApp};
+use crate::helpers::{
+    create_confirmed_subscriber, create_unconfirmed_subscriber, spawn_app, TestApp,
+};
+use whale_watcher_server::issue_delivery_worker::MAX_ATTEMPTS;
 use wiremock::matchers::{any, method, path};
 use wiremock::{Mock, ResponseTemplate};
 
@@ -23,50 +26,11 @@ async fn newsletters_are_not_delivered_to_unconfirmed_subscribers() {
     });
     let response = app.post_newsletters(newsletter_request_body).await;
     // Assert
-    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.status().as_u16(), 202);
+    app.dispatch_all_pending_emails().await;
     // Mock verifies on Drop that we haven't sent the newsletter email
 }
 
-/// Use the public API of the application under test to create /// an unconfirmed subscriber.
-async fn create_unconfirmed_subscriber(app: &TestApp) -> ConfirmationLinks {
-    let body = "name=le%20guin&email=ursula_le_guin%40gmail.com";
-
-    let _mock_guard = Mock::given(path("/email"))
-        .and(method("POST"))
-        .respond_with(ResponseTemplate::new(200))
-        .named("Create unconfirmed subscriber")
-        .expect(1)
-        .mount_as_scoped(&app.email_server)
-        .await;
-
-    app.post_subscriptions(body.into())
-        .await
-        .error_for_status()
-        .unwrap();
-
-    // We now inspect the requests received by the mock Postmark server // to retrieve the confirmation link and return it
-    let email_request = &app
-        .email_server
-        .received_requests()
-        .await
-        .unwrap()
-        .pop()
-        .unwrap();
-
-    app.get_confirmation_links(&email_request)
-}
-
-async fn create_confirmed_subscriber(app: &TestApp) {
-    // We can then reuse the same helper and just add
-    // an extra step to actually call the confirmation link!
-    let confirmation_link = create_unconfirmed_subscriber(app).await;
-    reqwest::get(confirmation_link.html)
-        .await
-        .unwrap()
-        .error_for_status()
-        .unwrap();
-}
-
 #[actix_rt::test]
 async fn newsletters_are_delivered_to_confirmed_subscribers() {
     // Arrange
@@ -88,7 +52,8 @@ async fn newsletters_are_delivered_to_confirmed_subscribers() {
     });
     let response = app.post_newsletters(newsletter_request_body).await;
     // Assert
-    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.status().as_u16(), 202);
+    app.dispatch_all_pending_emails().await;
     // Mock verifies on Drop that we have sent the newsletter email
 }
 
@@ -122,3 +87,239 @@ async fn newsletters_returns_400_for_invalid_data() {
         );
     }
 }
+
+fn newsletter_body() -> serde_json::Value {
+    serde_json::json!({
+        "title": "Newsletter title",
+        "content": {
+            "text": "Newsletter body as plain text",
+            "html": "<p>Newsletter body as HTML</p>",
+        }
+    })
+}
+
+#[actix_rt::test]
+async fn requests_missing_authorization_are_rejected() {
+    // Arrange
+    let app = spawn_app().await;
+    // Act
+    let response = reqwest::Client::new()
+        .post(format!("{}/newsletters", &app.address))
+        .json(&newsletter_body())
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    // Assert
+    assert_eq!(401, response.status().as_u16());
+    assert_eq!(
+        r#"Basic realm="publish""#,
+        response.headers()["WWW-Authenticate"]
+    );
+}
+
+#[actix_rt::test]
+async fn unknown_users_and_wrong_passwords_are_rejected() {
+    // Arrange
+    let app = spawn_app().await;
+    let test_cases = vec![
+        ("somebody", app.test_user.password.as_str(), "unknown user"),
+        (
+            app.test_user.username.as_str(),
+            "wrong password",
+            "wrong password",
+        ),
+    ];
+    for (username, password, description) in test_cases {
+        // Act
+        let response = reqwest::Client::new()
+            .post(format!("{}/newsletters", &app.address))
+            .basic_auth(username, Some(password))
+            .json(&newsletter_body())
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        // Assert
+        assert_eq!(
+            401,
+            response.status().as_u16(),
+            "The API did not reject a {}.",
+            description
+        );
+    }
+}
+
+#[actix_rt::test]
+async fn api_keys_need_the_publish_scope() {
+    // Arrange
+    let app = spawn_app().await;
+    let crawler: serde_json::Value = app
+        .post_api_keys(
+            &serde_json::json!({"name": "crawler", "scopes": ["ingest:holders"]}),
+            &app.admin_api_key,
+        )
+        .await
+        .json()
+        .await
+        .unwrap();
+    let publish_with_key = |api_key: String| {
+        reqwest::Client::new()
+            .post(format!("{}/newsletters", &app.address))
+            .header("X-Api-Key", api_key)
+            .json(&newsletter_body())
+            .send()
+    };
+    // Act
+    let without_scope = publish_with_key(crawler["api_key"].as_str().unwrap().to_string())
+        .await
+        .unwrap();
+    let with_scope = publish_with_key(app.api_key.clone()).await.unwrap();
+    let unknown = publish_with_key("not-a-key".to_string()).await.unwrap();
+    // Assert
+    assert_eq!(403, without_scope.status().as_u16());
+    assert!(without_scope.headers().get("WWW-Authenticate").is_none());
+    assert_eq!(202, with_scope.status().as_u16());
+    assert_eq!(401, unknown.status().as_u16());
+    assert_eq!(
+        r#"Basic realm="publish""#,
+        unknown.headers()["WWW-Authenticate"]
+    );
+}
+
+/// Makes every queued delivery due now, as if its backoff had passed.
+async fn skip_backoff(app: &TestApp) {
+    sqlx::query!("UPDATE issue_delivery_queue SET execute_after = now() - interval '1 second';")
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+}
+
+#[actix_rt::test]
+async fn publishing_accepts_the_issue_before_postmark_answers() {
+    // Arrange
+    let app = spawn_app().await;
+    create_confirmed_subscriber(&app).await;
+    let _failing = Mock::given(path("/email"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(1)
+        .mount_as_scoped(&app.email_server)
+        .await;
+    // Act
+    let response = app.post_newsletters(newsletter_body()).await;
+    // Assert
+    assert_eq!(202, response.status().as_u16());
+    let issue: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(1, issue["queued"]);
+    let newsletter_issue_id = issue["newsletter_issue_id"].as_str().unwrap();
+    let deliveries: serde_json::Value = app
+        .get_newsletter_deliveries(newsletter_issue_id)
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(1, deliveries["pending"]);
+    assert_eq!("Newsletter title", deliveries["title"]);
+
+    // The first attempt fails and is scheduled again later.
+    app.dispatch_all_pending_emails().await;
+    let deliveries: serde_json::Value = app
+        .get_newsletter_deliveries(newsletter_issue_id)
+        .await
+        .json()
+        .await
+        .unwrap();
+    let delivery = &deliveries["deliveries"][0];
+    assert_eq!("pending", delivery["status"]);
+    assert_eq!(1, delivery["n_retries"]);
+    assert!(delivery["last_error"].is_string());
+}
+
+#[actix_rt::test]
+async fn failed_deliveries_are_retried_until_they_succeed() {
+    // Arrange
+    let app = spawn_app().await;
+    create_confirmed_subscriber(&app).await;
+    let issue: serde_json::Value = app
+        .post_newsletters(newsletter_body())
+        .await
+        .json()
+        .await
+        .unwrap();
+    let newsletter_issue_id = issue["newsletter_issue_id"].as_str().unwrap();
+    {
+        let _failing = Mock::given(path("/email"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(1)
+            .mount_as_scoped(&app.email_server)
+            .await;
+        app.dispatch_all_pending_emails().await;
+    }
+    Mock::given(path("/email"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+    // Act
+    skip_backoff(&app).await;
+    app.dispatch_all_pending_emails().await;
+    // Assert
+    let deliveries: serde_json::Value = app
+        .get_newsletter_deliveries(newsletter_issue_id)
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(1, deliveries["delivered"]);
+    let delivery = &deliveries["deliveries"][0];
+    assert_eq!("delivered", delivery["status"]);
+    assert_eq!(1, delivery["n_retries"]);
+    assert!(delivery["last_error"].is_null());
+    assert!(delivery["delivered_on"].is_string());
+}
+
+#[actix_rt::test]
+async fn deliveries_fail_once_the_attempts_run_out() {
+    // Arrange
+    let app = spawn_app().await;
+    create_confirmed_subscriber(&app).await;
+    Mock::given(path("/email"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(MAX_ATTEMPTS as u64)
+        .mount(&app.email_server)
+        .await;
+    let issue: serde_json::Value = app
+        .post_newsletters(newsletter_body())
+        .await
+        .json()
+        .await
+        .unwrap();
+    // Act
+    for _ in 0..MAX_ATTEMPTS + 1 {
+        skip_backoff(&app).await;
+        app.dispatch_all_pending_emails().await;
+    }
+    // Assert
+    let deliveries: serde_json::Value = app
+        .get_newsletter_deliveries(issue["newsletter_issue_id"].as_str().unwrap())
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(1, deliveries["failed"]);
+    assert_eq!(MAX_ATTEMPTS, deliveries["deliveries"][0]["n_retries"]);
+}
+
+#[actix_rt::test]
+async fn deliveries_of_unknown_issues_are_not_found() {
+    let app = spawn_app().await;
+    let response = app
+        .get_newsletter_deliveries("8c6b3c1e-4a5b-4a9e-9f51-2d6f0c1f2b7a")
+        .await;
+    assert_eq!(404, response.status().as_u16());
+    let response = reqwest::get(format!(
+        "{}/newsletters/8c6b3c1e-4a5b-4a9e-9f51-2d6f0c1f2b7a",
+        &app.address
+    ))
+    .await
+    .unwrap();
+    assert_eq!(401, response.status().as_u16());
+}
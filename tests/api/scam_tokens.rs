@@ -0,0 +1,149 @@
+use crate::helpers::spawn_app;
+
+const ADDRESS: &str = "0xB91f05B798f8A010A1BDdbFf75dC3D106dC84B50";
+const NOTES: &str = "ladytigercat token";
+const SCAM_CREATOR_NETWORK: &str = "eth";
+const SCAM_CREATOR_ADDRESS: &str = "0x18ce832a86C207eeC301437f3dE05Aa11fd79fc1";
+const SCAM_TYPE: &str = "honeypot";
+
+#[derive(serde::Deserialize, Debug)]
+struct ScamTokenResponse {
+    data: Vec<ScamToken>,
+}
+
+#[derive(serde::Deserialize, Debug)]
+struct ScamToken {
+    address: String,
+    notes: String,
+    scam_creator_network: String,
+    scam_creator_address: String,
+    scam_type: String,
+}
+
+#[actix_rt::test]
+async fn register_scam_token_returns_a_200_for_valid_form_data() {
+    // Arrange
+    let app = spawn_app().await;
+    let body = format!(
+        "address={}&notes={}&scam_creator_network={}&scam_creator_address={}&scam_type={}",
+        ADDRESS, NOTES, SCAM_CREATOR_NETWORK, SCAM_CREATOR_ADDRESS, "honey pot"
+    );
+    let query_params = format!("contract_address={}", ADDRESS);
+    // Act
+    let response_post = app.post_scam_tokens(body).await;
+
+    // Assert
+    assert_eq!(200, response_post.status().as_u16());
+    let response_get = app.get_scam_tokens(&query_params).await;
+    assert_eq!(200, response_get.status().as_u16());
+
+    let parsed = response_get.json::<ScamTokenResponse>().await.unwrap();
+    assert_eq!(parsed.data.len(), 1);
+    assert_eq!(parsed.data[0].address, ADDRESS);
+    assert_eq!(parsed.data[0].notes, NOTES);
+    assert_eq!(parsed.data[0].scam_creator_network, SCAM_CREATOR_NETWORK);
+    assert_eq!(parsed.data[0].scam_creator_address, SCAM_CREATOR_ADDRESS);
+    assert_eq!(parsed.data[0].scam_type, SCAM_TYPE);
+}
+
+#[actix_rt::test]
+async fn register_scam_token_returns_a_400_when_data_is_missing() {
+    let app = spawn_app().await;
+    let test_cases = vec![
+        (
+            format!(
+                "notes={}&scam_creator_network={}&scam_creator_address={}&scam_type={}",
+                NOTES, SCAM_CREATOR_NETWORK, SCAM_CREATOR_ADDRESS, SCAM_TYPE
+            ),
+            "missing the address",
+        ),
+        (
+            format!(
+                "address={}&scam_creator_address={}&scam_type={}",
+                ADDRESS, SCAM_CREATOR_ADDRESS, SCAM_TYPE
+            ),
+            "missing scam_creator_network",
+        ),
+        (
+            format!(
+                "address={}&scam_creator_network={}&scam_type={}",
+                ADDRESS, SCAM_CREATOR_NETWORK, SCAM_TYPE
+            ),
+            "missing scam_creator_address",
+        ),
+        (
+            format!(
+                "address={}&scam_creator_network={}&scam_creator_address={}",
+                ADDRESS, SCAM_CREATOR_NETWORK, SCAM_CREATOR_ADDRESS
+            ),
+            "missing scam_type",
+        ),
+        ("".to_string(), "no params"),
+    ];
+
+    for (invalid_body, error_message) in test_cases {
+        let response = app.post_scam_tokens(invalid_body).await;
+
+        assert_eq!(
+            400,
+            response.status().as_u16(),
+            "The API did not fail with 400 Bad Request when the payload was {}.",
+            error_message
+        );
+    }
+}
+
+#[actix_rt::test]
+async fn register_scam_token_returns_a_400_when_fields_are_present_but_invalid() {
+    let app = spawn_app().await;
+    let test_cases = vec![
+        (
+            format!(
+                "address=&scam_creator_network={}&scam_creator_address={}&scam_type={}",
+                SCAM_CREATOR_NETWORK, SCAM_CREATOR_ADDRESS, SCAM_TYPE
+            ),
+            "empty address",
+        ),
+        (
+            format!(
+                "address={}&scam_creator_network=somesuperchain&scam_creator_address={}&scam_type={}",
+                ADDRESS, SCAM_CREATOR_ADDRESS, SCAM_TYPE
+            ),
+            "unsupported scam_creator_network",
+        ),
+        (
+            format!(
+                "address={}&scam_creator_network={}&scam_creator_address={}&scam_type=pump",
+                ADDRESS, SCAM_CREATOR_NETWORK, SCAM_CREATOR_ADDRESS
+            ),
+            "unsupported scam_type",
+        ),
+    ];
+
+    for (body, description) in test_cases {
+        let response = app.post_scam_tokens(body).await;
+
+        assert_eq!(
+            400,
+            response.status().as_u16(),
+            "The API did not return a 400 Bad Request when the payload was {}.",
+            description
+        );
+    }
+}
+
+#[actix_rt::test]
+async fn register_scam_token_fails_if_there_is_a_fatal_database_error() {
+    let app = spawn_app().await;
+    let body = format!(
+        "address={}&notes={}&scam_creator_network={}&scam_creator_address={}&scam_type={}",
+        ADDRESS, NOTES, SCAM_CREATOR_NETWORK, SCAM_CREATOR_ADDRESS, SCAM_TYPE
+    );
+
+    sqlx::query!("ALTER TABLE scam_tokens DROP COLUMN address",)
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+    let response_post = app.post_scam_tokens(body).await;
+    assert_eq!(response_post.status().as_u16(), 500);
+}
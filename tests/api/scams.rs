@@ -9,6 +9,7 @@ const SCAMMED_TOKEN_ADDRESS: &str = "0xB91f05B798f8A010A1BDdbFf75dC3D106dC84B50"
 #[derive(serde::Deserialize, Debug)]
 struct ScammerResponse {
     data: Vec<Scammer>,
+    by_network: std::collections::BTreeMap<String, Vec<Scammer>>,
 }
 
 #[derive(serde::Deserialize, Debug)]
@@ -148,3 +149,85 @@ async fn register_scammer_fails_if_there_is_a_fatal_database_error() {
     let response_post = app.post_scam_creators(body.into()).await;
     assert_eq!(response_post.status().as_u16(), 500);
 }
+
+#[actix_rt::test]
+async fn get_scammers_finds_a_scammer_regardless_of_address_case() {
+    let app = spawn_app().await;
+    let body = format!(
+        "address={}&notes={}&network_of_scammed_token={}&scammed_contract_address={}",
+        ADDRESS, NOTES, NETWORK_OF_SCAMMED_TOKEN, SCAMMED_TOKEN_ADDRESS
+    );
+    assert_eq!(200, app.post_scam_creators(body).await.status().as_u16());
+    let lowercase_body = format!(
+        "address={}&notes={}&network_of_scammed_token={}&scammed_contract_address={}",
+        ADDRESS.to_lowercase(),
+        NOTES,
+        NETWORK_OF_SCAMMED_TOKEN,
+        SCAMMED_TOKEN_ADDRESS.to_lowercase()
+    );
+    assert_eq!(
+        200,
+        app.post_scam_creators(lowercase_body)
+            .await
+            .status()
+            .as_u16()
+    );
+
+    let saved = sqlx::query!("SELECT address, display_address FROM addresses")
+        .fetch_all(&app.db_pool)
+        .await
+        .expect("Failed to fetch saved addresses.");
+    assert_eq!(saved.len(), 2);
+    assert!(saved
+        .iter()
+        .any(|a| a.address == ADDRESS.to_lowercase() && a.display_address == ADDRESS));
+
+    let query_params = format!(
+        "token_creator_address={}",
+        ADDRESS.to_uppercase().replace("0X", "0x")
+    );
+    let response_get = app.get_scam_creators(&query_params).await;
+    assert_eq!(200, response_get.status().as_u16());
+    let parsed = response_get.json::<ScammerResponse>().await.unwrap();
+    assert_eq!(parsed.data.len(), 2);
+    assert_eq!(parsed.data[0].address, ADDRESS);
+    assert_eq!(
+        parsed.data[0].scammed_contract_address,
+        SCAMMED_TOKEN_ADDRESS
+    );
+}
+
+#[actix_rt::test]
+async fn a_scammer_is_found_on_every_evm_chain_unless_a_network_is_given() {
+    let app = spawn_app().await;
+    for network in ["eth", "bsc", "matic"] {
+        let body = format!(
+            "address={}&notes={}&network_of_scammed_token={}&scammed_contract_address={}",
+            ADDRESS, NOTES, network, SCAMMED_TOKEN_ADDRESS
+        );
+        assert_eq!(200, app.post_scam_creators(body).await.status().as_u16());
+    }
+
+    let response_get = app
+        .get_scam_creators(&format!("token_creator_address={}", ADDRESS))
+        .await;
+    assert_eq!(200, response_get.status().as_u16());
+    let parsed = response_get.json::<ScammerResponse>().await.unwrap();
+    assert_eq!(parsed.data.len(), 3);
+    let networks: Vec<&str> = parsed.by_network.keys().map(|n| n.as_str()).collect();
+    assert_eq!(networks, vec!["bsc", "eth", "matic"]);
+
+    let response_get = app
+        .get_scam_creators(&format!("token_creator_address={}&network=bsc", ADDRESS))
+        .await;
+    assert_eq!(200, response_get.status().as_u16());
+    let parsed = response_get.json::<ScammerResponse>().await.unwrap();
+    assert_eq!(parsed.data.len(), 1);
+    assert_eq!(parsed.data[0].network_of_scammed_token, "bsc");
+
+    // The address cannot be a Solana one.
+    let response_get = app
+        .get_scam_creators(&format!("token_creator_address={}&network=sol", ADDRESS))
+        .await;
+    assert_eq!(400, response_get.status().as_u16());
+}
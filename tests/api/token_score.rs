@@ -0,0 +1,118 @@
+use crate::helpers::spawn_app;
+use serde_json::Value;
+
+const NETWORK: &str = "eth";
+const CONTRACT_ADDRESS: &str = "0xB91f05B798f8A010A1BDdbFf75dC3D106dC84B50";
+const CREATOR_ADDRESS: &str = "0x18ce832a86C207eeC301437f3dE05Aa11fd79fc1";
+const WHALE: &str = "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed";
+const SHRIMP: &str = "0xfb6916095ca1df60bb79ce92ce3ea74c37c5d359";
+
+fn rule<'a>(score: &'a Value, name: &str) -> &'a Value {
+    score["rules"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|r| r["rule"] == name)
+        .unwrap()
+}
+
+#[actix_rt::test]
+async fn token_score_adds_up_the_triggered_rules() {
+    let app = spawn_app().await;
+    let scam_token = format!(
+        "address={}&notes=honeypot&scam_creator_network={}&scam_creator_address={}&scam_type=honeypot",
+        CONTRACT_ADDRESS, NETWORK, CREATOR_ADDRESS
+    );
+    assert_eq!(
+        200,
+        app.post_scam_tokens(scam_token).await.status().as_u16()
+    );
+    let holders = serde_json::json!({
+        "network": NETWORK,
+        "token_name": "some coin",
+        "contract_address": CONTRACT_ADDRESS,
+        "holders": [
+            {"holder_address": WHALE, "place": 1, "amount": "900"},
+            {"holder_address": SHRIMP, "place": 2, "amount": "100"}
+        ]
+    });
+    assert_eq!(200, app.post_holders(&holders).await.status().as_u16());
+
+    let response = app
+        .get_token_score(NETWORK, &CONTRACT_ADDRESS.to_lowercase())
+        .await;
+    assert_eq!(200, response.status().as_u16());
+
+    let score: Value = response.json().await.unwrap();
+    let creator = rule(&score, "creator_is_known_scammer");
+    assert_eq!(creator["triggered"], true);
+    assert!(creator["detail"]
+        .as_str()
+        .unwrap()
+        .contains(CREATOR_ADDRESS));
+    assert_eq!(rule(&score, "top_holder_share_above")["triggered"], true);
+    assert_eq!(
+        rule(&score, "no_liquidity_locker_in_top_holders")["triggered"],
+        true
+    );
+    assert_eq!(rule(&score, "tagged_holders_at_least")["triggered"], false);
+    let triggered_weights: f64 = score["rules"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|r| r["triggered"] == true)
+        .map(|r| r["weight"].as_f64().unwrap())
+        .sum();
+    assert_eq!(score["score"].as_f64().unwrap(), triggered_weights);
+}
+
+#[actix_rt::test]
+async fn a_liquidity_locker_among_the_top_holders_is_recognised() {
+    let app = spawn_app().await;
+    let description = serde_json::json!({
+        "network_name": NETWORK,
+        "holder_descriptions": [
+            {"holder_address": WHALE, "contract_address": CONTRACT_ADDRESS, "notes": "team.finance lock", "address_types": ["liquidity_locker"]}
+        ]
+    });
+    assert_eq!(
+        200,
+        app.post_holder_descriptions(&description)
+            .await
+            .status()
+            .as_u16()
+    );
+    let holders = serde_json::json!({
+        "network": NETWORK,
+        "token_name": "some coin",
+        "contract_address": CONTRACT_ADDRESS,
+        "holders": [
+            {"holder_address": WHALE, "place": 1, "amount": "900"},
+            {"holder_address": SHRIMP, "place": 2, "amount": "100"}
+        ]
+    });
+    assert_eq!(200, app.post_holders(&holders).await.status().as_u16());
+
+    let score: Value = app
+        .get_token_score(NETWORK, CONTRACT_ADDRESS)
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(
+        rule(&score, "no_liquidity_locker_in_top_holders")["triggered"],
+        false
+    );
+    // The locker is left out of the top holder share, leaving the shrimp as the only holder.
+    assert_eq!(rule(&score, "top_holder_share_above")["triggered"], true);
+    assert_eq!(rule(&score, "creator_is_known_scammer")["triggered"], false);
+}
+
+#[actix_rt::test]
+async fn token_score_returns_a_400_for_an_invalid_contract_address() {
+    let app = spawn_app().await;
+
+    let response = app.get_token_score(NETWORK, "not an address").await;
+
+    assert_eq!(400, response.status().as_u16());
+}
@@ -0,0 +1,166 @@
+use crate::helpers::spawn_app;
+use serde_json::Value;
+use sqlx::types::BigDecimal;
+use std::str::FromStr;
+
+const CONTRACT_ADDRESS: &str = "0x8076c74c5e3f5852037f31ff0093eeb8c8add8d3";
+const CREATOR: &str = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+const HOLDER: &str = "0xfb6916095ca1df60bb79ce92ce3ea74c37c5d359";
+
+fn safemoon_holders(network: &str) -> Value {
+    serde_json::json!({
+        "network": network,
+        "token_name": "SafeMoon",
+        "contract_address": CONTRACT_ADDRESS,
+        "holders": [{"holder_address": HOLDER, "place": 1, "amount": "10"}]
+    })
+}
+
+#[actix_rt::test]
+async fn a_registered_token_can_be_fetched_and_updated() {
+    let app = spawn_app().await;
+    let token = serde_json::json!({
+        "network": "bsc",
+        "contract_address": CONTRACT_ADDRESS,
+        "token_name": "Santa Coin",
+        "symbol": "SANTA",
+        "decimals": 9,
+        "creator_address": CREATOR,
+        "launched_on": "2021-12-01T12:00:00Z"
+    });
+    let response = app.post_token(&token).await;
+    assert_eq!(200, response.status().as_u16());
+
+    // Fields left out keep their values, the name is replaced.
+    let rename = serde_json::json!({
+        "network": "bsc",
+        "contract_address": CONTRACT_ADDRESS,
+        "token_name": "Santa Coin V2"
+    });
+    assert_eq!(200, app.post_token(&rename).await.status().as_u16());
+
+    let token: Value = app
+        .get_token("bsc", CONTRACT_ADDRESS)
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(token["token_name"], "Santa Coin V2");
+    assert_eq!(token["symbol"], "SANTA");
+    assert_eq!(token["decimals"], 9);
+    assert_eq!(token["creator_address"], CREATOR);
+    assert_eq!(token["launched_on"], "2021-12-01T12:00:00Z");
+
+    let by_creator: Value = app
+        .get_tokens(&format!("network=bsc&creator_address={}", CREATOR))
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(by_creator["data"].as_array().unwrap().len(), 1);
+}
+
+#[actix_rt::test]
+async fn tokens_with_the_same_name_on_different_networks_do_not_collide() {
+    let app = spawn_app().await;
+    for network in ["bsc", "eth"] {
+        let response = app.post_holders(&safemoon_holders(network)).await;
+        assert_eq!(200, response.status().as_u16());
+    }
+
+    let tokens: Value = app
+        .get_tokens("token_name=safemoon")
+        .await
+        .json()
+        .await
+        .unwrap();
+
+    let networks: Vec<&str> = tokens["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["network"].as_str().unwrap())
+        .collect();
+    assert_eq!(networks, vec!["bsc", "eth"]);
+}
+
+#[actix_rt::test]
+async fn holder_submissions_write_through_to_the_registry() {
+    let app = spawn_app().await;
+    let mut holders = safemoon_holders("bsc");
+    holders["symbol"] = "SAFEMOON".into();
+    holders["decimals"] = 9.into();
+    holders["total_supply"] = "1,000,000,000,000,000".into();
+    assert_eq!(200, app.post_holders(&holders).await.status().as_u16());
+
+    let token: Value = app
+        .get_token("bsc", CONTRACT_ADDRESS)
+        .await
+        .json()
+        .await
+        .unwrap();
+
+    assert_eq!(token["token_name"], "SafeMoon");
+    assert_eq!(token["symbol"], "SAFEMOON");
+    let decimal = |v: &Value| BigDecimal::from_str(v.as_str().unwrap()).unwrap();
+    assert_eq!(
+        decimal(&token["total_supply"]),
+        BigDecimal::from_str("1000000000000000").unwrap()
+    );
+    assert_eq!(
+        decimal(&token["raw_total_supply"]),
+        BigDecimal::from_str("1000000000000000000000000").unwrap()
+    );
+}
+
+#[actix_rt::test]
+async fn a_token_cannot_change_its_decimals() {
+    let app = spawn_app().await;
+    let mut token = serde_json::json!({
+        "network": "bsc",
+        "contract_address": CONTRACT_ADDRESS,
+        "token_name": "Santa Coin",
+        "decimals": 9
+    });
+    assert_eq!(200, app.post_token(&token).await.status().as_u16());
+
+    token["decimals"] = 18.into();
+    assert_eq!(400, app.post_token(&token).await.status().as_u16());
+    let mut holders = safemoon_holders("bsc");
+    holders["decimals"] = 18.into();
+    assert_eq!(400, app.post_holders(&holders).await.status().as_u16());
+}
+
+#[actix_rt::test]
+async fn tokens_returns_a_400_for_invalid_requests_and_a_404_for_unknown_tokens() {
+    let app = spawn_app().await;
+    let test_cases = vec![
+        ("", "no filters"),
+        (
+            "creator_address=0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed",
+            "a creator without a network",
+        ),
+        (
+            "network=bsc&creator_address=somecreator",
+            "an invalid creator address",
+        ),
+    ];
+    for (query, description) in test_cases {
+        let response = app.get_tokens(query).await;
+        assert_eq!(
+            400,
+            response.status().as_u16(),
+            "The API did not fail with 400 Bad Request for {}.",
+            description
+        );
+    }
+    let invalid_token = serde_json::json!({
+        "network": "bsc",
+        "contract_address": "somecontract",
+        "token_name": "Santa Coin"
+    });
+    assert_eq!(400, app.post_token(&invalid_token).await.status().as_u16());
+
+    let response = app.get_token("bsc", CONTRACT_ADDRESS).await;
+    assert_eq!(404, response.status().as_u16());
+}
@@ -0,0 +1,198 @@
+use crate::helpers::{
+    create_confirmed_subscriber, create_unconfirmed_subscriber, spawn_app, TestApp,
+};
+use serde_json::Value;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+async fn publish(app: &TestApp) -> Value {
+    app.post_newsletters(serde_json::json!({
+        "title": "Newsletter title",
+        "content": {"text": "plain text", "html": "<p>HTML</p>"}
+    }))
+    .await
+    .json()
+    .await
+    .unwrap()
+}
+
+/// Sends an issue to the confirmed subscriber and returns the Postmark request it made.
+async fn deliver_issue(app: &TestApp) -> Value {
+    let _mock_guard = Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount_as_scoped(&app.email_server)
+        .await;
+    publish(app).await;
+    app.dispatch_all_pending_emails().await;
+    let email_request = app
+        .email_server
+        .received_requests()
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    serde_json::from_slice(&email_request.body).unwrap()
+}
+
+fn header<'a>(email: &'a Value, name: &str) -> &'a str {
+    email["Headers"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|h| h["Name"] == name)
+        .and_then(|h| h["Value"].as_str())
+        .unwrap()
+}
+
+/// The token of the `List-Unsubscribe` link, e.g. `<http://.../unsubscribe?token=...>`.
+fn unsubscribe_token(email: &Value) -> String {
+    let link = header(email, "List-Unsubscribe");
+    link.trim_end_matches('>')
+        .split_once("token=")
+        .unwrap()
+        .1
+        .to_string()
+}
+
+async fn subscriber_status(app: &TestApp) -> String {
+    sqlx::query!("SELECT status FROM subscriptions")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap()
+        .status
+}
+
+async fn post_unsubscribe(app: &TestApp, token: &str) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(format!("{}/subscriptions/unsubscribe", &app.address))
+        .query(&[("token", token)])
+        .send()
+        .await
+        .unwrap()
+}
+
+#[actix_rt::test]
+async fn newsletters_carry_an_unsubscribe_link_and_header() {
+    // Arrange
+    let app = spawn_app().await;
+    create_confirmed_subscriber(&app).await;
+    // Act
+    let email = deliver_issue(&app).await;
+    // Assert
+    let link = header(&email, "List-Unsubscribe");
+    assert!(link.starts_with('<') && link.ends_with('>'));
+    let link = link.trim_start_matches('<').trim_end_matches('>');
+    assert!(link.contains("/subscriptions/unsubscribe?token="));
+    assert_eq!(
+        "List-Unsubscribe=One-Click",
+        header(&email, "List-Unsubscribe-Post")
+    );
+    assert!(email["TextBody"].as_str().unwrap().contains(link));
+    assert!(email["HtmlBody"].as_str().unwrap().contains(link));
+}
+
+#[actix_rt::test]
+async fn the_link_asks_before_unsubscribing() {
+    // Arrange
+    let app = spawn_app().await;
+    create_confirmed_subscriber(&app).await;
+    let token = unsubscribe_token(&deliver_issue(&app).await);
+    // Act
+    let response = reqwest::Client::new()
+        .get(format!("{}/subscriptions/unsubscribe", &app.address))
+        .query(&[("token", &token)])
+        .send()
+        .await
+        .unwrap();
+    // Assert
+    assert_eq!(200, response.status().as_u16());
+    let page = response.text().await.unwrap();
+    assert!(page.contains(r#"method="post""#));
+    assert!(page.contains(&token));
+    assert_eq!("confirmed", subscriber_status(&app).await);
+}
+
+#[actix_rt::test]
+async fn unsubscribed_subscribers_are_kept_but_never_mailed_again() {
+    // Arrange
+    let app = spawn_app().await;
+    create_confirmed_subscriber(&app).await;
+    let token = unsubscribe_token(&deliver_issue(&app).await);
+    // Act
+    let response = post_unsubscribe(&app, &token).await;
+    // Assert
+    assert_eq!(200, response.status().as_u16());
+    assert_eq!("unsubscribed", subscriber_status(&app).await);
+    assert_eq!(0, publish(&app).await["queued"]);
+    // Unsubscribing twice is fine.
+    assert_eq!(200, post_unsubscribe(&app, &token).await.status().as_u16());
+}
+
+#[actix_rt::test]
+async fn mail_clients_can_unsubscribe_in_one_click() {
+    // Arrange
+    let app = spawn_app().await;
+    create_confirmed_subscriber(&app).await;
+    let token = unsubscribe_token(&deliver_issue(&app).await);
+    // Act - RFC 8058 sends this body to the List-Unsubscribe link
+    let response = reqwest::Client::new()
+        .post(format!("{}/subscriptions/unsubscribe", &app.address))
+        .query(&[("token", &token)])
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body("List-Unsubscribe=One-Click")
+        .send()
+        .await
+        .unwrap();
+    // Assert
+    assert_eq!(200, response.status().as_u16());
+    assert_eq!("unsubscribed", subscriber_status(&app).await);
+}
+
+#[actix_rt::test]
+async fn tampered_tokens_are_rejected() {
+    // Arrange
+    let app = spawn_app().await;
+    create_confirmed_subscriber(&app).await;
+    let token = unsubscribe_token(&deliver_issue(&app).await);
+    let (_, signature) = token.split_once('.').unwrap();
+    let test_cases = vec![
+        (
+            format!("{}.{}", uuid::Uuid::new_v4(), signature),
+            "another subscriber",
+        ),
+        (format!("{}x", token), "a changed signature"),
+        ("garbage".to_string(), "garbage"),
+    ];
+    for (token, description) in test_cases {
+        // Act
+        let response = post_unsubscribe(&app, &token).await;
+        // Assert
+        assert_eq!(
+            400,
+            response.status().as_u16(),
+            "The API did not reject {}.",
+            description
+        );
+    }
+    assert_eq!("confirmed", subscriber_status(&app).await);
+}
+
+#[actix_rt::test]
+async fn an_old_confirmation_link_does_not_resubscribe() {
+    // Arrange
+    let app = spawn_app().await;
+    let confirmation_links = create_unconfirmed_subscriber(&app).await;
+    reqwest::get(confirmation_links.html.clone())
+        .await
+        .unwrap()
+        .error_for_status()
+        .unwrap();
+    let token = unsubscribe_token(&deliver_issue(&app).await);
+    post_unsubscribe(&app, &token).await;
+    // Act
+    reqwest::get(confirmation_links.html).await.unwrap();
+    // Assert
+    assert_eq!("unsubscribed", subscriber_status(&app).await);
+}
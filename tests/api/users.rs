@@ -0,0 +1,54 @@
+use crate::helpers::spawn_app;
+
+#[actix_rt::test]
+async fn adding_a_user_returns_400_for_invalid_data() {
+    // Arrange
+    let app = spawn_app().await;
+    let test_cases = vec![
+        (
+            serde_json::json!({"username": "another editor", "password": "long enough password"}),
+            "a username with a space",
+        ),
+        (
+            serde_json::json!({"username": "another-editor", "password": "short"}),
+            "a short password",
+        ),
+        (
+            serde_json::json!({"username": app.test_user.username, "password": "long enough password"}),
+            "a username that is taken",
+        ),
+    ];
+    for (body, description) in test_cases {
+        // Act
+        let response = app.post_users(&body).await;
+        // Assert
+        assert_eq!(
+            400,
+            response.status().as_u16(),
+            "The API did not fail with 400 Bad Request for {}.",
+            description
+        );
+    }
+}
+
+#[actix_rt::test]
+async fn only_the_admin_key_can_add_users() {
+    // Arrange
+    let app = spawn_app().await;
+    let body =
+        serde_json::json!({"username": "another-editor", "password": "long enough password"});
+    // Act
+    let response = reqwest::Client::new()
+        .post(format!("{}/admin/users", &app.address))
+        .header("X-Api-Key", &app.api_key)
+        .json(&body)
+        .send()
+        .await
+        .unwrap();
+    // Assert
+    assert_eq!(403, response.status().as_u16());
+    let response = app.post_users(&body).await;
+    assert_eq!(200, response.status().as_u16());
+    let user: serde_json::Value = response.json().await.unwrap();
+    assert_eq!("another-editor", user["username"]);
+}